@@ -0,0 +1,72 @@
+// benches/parallel_coverage.rs
+// 运行: cargo bench --features parallel --bench parallel_coverage
+use std::time::{Duration, Instant};
+
+use geosot::spatial::{spatial_analysis, GeoSotRegion};
+
+/// 卢森堡国界的简化多边形（经度, 纬度）
+const LUXEMBOURG: [(f64, f64); 8] = [
+    (5.73, 49.55),
+    (5.90, 49.45),
+    (6.36, 49.46),
+    (6.53, 49.81),
+    (6.13, 50.18),
+    (5.97, 50.17),
+    (5.75, 49.87),
+    (5.73, 49.55),
+];
+
+const LEVEL: usize = 20;
+
+fn time<T>(f: impl Fn() -> T) -> (Duration, T) {
+    let start = Instant::now();
+    let out = f();
+    (start.elapsed(), out)
+}
+
+fn main() {
+    let max_threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut threads = vec![1];
+    while threads.last().unwrap() * 2 <= max_threads {
+        threads.push(threads.last().unwrap() * 2);
+    }
+
+    // 平移后的多边形，用于集合运算
+    let shifted: Vec<(f64, f64)> = LUXEMBOURG.iter().map(|&(lng, lat)| (lng + 0.2, lat + 0.1)).collect();
+
+    println!("level {} 卢森堡多边形覆盖与集合运算", LEVEL);
+    println!("{:>8} {:>12} {:>12} {:>12} {:>8}", "threads", "cover", "union", "intersect", "speedup");
+
+    let mut baseline = None;
+    let mut reference: Option<(GeoSotRegion, GeoSotRegion)> = None;
+    for &n in &threads {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(n).build().unwrap();
+        pool.install(|| {
+            let (t_cover, region) = time(|| GeoSotRegion::from_polygon(&LUXEMBOURG, LEVEL));
+            let other = GeoSotRegion::from_polygon(&shifted, LEVEL);
+            let (t_union, union) = time(|| region.union(&other));
+            let (t_inter, inter) = time(|| region.intersection(&other));
+
+            // 各线程数下结果必须完全一致
+            match &reference {
+                Some((u, i)) => assert!(u == &union && i == &inter),
+                None => reference = Some((union, inter)),
+            }
+
+            let total = t_cover + t_union + t_inter;
+            let base = *baseline.get_or_insert(total);
+            println!(
+                "{:>8} {:>10.1}ms {:>10.1}ms {:>10.1}ms {:>7.2}x",
+                n,
+                t_cover.as_secs_f64() * 1e3,
+                t_union.as_secs_f64() * 1e3,
+                t_inter.as_secs_f64() * 1e3,
+                base.as_secs_f64() / total.as_secs_f64()
+            );
+        });
+    }
+
+    let (union, inter) = reference.unwrap();
+    println!("网格数: 并集 {} 交集 {}", union.size(), inter.size());
+    println!("Jaccard: {:.4}", spatial_analysis::jaccard_similarity(&union, &inter));
+}
@@ -6,7 +6,7 @@ fn main() {
     let y = 27.688;
     let z = 100;
     let level = 32;
-    let code = get_code(x, y, level.clone());
+    let code = get_code(x, y, level);
     // let code_3d = get_code_3d(x, y, z, level);
     
     // 32级
@@ -6,7 +6,6 @@ fn main() {
 
     // 创建测试区域
     let level = 20;
-    use geosot::spatial::GeoSotRegion;
 
     // 创建矩形区域
     let region = GeoSotRegion::from_rectangle(
@@ -86,7 +85,7 @@ fn main() {
 
     // 子集和超集关系
     let small_beijing = GeoSotRegion::from_codes(
-        beijing_region.codes.iter().take(2).cloned().collect(), 
+        beijing_region.codes.iter().take(2).collect(), 
         level
     );
     println!("小北京区域是否是北京区域的子集: {}", small_beijing.is_subset(&beijing_region));
@@ -0,0 +1,387 @@
+//! 按网格聚合点数据的统计量
+//!
+//! 每个网格保存数量、总和、最值以及均值与离差平方和（Welford 算法），
+//! 两组统计量可以按 Chan 等人的并行公式精确合并，因此细层级的结果可以
+//! 沿 `parent` 逐级汇总到粗层级，而不必重新读取原始数据。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::encode_points;
+use crate::spatial::{GeoSotCell, GeoSotRegion};
+
+/// 单个网格内数值的统计量
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellStats {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    /// 均值
+    pub mean: f64,
+    /// 离差平方和，方差为 `m2 / count`
+    pub m2: f64,
+}
+
+impl Default for CellStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+}
+
+impl CellStats {
+    /// 加入一个数值
+    pub fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// 合并另一组统计量，结果与把两组数值放在一起统计相同
+    pub fn merge(&mut self, other: &CellStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// 总体方差，空网格返回 NaN
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// 样本方差（自由度 n - 1），少于 2 个数值时返回 NaN
+    pub fn sample_variance(&self) -> f64 {
+        if self.count < 2 {
+            f64::NAN
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    /// 转为 JSON 对象，便于作为 GeoJSON 属性输出
+    pub fn to_json(&self) -> Value {
+        json!({
+            "count": self.count,
+            "sum": self.sum,
+            "mean": self.mean,
+            "min": self.min,
+            "max": self.max,
+            "variance": self.variance(),
+        })
+    }
+}
+
+/// 按网格聚合点数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoSotAggregator {
+    /// 聚合层级
+    pub level: usize,
+    cells: BTreeMap<u64, CellStats>,
+}
+
+impl Default for GeoSotAggregator {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl GeoSotAggregator {
+    /// 创建指定层级的空聚合器，层级限制在 1~32 之内
+    pub fn new(level: usize) -> Self {
+        Self { level: level.clamp(1, 32), cells: BTreeMap::new() }
+    }
+
+    /// 加入一个带数值的点
+    pub fn add(&mut self, lng: f64, lat: f64, value: f64) {
+        let code = crate::get_code(lng, lat, self.level);
+        self.cells.entry(code).or_default().add(value);
+    }
+
+    /// 批量加入 (lng, lat, value)，编码走批量路径（启用 `parallel` 特性时并行）
+    pub fn add_points(&mut self, points: &[(f64, f64, f64)]) {
+        let coords: Vec<(f64, f64)> = points.iter().map(|&(lng, lat, _)| (lng, lat)).collect();
+        for (code, &(_, _, value)) in encode_points(&coords, self.level).into_iter().zip(points) {
+            self.cells.entry(code).or_default().add(value);
+        }
+    }
+
+    /// 合并同层级的另一个聚合器，层级不同时 panic
+    pub fn merge(&mut self, other: &GeoSotAggregator) {
+        assert_eq!(self.level, other.level, "Cannot merge aggregators with different levels");
+        for (&code, stats) in &other.cells {
+            self.cells.entry(code).or_default().merge(stats);
+        }
+    }
+
+    /// 网格的统计量
+    pub fn get(&self, cell: &GeoSotCell) -> Option<&CellStats> {
+        if cell.level != self.level {
+            return None;
+        }
+        self.cells.get(&cell.code)
+    }
+
+    /// 非空网格数量
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// 是否没有任何数据
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// 按编码顺序遍历 (网格, 统计量)
+    pub fn iter(&self) -> impl Iterator<Item = (GeoSotCell, &CellStats)> + '_ {
+        self.cells.iter().map(|(&code, stats)| (GeoSotCell::new(code, self.level), stats))
+    }
+
+    /// 转为网格到统计量的映射
+    pub fn to_map(&self) -> BTreeMap<GeoSotCell, CellStats> {
+        self.iter().map(|(cell, stats)| (cell, *stats)).collect()
+    }
+
+    /// 汇总到更粗的层级：每个网格沿 `parent` 上溯后合并统计量
+    ///
+    /// `level` 不小于当前层级时返回副本。
+    pub fn roll_up(&self, level: usize) -> GeoSotAggregator {
+        let level = level.clamp(1, self.level.max(1));
+        let mut result = GeoSotAggregator::new(level);
+        for (mut cell, stats) in self.iter() {
+            while cell.level > level {
+                cell = cell.parent().expect("level above 1 has a parent");
+            }
+            result.cells.entry(cell.code).or_default().merge(stats);
+        }
+        result
+    }
+
+    /// 非空网格组成的区域
+    pub fn region(&self) -> GeoSotRegion {
+        GeoSotRegion::from_codes(self.cells.keys().copied().collect(), self.level)
+    }
+
+    /// 转为 GeoJSON FeatureCollection，统计量写入每个网格 Feature 的属性，可直接用于热力图
+    pub fn geojson_feature_collection(&self) -> Value {
+        let features: Vec<Value> = self
+            .iter()
+            .map(|(cell, stats)| {
+                let mut feature = cell.geojson_feature();
+                if let (Some(properties), Value::Object(values)) = (feature["properties"].as_object_mut(), stats.to_json()) {
+                    properties.extend(values);
+                }
+                feature
+            })
+            .collect();
+        json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+    }
+}
+
+/// Python 中的网格聚合器
+#[pyclass(name = "GeoSotAggregator")]
+pub struct PyGeoSotAggregator {
+    inner: GeoSotAggregator,
+}
+
+#[pymethods]
+impl PyGeoSotAggregator {
+    /// 创建指定层级的空聚合器，层级不在 1~32 之内时抛出 ValueError
+    #[new]
+    fn new(level: usize) -> PyResult<Self> {
+        if !(1..=32).contains(&level) {
+            return Err(PyValueError::new_err(format!("level must be between 1 and 32, got {level}")));
+        }
+        Ok(Self { inner: GeoSotAggregator::new(level) })
+    }
+
+    /// 聚合层级
+    #[getter]
+    fn level(&self) -> usize {
+        self.inner.level
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// 加入一个带数值的点
+    fn add(&mut self, lng: f64, lat: f64, value: f64) {
+        self.inner.add(lng, lat, value);
+    }
+
+    /// 批量加入 [(lng, lat, value)]
+    fn add_points(&mut self, points: Vec<(f64, f64, f64)>) {
+        self.inner.add_points(&points);
+    }
+
+    /// 汇总到更粗的层级
+    fn roll_up(&self, level: usize) -> Self {
+        Self { inner: self.inner.roll_up(level) }
+    }
+
+    /// 非空网格组成的区域
+    fn region(&self) -> GeoSotRegion {
+        self.inner.region()
+    }
+
+    /// {编码: {"count", "sum", "mean", "min", "max", "variance"}}，
+    /// 可直接交给 `pandas.DataFrame.from_dict(..., orient="index")`
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let result = PyDict::new(py);
+        for (cell, stats) in self.inner.iter() {
+            let item = PyDict::new(py);
+            item.set_item("count", stats.count)?;
+            item.set_item("sum", stats.sum)?;
+            item.set_item("mean", stats.mean)?;
+            item.set_item("min", stats.min)?;
+            item.set_item("max", stats.max)?;
+            item.set_item("variance", stats.variance())?;
+            result.set_item(cell.code, item)?;
+        }
+        Ok(result)
+    }
+
+    /// 带统计属性的 GeoJSON FeatureCollection 字符串
+    fn to_geojson(&self) -> String {
+        self.inner.geojson_feature_collection().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * (1.0 + a.abs().max(b.abs()))
+    }
+
+    fn sample_points() -> Vec<(f64, f64, f64)> {
+        (0..2000)
+            .map(|i| {
+                let t = i as f64;
+                (116.0 + (t * 0.618).fract() * 0.5, 39.0 + (t * 0.414).fract() * 0.5, (t * 0.37).sin() * 10.0 + 20.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cell_stats() {
+        let mut stats = CellStats::default();
+        for v in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.add(v);
+        }
+        assert_eq!((stats.count, stats.sum, stats.min, stats.max), (8, 40.0, 2.0, 9.0));
+        assert!(close(stats.mean, 5.0));
+        assert!(close(stats.variance(), 4.0));
+        assert!(close(stats.sample_variance(), 32.0 / 7.0));
+
+        let (mut a, mut b) = (CellStats::default(), CellStats::default());
+        for v in [2.0, 4.0, 4.0] {
+            a.add(v);
+        }
+        for v in [4.0, 5.0, 5.0, 7.0, 9.0] {
+            b.add(v);
+        }
+        a.merge(&b);
+        assert_eq!((a.count, a.min, a.max), (8, 2.0, 9.0));
+        assert!(close(a.mean, 5.0) && close(a.variance(), 4.0));
+
+        let empty = CellStats::default();
+        assert!(empty.variance().is_nan());
+        a.merge(&empty);
+        assert_eq!(a.count, 8);
+    }
+
+    #[test]
+    fn test_aggregate_and_roll_up() {
+        let points = sample_points();
+        let mut fine = GeoSotAggregator::new(18);
+        fine.add_points(&points);
+        assert_eq!(fine.iter().map(|(_, s)| s.count).sum::<u64>(), points.len() as u64);
+
+        let mut one_by_one = GeoSotAggregator::new(18);
+        for &(lng, lat, v) in &points {
+            one_by_one.add(lng, lat, v);
+        }
+        assert_eq!(one_by_one.len(), fine.len());
+
+        // 汇总到粗层级与直接在粗层级聚合一致
+        let mut direct = GeoSotAggregator::new(13);
+        direct.add_points(&points);
+        let rolled = fine.roll_up(13);
+        assert_eq!(rolled.len(), direct.len());
+        for (cell, expected) in direct.iter() {
+            let actual = rolled.get(&cell).unwrap();
+            assert_eq!((actual.count, actual.min, actual.max), (expected.count, expected.min, expected.max));
+            assert!(close(actual.sum, expected.sum) && close(actual.mean, expected.mean));
+            assert!(close(actual.variance(), expected.variance()));
+        }
+        assert_eq!(fine.roll_up(30), fine);
+    }
+
+    #[test]
+    fn test_level_is_clamped() {
+        assert_eq!(GeoSotAggregator::new(0).level, 1);
+        assert_eq!(GeoSotAggregator::new(40).level, 32);
+
+        let mut agg = GeoSotAggregator::default();
+        agg.add(116.39, 39.91, 1.0);
+        assert_eq!(agg.iter().next().unwrap().0.level, 1);
+    }
+
+    #[test]
+    fn test_merge_region_and_geojson() {
+        let points = sample_points();
+        let (left, right) = points.split_at(700);
+        let mut a = GeoSotAggregator::new(15);
+        a.add_points(left);
+        let mut b = GeoSotAggregator::new(15);
+        b.add_points(right);
+        a.merge(&b);
+        let mut all = GeoSotAggregator::new(15);
+        all.add_points(&points);
+        assert_eq!(a.len(), all.len());
+        assert_eq!(a.iter().map(|(_, s)| s.count).collect::<Vec<_>>(), all.iter().map(|(_, s)| s.count).collect::<Vec<_>>());
+
+        assert_eq!(all.region().size(), all.len());
+        let collection = all.geojson_feature_collection();
+        let feature = &collection["features"][0];
+        assert!(feature["properties"]["grid"].is_string());
+        assert!(feature["properties"]["count"].as_u64().unwrap() > 0);
+        assert!(all.get(&GeoSotCell::from_coords(0.0, 0.0, 15)).is_none());
+    }
+}
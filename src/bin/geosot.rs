@@ -0,0 +1,561 @@
+//! geosot 命令行工具
+//!
+//! 所有子命令默认从标准输入读取、向标准输出写出，每行一个网格，便于在 shell 管道中组合：
+//!
+//! ```text
+//! echo "116.3912 39.9075" | geosot encode --level 18 | geosot neighbors
+//! geosot cover --level 15 area.geojson > a.txt
+//! geosot setop intersection a.txt b.txt | geosot export --dissolve > overlap.geojson
+//! ```
+//!
+//! 网格既可以写成网格字符串（`G001310322-230230`，自带层级），也可以写成十进制编码，
+//! 此时层级由 `--level` 指定（默认 32）。
+
+use std::fs;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::process::ExitCode;
+
+use geosot::csv::{CsvEncoder, CsvError};
+use geosot::geometry::{Geometry, ParseError};
+use geosot::spatial::{GeoSotCell, GeoSotRegion};
+
+const USAGE: &str = "\
+用法: geosot <命令> [选项] [参数]
+
+命令:
+  encode [LNG LAT]            经纬度编码为网格，未给出坐标时逐行读取 \"lng lat\" 或 \"lng,lat\"
+  decode [CELL...]            输出网格的字符串、编码、层级、中心与范围（制表符分隔）
+  parent [CELL...]            父网格
+  children [CELL...]          子网格
+  neighbors [CELL...]         同层级相邻网格（最多 8 个）
+  cover [FILE]                覆盖 GeoJSON / WKT 几何，需要 --level
+  setop OP A B                集合运算，OP 为 intersection、union、difference、symmetric-difference
+  export [FILE]               网格列表转为 GeoJSON FeatureCollection
+  csv [FILE]                  为 CSV / TSV 点文件追加编码列，需要 --level（可重复）
+
+选项:
+  --level N                   编码层级，也是十进制编码输入的层级（默认 32）
+  --codes                     输出十进制编码而不是网格字符串
+  --dissolve                  export 时合并为轮廓
+  --lng NAME, --lat NAME      csv 的经纬度列名（默认 lng、lat）
+  --delimiter C, --tsv        csv 的分隔符（默认逗号）
+  --columns LIST              csv 追加的列：code、grid 或 code,grid（默认 code）
+  --skip-invalid              csv 中坐标不合法的行编码列留空而不是报错
+  -h, --help                  显示帮助
+
+FILE 为 - 或省略时读取标准输入。";
+
+/// 命令行错误，参数错误时同时输出用法
+#[derive(Debug)]
+enum Error {
+    Usage(String),
+    Io(io::Error),
+    Failed(String),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<CsvError> for Error {
+    fn from(err: CsvError) -> Self {
+        match err {
+            CsvError::Io(err) => Error::Io(err),
+            err => Error::Failed(err.to_string()),
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Failed(err.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// 解析后的命令行：子命令、位置参数与选项
+#[derive(Debug, Default)]
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    levels: Vec<usize>,
+    codes: bool,
+    dissolve: bool,
+    lng: Option<String>,
+    lat: Option<String>,
+    delimiter: Option<u8>,
+    columns: Option<String>,
+    skip_invalid: bool,
+    help: bool,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut parsed = Args::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            // 负数坐标（如 -73.98）与 - 都是位置参数
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "--codes" => parsed.codes = true,
+                "--dissolve" => parsed.dissolve = true,
+                "--skip-invalid" => parsed.skip_invalid = true,
+                "--tsv" => parsed.delimiter = Some(b'\t'),
+                "--level" | "--lng" | "--lat" | "--delimiter" | "--columns" => {
+                    let value = iter.next().ok_or_else(|| Error::Usage(format!("{arg} 需要一个参数")))?;
+                    parsed.set(arg, value)?;
+                }
+                flag if flag.starts_with("--") => match flag.split_once('=') {
+                    Some((name, value)) => parsed.set(name, value)?,
+                    None => return Err(Error::Usage(format!("未知选项 {flag}"))),
+                },
+                _ if parsed.command.is_empty() => parsed.command = arg.clone(),
+                _ => parsed.positional.push(arg.clone()),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// 带参数的选项
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            "--level" => self.levels.push(parse_level(value)?),
+            "--lng" => self.lng = Some(value.to_string()),
+            "--lat" => self.lat = Some(value.to_string()),
+            "--delimiter" => match value.as_bytes() {
+                [b'\\', b't'] => self.delimiter = Some(b'\t'),
+                &[c] => self.delimiter = Some(c),
+                _ => return Err(Error::Usage(format!("分隔符必须是单个 ASCII 字符: {value}"))),
+            },
+            "--columns" => self.columns = Some(value.to_string()),
+            _ => return Err(Error::Usage(format!("未知选项 {name}"))),
+        }
+        Ok(())
+    }
+
+    /// 十进制编码输入与编码输出的层级，多次指定时取第一个
+    fn level(&self) -> usize {
+        self.levels.first().copied().unwrap_or(32)
+    }
+
+    fn format(&self, cell: &GeoSotCell) -> String {
+        if self.codes {
+            cell.code.to_string()
+        } else {
+            cell.to_string()
+        }
+    }
+}
+
+fn parse_level(text: &str) -> Result<usize> {
+    match text.parse::<usize>() {
+        Ok(level) if (1..=32).contains(&level) => Ok(level),
+        _ => Err(Error::Usage(format!("层级必须是 1~32 的整数: {text}"))),
+    }
+}
+
+/// 解析网格字符串或十进制编码
+fn parse_cell(text: &str, level: usize) -> Result<GeoSotCell> {
+    if text.starts_with('G') {
+        GeoSotCell::from_grid_string(text).ok_or_else(|| Error::Failed(format!("无效的网格字符串: {text}")))
+    } else {
+        text.parse::<u64>()
+            .map(|code| GeoSotCell::new(code, level))
+            .map_err(|_| Error::Failed(format!("无效的网格: {text}")))
+    }
+}
+
+/// 打开输入，`-` 表示标准输入
+fn open_input<'a>(path: &str, stdin: &'a mut dyn BufRead) -> Result<Box<dyn BufRead + 'a>> {
+    if path == "-" {
+        Ok(Box::new(stdin))
+    } else {
+        let file = fs::File::open(path).map_err(|err| Error::Failed(format!("{path}: {err}")))?;
+        Ok(Box::new(io::BufReader::new(file)))
+    }
+}
+
+/// 读取文件内容，`-` 表示标准输入；只用于需要整体解析的几何文本
+fn read_input(path: &str, stdin: &mut dyn BufRead) -> Result<String> {
+    let mut text = String::new();
+    open_input(path, stdin)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// 逐行处理输入中非空、非注释（# 开头）的行，不把整个输入读入内存
+fn for_each_line(path: &str, stdin: &mut dyn BufRead, mut f: impl FnMut(&str) -> Result<()>) -> Result<()> {
+    for line in open_input(path, stdin)?.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            f(line)?;
+        }
+    }
+    Ok(())
+}
+
+/// 逐个处理位置参数中的网格，没有位置参数时逐行读取标准输入
+fn for_each_cell(args: &Args, stdin: &mut dyn BufRead, mut f: impl FnMut(GeoSotCell) -> Result<()>) -> Result<()> {
+    if !args.positional.is_empty() {
+        return args.positional.iter().try_for_each(|text| f(parse_cell(text, args.level())?));
+    }
+    for_each_line("-", stdin, |line| f(parse_cell(line, args.level())?))
+}
+
+/// 读取网格列表文件中的所有网格
+fn read_cells(path: &str, args: &Args, stdin: &mut dyn BufRead) -> Result<Vec<GeoSotCell>> {
+    let mut cells = Vec::new();
+    for_each_line(path, stdin, |line| {
+        cells.push(parse_cell(line, args.level())?);
+        Ok(())
+    })?;
+    Ok(cells)
+}
+
+/// 读取网格列表文件为区域，所有网格必须同一层级
+fn read_region(path: &str, args: &Args, stdin: &mut dyn BufRead) -> Result<GeoSotRegion> {
+    let cells = read_cells(path, args, stdin)?;
+    region_from_cells(&cells, args.level(), path)
+}
+
+fn region_from_cells(cells: &[GeoSotCell], level: usize, source: &str) -> Result<GeoSotRegion> {
+    let level = cells.first().map_or(level, |cell| cell.level);
+    if cells.iter().any(|cell| cell.level != level) {
+        return Err(Error::Failed(format!("{source}: 网格层级不一致")));
+    }
+    Ok(GeoSotRegion::from_codes(cells.iter().map(|cell| cell.code).collect(), level))
+}
+
+fn parse_lnglat(line: &str) -> Result<(f64, f64)> {
+    let parts: Vec<&str> = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()).collect();
+    match parts.as_slice() {
+        [lng, lat] => match (lng.parse::<f64>(), lat.parse::<f64>()) {
+            (Ok(lng), Ok(lat)) if (-180.0..=180.0).contains(&lng) && (-90.0..=90.0).contains(&lat) => Ok((lng, lat)),
+            _ => Err(Error::Failed(format!("无效的经纬度: {line}"))),
+        },
+        _ => Err(Error::Failed(format!("需要经度和纬度两个数值: {line}"))),
+    }
+}
+
+fn encode(args: &Args, stdin: &mut dyn BufRead, out: &mut dyn Write) -> Result<()> {
+    let mut write = |line: &str| {
+        let (lng, lat) = parse_lnglat(line)?;
+        writeln!(out, "{}", args.format(&GeoSotCell::from_coords(lng, lat, args.level())))?;
+        Ok(())
+    };
+    match args.positional.as_slice() {
+        [] => for_each_line("-", stdin, write),
+        [lng, lat] => write(&format!("{lng} {lat}")),
+        _ => Err(Error::Usage("encode 需要 LNG LAT 两个参数".into())),
+    }
+}
+
+fn decode(args: &Args, stdin: &mut dyn BufRead, out: &mut dyn Write) -> Result<()> {
+    for_each_cell(args, stdin, |cell| {
+        let (lng, lat) = cell.center();
+        let (min_lng, min_lat, max_lng, max_lat) = cell.bounds();
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{lng}\t{lat}\t{min_lng}\t{min_lat}\t{max_lng}\t{max_lat}",
+            cell, cell.code, cell.level
+        )?;
+        Ok(())
+    })
+}
+
+/// parent / children / neighbors：每个输入网格输出若干网格
+fn related(args: &Args, stdin: &mut dyn BufRead, out: &mut dyn Write, f: fn(&GeoSotCell) -> Vec<GeoSotCell>) -> Result<()> {
+    for_each_cell(args, stdin, |cell| {
+        for related in f(&cell) {
+            writeln!(out, "{}", args.format(&related))?;
+        }
+        Ok(())
+    })
+}
+
+fn cover(args: &Args, stdin: &mut dyn BufRead, out: &mut dyn Write) -> Result<()> {
+    let &[level] = args.levels.as_slice() else {
+        return Err(Error::Usage("cover 需要一个 --level".into()));
+    };
+    let path = match args.positional.as_slice() {
+        [] => "-",
+        [path] => path.as_str(),
+        _ => return Err(Error::Usage("cover 只接受一个输入文件".into())),
+    };
+    let text = read_input(path, stdin)?;
+    // 以 { 开头的视为 GeoJSON，其余按 WKT 解析
+    let geometry = if text.trim_start().starts_with('{') {
+        Geometry::from_geojson(&text)?
+    } else {
+        Geometry::from_wkt(&text)?
+    };
+    for cell in GeoSotRegion::cover_geometry(&geometry, level).cells() {
+        writeln!(out, "{}", args.format(&cell))?;
+    }
+    Ok(())
+}
+
+fn setop(args: &Args, stdin: &mut dyn BufRead, out: &mut dyn Write) -> Result<()> {
+    let [op, a, b] = args.positional.as_slice() else {
+        return Err(Error::Usage("setop 需要 OP A B 三个参数".into()));
+    };
+    if a == "-" && b == "-" {
+        return Err(Error::Usage("A 与 B 不能同时读取标准输入".into()));
+    }
+    let mut a = read_region(a, args, stdin)?;
+    let mut b = read_region(b, args, stdin)?;
+    // 空区域没有层级信息，沿用另一侧的层级
+    if a.is_empty() {
+        a.level = b.level;
+    } else if b.is_empty() {
+        b.level = a.level;
+    } else if a.level != b.level {
+        return Err(Error::Failed(format!("两个区域层级不同: {} 与 {}", a.level, b.level)));
+    }
+    let result = match op.as_str() {
+        "intersection" => a.intersection(&b),
+        "union" => a.union(&b),
+        "difference" => a.difference(&b),
+        "symmetric-difference" => a.symmetric_difference(&b),
+        _ => return Err(Error::Usage(format!("未知的集合运算: {op}"))),
+    };
+    for cell in result.cells() {
+        writeln!(out, "{}", args.format(&cell))?;
+    }
+    Ok(())
+}
+
+fn export(args: &Args, stdin: &mut dyn BufRead, out: &mut dyn Write) -> Result<()> {
+    let path = match args.positional.as_slice() {
+        [] => "-",
+        [path] => path.as_str(),
+        _ => return Err(Error::Usage("export 只接受一个输入文件".into())),
+    };
+    let cells = read_cells(path, args, stdin)?;
+    let collection = if args.dissolve {
+        region_from_cells(&cells, args.level(), path)?.geojson_feature_collection(true)
+    } else {
+        // 不合并时允许混合层级，每个网格一个 Feature
+        serde_json::json!({
+            "type": "FeatureCollection",
+            "features": cells.iter().map(GeoSotCell::geojson_feature).collect::<Vec<_>>(),
+        })
+    };
+    writeln!(out, "{collection}")?;
+    Ok(())
+}
+
+fn csv(args: &Args, stdin: &mut dyn BufRead, out: &mut dyn Write) -> Result<()> {
+    if args.levels.is_empty() {
+        return Err(Error::Usage("csv 需要 --level".into()));
+    }
+    let mut encoder = CsvEncoder::new(&args.levels);
+    if let Some(lng) = &args.lng {
+        encoder.lng_column = lng.clone();
+    }
+    if let Some(lat) = &args.lat {
+        encoder.lat_column = lat.clone();
+    }
+    if let Some(delimiter) = args.delimiter {
+        encoder.delimiter = delimiter;
+    }
+    if let Some(columns) = &args.columns {
+        encoder.code = false;
+        for column in columns.split(',') {
+            match column.trim() {
+                "code" => encoder.code = true,
+                "grid" => encoder.grid = true,
+                other => return Err(Error::Usage(format!("未知的列: {other}"))),
+            }
+        }
+    }
+    encoder.skip_invalid = args.skip_invalid;
+
+    let path = match args.positional.as_slice() {
+        [] => "-",
+        [path] => path.as_str(),
+        _ => return Err(Error::Usage("csv 只接受一个输入文件".into())),
+    };
+    let stats = encoder.encode(open_input(path, stdin)?, out)?;
+    if stats.invalid > 0 {
+        eprintln!("geosot: {} 行坐标不合法，编码列留空", stats.invalid);
+    }
+    Ok(())
+}
+
+fn run(args: &[String], stdin: &mut dyn BufRead, out: &mut dyn Write) -> Result<()> {
+    let args = Args::parse(args)?;
+    if args.help {
+        writeln!(out, "{USAGE}")?;
+        return Ok(());
+    }
+    match args.command.as_str() {
+        "encode" => encode(&args, stdin, out),
+        "decode" => decode(&args, stdin, out),
+        "parent" => related(&args, stdin, out, |cell| cell.parent().into_iter().collect()),
+        "children" => related(&args, stdin, out, GeoSotCell::children),
+        "neighbors" => related(&args, stdin, out, GeoSotCell::neighbors),
+        "cover" => cover(&args, stdin, out),
+        "setop" => setop(&args, stdin, out),
+        "export" => export(&args, stdin, out),
+        "csv" => csv(&args, stdin, out),
+        "" => Err(Error::Usage("缺少命令".into())),
+        command => Err(Error::Usage(format!("未知命令: {command}"))),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let result = run(&args, &mut io::stdin().lock(), &mut out).and_then(|()| Ok(out.flush()?));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        // 下游（如 head）提前关闭管道时静默退出
+        Err(Error::Io(err)) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(Error::Io(err)) => {
+            eprintln!("geosot: {err}");
+            ExitCode::FAILURE
+        }
+        Err(Error::Failed(message)) => {
+            eprintln!("geosot: {message}");
+            ExitCode::FAILURE
+        }
+        Err(Error::Usage(message)) => {
+            eprintln!("geosot: {message}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn run_with(args: &[&str], input: &str) -> Result<String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut out = Vec::new();
+        run(&args, &mut Cursor::new(input.as_bytes()), &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let expected = GeoSotCell::from_coords(116.3912, 39.9075, 18).to_string();
+        assert_eq!(run_with(&["encode", "--level", "18", "116.3912", "39.9075"], "").unwrap(), format!("{expected}\n"));
+        let lines = run_with(&["encode", "--level=18"], "116.3912 39.9075\n# 注释\n\n-73.98,40.75\n").unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        assert_eq!(lines.lines().next(), Some(expected.as_str()));
+
+        let code = GeoSotCell::from_coords(116.3912, 39.9075, 18).code.to_string();
+        assert_eq!(run_with(&["encode", "--level", "18", "--codes", "116.3912", "39.9075"], "").unwrap(), format!("{code}\n"));
+
+        // 网格字符串与十进制编码解码结果一致
+        let decoded = run_with(&["decode", &expected], "").unwrap();
+        assert_eq!(run_with(&["decode", "--level", "18"], &code).unwrap(), decoded);
+        let fields: Vec<&str> = decoded.trim_end().split('\t').collect();
+        assert_eq!(fields.len(), 9);
+        assert_eq!((fields[0], fields[1], fields[2]), (expected.as_str(), code.as_str(), "18"));
+    }
+
+    /// 读完给定内容后报错的输入，用于确认命令逐行处理而不是先读完整个输入
+    struct FailAfter<'a>(&'a [u8]);
+
+    impl io::Read for FailAfter<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("input interrupted"));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn test_streaming_input() {
+        for command in [&["encode", "--level", "18"][..], &["decode", "--level", "18"], &["parent"]] {
+            let input = if command[0] == "encode" {
+                "116.3912 39.9075\n".to_string()
+            } else {
+                format!("{}\n", GeoSotCell::from_coords(116.3912, 39.9075, 18))
+            };
+            let args: Vec<String> = command.iter().map(|s| s.to_string()).collect();
+            let mut out = Vec::new();
+            let mut stdin = io::BufReader::new(FailAfter(input.as_bytes()));
+            assert!(matches!(run(&args, &mut stdin, &mut out), Err(Error::Io(_))));
+            assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1, "{command:?}");
+        }
+    }
+
+    #[test]
+    fn test_hierarchy_commands() {
+        let cell = GeoSotCell::from_coords(116.3912, 39.9075, 18);
+        let parent = run_with(&["parent"], &cell.to_string()).unwrap();
+        assert_eq!(parent.trim(), cell.parent().unwrap().to_string());
+        let children = run_with(&["children", &cell.to_string()], "").unwrap();
+        assert_eq!(children.lines().count(), 4);
+        let neighbors = run_with(&["neighbors", &cell.to_string()], "").unwrap();
+        assert_eq!(neighbors.lines().count(), 8);
+    }
+
+    #[test]
+    fn test_cover_setop_export() {
+        let dir = std::env::temp_dir().join(format!("geosot-cli-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.txt");
+        let b = dir.join("b.txt");
+        fs::write(&a, run_with(&["cover", "--level", "15"], "POLYGON((116 39, 116.1 39, 116.1 39.1, 116 39.1, 116 39))").unwrap()).unwrap();
+        let geojson = r#"{"type":"Polygon","coordinates":[[[116.05,39],[116.15,39],[116.15,39.1],[116.05,39.1],[116.05,39]]]}"#;
+        fs::write(&b, run_with(&["cover", "--level", "15", "-"], geojson).unwrap()).unwrap();
+
+        let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+        assert_eq!(run_with(&["setop", "intersection", a, b], "").unwrap().lines().count(), 18);
+        assert_eq!(run_with(&["setop", "union", a, b], "").unwrap().lines().count(), 54);
+        assert_eq!(run_with(&["setop", "difference", a, "-"], "").unwrap().lines().count(), 36);
+
+        let overlap = run_with(&["setop", "intersection", a, b], "").unwrap();
+        let collection: serde_json::Value = serde_json::from_str(&run_with(&["export", "--dissolve"], &overlap).unwrap()).unwrap();
+        assert_eq!(collection["features"].as_array().unwrap().len(), 1);
+        let collection: serde_json::Value = serde_json::from_str(&run_with(&["export"], &overlap).unwrap()).unwrap();
+        assert_eq!(collection["features"].as_array().unwrap().len(), 18);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(run_with(&[], ""), Err(Error::Usage(_))));
+        assert!(matches!(run_with(&["frobnicate"], ""), Err(Error::Usage(_))));
+        assert!(matches!(run_with(&["cover"], "POINT(1 2)"), Err(Error::Usage(_))));
+        assert!(matches!(run_with(&["encode", "--level", "40", "1", "2"], ""), Err(Error::Usage(_))));
+        assert!(matches!(run_with(&["decode", "G9"], ""), Err(Error::Failed(_))));
+        match run_with(&["cover", "--level", "10"], "POLYGON((1 2, 3") {
+            Err(Error::Failed(message)) => assert!(message.starts_with("line 1, column")),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(run_with(&["--help"], "").unwrap().contains("setop"));
+    }
+
+    #[test]
+    fn test_csv() {
+        let input = "id\tlon\tlat\n1\t116.3912\t39.9075\n2\t\t\n";
+        let output = run_with(
+            &["csv", "--tsv", "--lng", "lon", "--level", "10", "--level=18", "--columns", "code,grid", "--skip-invalid"],
+            input,
+        )
+        .unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "id\tlon\tlat\tgeosot_code_10\tgeosot_grid_10\tgeosot_code_18\tgeosot_grid_18");
+        let cell = GeoSotCell::from_coords(116.3912, 39.9075, 18);
+        assert!(lines[1].ends_with(&format!("\t{}\t{cell}", cell.code)));
+        assert_eq!(lines[2], "2\t\t\t\t\t\t");
+
+        assert!(matches!(run_with(&["csv"], input), Err(Error::Usage(_))));
+        match run_with(&["csv", "--level", "18"], "lng,lat\n1,x\n") {
+            Err(Error::Failed(message)) => assert_eq!(message, "line 2: invalid latitude 'x'"),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
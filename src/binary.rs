@@ -0,0 +1,460 @@
+//! 区域的紧凑二进制格式
+//!
+//! ```text
+//! 偏移  长度    内容
+//! 0     4       魔数 "GSOT"
+//! 4     1       版本号，当前为 1
+//! 5     1       标志位，bit0 = 混合层级
+//! 6     1       精度等级（混合层级时为 0）
+//! 7     1       移位量：所有编码共同的低位 0 位数，写入前右移去掉
+//! 8     varint  编码个数
+//! ...   varint  升序编码（右移后）的差分，第一个为原值；混合层级时每个差分后跟 1 字节层级
+//! 末尾  4       之前全部字节的 CRC-32（IEEE），小端
+//! ```
+//!
+//! 同一层级的编码低位全为 0，右移后连片网格的编码相邻，差分大多只占 1 字节。
+//! [`RegionReader`] 在构造时一次性校验整个缓冲区，之后直接在借用的字节上迭代，不做复制。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::PyErr;
+use std::fmt;
+
+use crate::spatial::{level_mask, GeoSotCell, GeoSotRegion};
+
+/// 魔数
+pub const MAGIC: [u8; 4] = *b"GSOT";
+/// 当前格式版本
+pub const VERSION: u8 = 1;
+/// 标志位：编码属于不同层级
+pub const FLAG_MIXED_LEVELS: u8 = 0b0000_0001;
+
+const HEADER_LEN: usize = 8;
+const CHECKSUM_LEN: usize = 4;
+
+/// 二进制数据解码错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// 数据长度不足
+    Truncated,
+    /// 魔数不匹配
+    BadMagic,
+    /// 不支持的版本号
+    UnsupportedVersion(u8),
+    /// 精度等级不在 [1~32]
+    InvalidLevel(u8),
+    /// 校验和不匹配
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// 数据内容不合法（varint 溢出、编码越界、编码未对齐到层级、多余字节等）
+    Corrupt(&'static str),
+    /// 混合层级的数据无法放入单一层级的区域
+    MixedLevels,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated => write!(f, "unexpected end of data"),
+            DecodeError::BadMagic => write!(f, "not a GeoSOT region (bad magic)"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported format version {v}"),
+            DecodeError::InvalidLevel(l) => write!(f, "invalid level {l}"),
+            DecodeError::ChecksumMismatch { expected, actual } => {
+                write!(f, "checksum mismatch: expected {expected:#010x}, got {actual:#010x}")
+            }
+            DecodeError::Corrupt(reason) => write!(f, "corrupt data: {reason}"),
+            DecodeError::MixedLevels => write!(f, "data contains mixed levels"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecodeError> for PyErr {
+    fn from(err: DecodeError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// 二进制编码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// 精度等级不在 [1~32]，无法放入一个字节
+    InvalidLevel(usize),
+    /// 编码在层级之外的低位不为 0，写出的数据无法解码
+    Unaligned { code: u64, level: usize },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::InvalidLevel(l) => write!(f, "cannot encode invalid level {l}"),
+            EncodeError::Unaligned { code, level } => write!(f, "code {code} is not aligned to level {level}"),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+impl From<EncodeError> for PyErr {
+    fn from(err: EncodeError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// 检查层级能放入一个字节、编码对齐到层级
+fn check_entry(code: u64, level: usize) -> Result<(), EncodeError> {
+    if !(1..=32).contains(&level) {
+        return Err(EncodeError::InvalidLevel(level));
+    }
+    if code & !level_mask(level) != 0 {
+        return Err(EncodeError::Unaligned { code, level });
+    }
+    Ok(())
+}
+
+/// CRC-32（IEEE 802.3，反射多项式 0xEDB88320）查找表
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// 计算 CRC-32
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| CRC_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, DecodeError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or(DecodeError::Truncated)?;
+        *pos += 1;
+        if shift == 63 && byte > 1 {
+            return Err(DecodeError::Corrupt("varint overflows u64"));
+        }
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DecodeError::Corrupt("varint overflows u64"))
+}
+
+/// 所有编码共同的低位 0 位数，不超过 `max`
+fn common_shift(codes: impl Iterator<Item = u64>, max: u32) -> u32 {
+    let bits = codes.fold(0u64, |acc, code| acc | code);
+    if bits == 0 {
+        max
+    } else {
+        bits.trailing_zeros().min(max)
+    }
+}
+
+/// 写入一组已排序的条目
+fn encode(level: u8, flags: u8, shift: u32, len: usize, entries: impl Iterator<Item = (u64, Option<u8>)>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + len * 2 + CHECKSUM_LEN + 10);
+    out.extend_from_slice(&MAGIC);
+    // common_shift 的结果不超过 63，可以放入一个字节
+    out.extend_from_slice(&[VERSION, flags, level, shift as u8]);
+    write_varint(&mut out, len as u64);
+
+    let mut prev = 0u64;
+    for (code, level) in entries {
+        let value = code >> shift;
+        write_varint(&mut out, value - prev);
+        prev = value;
+        if let Some(level) = level {
+            out.push(level);
+        }
+    }
+
+    let checksum = crc32(&out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    out
+}
+
+/// 将一组可能处于不同层级的网格编码为二进制，网格按 (编码, 层级) 排序并去重
+///
+/// 任一网格的层级不在 [1~32] 或编码未对齐到层级时返回错误。
+pub fn encode_cells(cells: &[GeoSotCell]) -> Result<Vec<u8>, EncodeError> {
+    for cell in cells {
+        check_entry(cell.code, cell.level)?;
+    }
+    let mut cells = cells.to_vec();
+    cells.sort_unstable();
+    cells.dedup();
+    let shift = common_shift(cells.iter().map(|c| c.code), 63);
+    Ok(encode(0, FLAG_MIXED_LEVELS, shift, cells.len(), cells.iter().map(|c| (c.code, Some(c.level as u8)))))
+}
+
+/// 已校验的二进制区域的只读视图，直接借用输入字节
+#[derive(Debug, Clone, Copy)]
+pub struct RegionReader<'a> {
+    level: u8,
+    flags: u8,
+    shift: u32,
+    len: usize,
+    /// 条目部分（不含头部、个数与校验和）
+    entries: &'a [u8],
+}
+
+impl<'a> RegionReader<'a> {
+    /// 解析并校验数据：魔数、版本、校验和以及全部条目（包括编码是否对齐到层级）
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        if bytes.len() < HEADER_LEN + 1 + CHECKSUM_LEN {
+            return Err(if bytes.len() >= 4 && bytes[..4] != MAGIC { DecodeError::BadMagic } else { DecodeError::Truncated });
+        }
+        if bytes[..4] != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        if bytes[4] != VERSION {
+            return Err(DecodeError::UnsupportedVersion(bytes[4]));
+        }
+
+        let (body, tail) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        let expected = u32::from_le_bytes(tail.try_into().unwrap());
+        let actual = crc32(body);
+        if expected != actual {
+            return Err(DecodeError::ChecksumMismatch { expected, actual });
+        }
+
+        let (flags, level, shift) = (body[5], body[6], body[7] as u32);
+        let mixed = flags & FLAG_MIXED_LEVELS != 0;
+        if flags & !FLAG_MIXED_LEVELS != 0 {
+            return Err(DecodeError::Corrupt("unknown flags"));
+        }
+        if !mixed && !(1..=32).contains(&level) {
+            return Err(DecodeError::InvalidLevel(level));
+        }
+        if shift > 63 {
+            return Err(DecodeError::Corrupt("shift out of range"));
+        }
+
+        let mut pos = HEADER_LEN;
+        let len = read_varint(body, &mut pos)?;
+        let start = pos;
+
+        // 预先走一遍条目，保证迭代时不会失败
+        let mut value = 0u64;
+        for _ in 0..len {
+            let delta = read_varint(body, &mut pos)?;
+            value = value.checked_add(delta).ok_or(DecodeError::Corrupt("code overflows u64"))?;
+            if value.leading_zeros() < shift {
+                return Err(DecodeError::Corrupt("code overflows u64"));
+            }
+            let level = if mixed {
+                let level = *body.get(pos).ok_or(DecodeError::Truncated)?;
+                if !(1..=32).contains(&level) {
+                    return Err(DecodeError::InvalidLevel(level));
+                }
+                pos += 1;
+                level
+            } else {
+                level
+            };
+            if (value << shift) & !level_mask(level as usize) != 0 {
+                return Err(DecodeError::Corrupt("code is not aligned to its level"));
+            }
+        }
+        if pos != body.len() {
+            return Err(DecodeError::Corrupt("trailing bytes after entries"));
+        }
+
+        Ok(Self { level, flags, shift, len: len as usize, entries: &body[start..] })
+    }
+
+    /// 是否包含不同层级的编码
+    pub fn is_mixed(&self) -> bool {
+        self.flags & FLAG_MIXED_LEVELS != 0
+    }
+
+    /// 精度等级，混合层级时返回 None
+    pub fn level(&self) -> Option<usize> {
+        (!self.is_mixed()).then_some(self.level as usize)
+    }
+
+    /// 编码个数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 按升序迭代全部网格
+    pub fn cells(&self) -> Cells<'a> {
+        Cells { reader: *self, pos: 0, value: 0, remaining: self.len }
+    }
+}
+
+/// [`RegionReader::cells`] 返回的迭代器
+#[derive(Debug, Clone)]
+pub struct Cells<'a> {
+    reader: RegionReader<'a>,
+    pos: usize,
+    value: u64,
+    remaining: usize,
+}
+
+impl Iterator for Cells<'_> {
+    type Item = GeoSotCell;
+
+    fn next(&mut self) -> Option<GeoSotCell> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let entries = self.reader.entries;
+        // 构造时已校验，这里不会出错
+        self.value += read_varint(entries, &mut self.pos).ok()?;
+        let level = if self.reader.is_mixed() {
+            self.pos += 1;
+            entries[self.pos - 1]
+        } else {
+            self.reader.level
+        };
+        Some(GeoSotCell::new(self.value << self.reader.shift, level as usize))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Cells<'_> {}
+
+impl GeoSotRegion {
+    /// 编码为紧凑二进制格式
+    ///
+    /// `level` 不在 [1~32] 或有编码未对齐到层级时返回错误，避免写出无法解码的数据。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        if !(1..=32).contains(&self.level) {
+            return Err(EncodeError::InvalidLevel(self.level));
+        }
+        for code in self.codes.iter() {
+            check_entry(code, self.level)?;
+        }
+        let max_shift = (32 - self.level as u32) * 2;
+        let shift = common_shift(self.codes.iter(), max_shift);
+        Ok(encode(self.level as u8, 0, shift, self.codes.len(), self.codes.iter().map(|code| (code, None))))
+    }
+
+    /// 从紧凑二进制格式解码，存储方式为默认的 BTree
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let reader = RegionReader::new(bytes)?;
+        let level = reader.level().ok_or(DecodeError::MixedLevels)?;
+        let mut region = GeoSotRegion::new(level);
+        region.codes.extend(reader.cells().map(|cell| cell.code));
+        Ok(region)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::RegionStorage;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_round_trip_is_compact() {
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.2, 39.2, 18);
+        let bytes = region.to_bytes().unwrap();
+        assert_eq!(GeoSotRegion::from_bytes(&bytes).unwrap(), region);
+
+        let json = format!("{:?}", region.codes.iter().collect::<Vec<_>>());
+        assert!(bytes.len() * 10 < json.len(), "{} vs {}", bytes.len(), json.len());
+
+        let bitmap = region.to_storage(RegionStorage::Bitmap);
+        assert_eq!(bitmap.to_bytes().unwrap(), bytes);
+
+        let empty = GeoSotRegion::new(20);
+        assert_eq!(GeoSotRegion::from_bytes(&empty.to_bytes().unwrap()).unwrap(), empty);
+    }
+
+    #[test]
+    fn test_unaligned_and_extreme_codes() {
+        let region = GeoSotRegion::from_codes(vec![0, 1, 3, u64::MAX], 32);
+        assert_eq!(GeoSotRegion::from_bytes(&region.to_bytes().unwrap()).unwrap(), region);
+
+        let region = GeoSotRegion::from_codes(vec![5 << 44, u64::MAX << 44], 10);
+        assert_eq!(GeoSotRegion::from_bytes(&region.to_bytes().unwrap()).unwrap(), region);
+        let region = GeoSotRegion::from_codes(vec![5 << 40], 10);
+        assert_eq!(region.to_bytes(), Err(EncodeError::Unaligned { code: 5 << 40, level: 10 }));
+        assert!(encode_cells(&[GeoSotCell::new(1, 31)]).is_err());
+
+        // 校验和正确但编码带有层级之外的低位
+        let unaligned = encode(10, 0, 0, 1, [(5 << 40, None)].into_iter());
+        assert_eq!(RegionReader::new(&unaligned).unwrap_err(), DecodeError::Corrupt("code is not aligned to its level"));
+        let unaligned = encode(0, FLAG_MIXED_LEVELS, 0, 1, [(1, Some(31))].into_iter());
+        assert!(matches!(RegionReader::new(&unaligned), Err(DecodeError::Corrupt(_))));
+    }
+
+    #[test]
+    fn test_invalid_level() {
+        // 276 截断为一个字节后是合法的 20 级，必须在编码前拒绝
+        let mut region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.01, 39.01, 20);
+        region.level = 276;
+        assert_eq!(region.to_bytes(), Err(EncodeError::InvalidLevel(276)));
+        assert_eq!(encode_cells(&[GeoSotCell::new(0, 0)]), Err(EncodeError::InvalidLevel(0)));
+    }
+
+    #[test]
+    fn test_mixed_levels() {
+        let cells = vec![
+            GeoSotCell::from_coords(116.0, 39.0, 20),
+            GeoSotCell::from_coords(116.0, 39.0, 10),
+            GeoSotCell::from_coords(-73.0, 40.0, 15),
+        ];
+        let bytes = encode_cells(&cells).unwrap();
+        let reader = RegionReader::new(&bytes).unwrap();
+        assert!(reader.is_mixed());
+        assert_eq!(reader.level(), None);
+
+        let mut expected = cells.clone();
+        expected.sort();
+        assert_eq!(reader.cells().collect::<Vec<_>>(), expected);
+        assert_eq!(GeoSotRegion::from_bytes(&bytes), Err(DecodeError::MixedLevels));
+    }
+
+    #[test]
+    fn test_corrupt_data() {
+        let bytes = GeoSotRegion::from_rectangle(116.0, 39.0, 116.01, 39.01, 20).to_bytes().unwrap();
+
+        let mut flipped = bytes.clone();
+        flipped[10] ^= 0x01;
+        assert!(matches!(RegionReader::new(&flipped), Err(DecodeError::ChecksumMismatch { .. })));
+
+        let mut magic = bytes.clone();
+        magic[0] = b'X';
+        assert_eq!(RegionReader::new(&magic).unwrap_err(), DecodeError::BadMagic);
+
+        let mut version = bytes.clone();
+        version[4] = 9;
+        assert_eq!(RegionReader::new(&version).unwrap_err(), DecodeError::UnsupportedVersion(9));
+
+        assert_eq!(RegionReader::new(&bytes[..6]).unwrap_err(), DecodeError::Truncated);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::geometry::{Coord, Geometry, ParseError};
+use crate::magic_bits;
+use crate::spatial::GeoSotRegion;
+use crate::storage::{CodeSet, RegionStorage};
+use crate::utils::{axis_cells, axis_code_at, TICKS_PER_DEGREE};
+
+/// 将几何覆盖为指定层级的 GeoSOT 网格
+///
+/// 结果包含与几何相交的全部网格：点取所在网格，线取经过的网格，
+/// 面取与面（扣除洞）相交的网格。计算在刻度坐标下逐行进行，
+/// 分、秒字段截断处的网格也不会遗漏。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coverer {
+    /// 精度等级 [1~32]
+    pub level: usize,
+    /// 结果区域的存储方式
+    pub storage: RegionStorage,
+}
+
+/// 刻度坐标
+type TickCoord = (f64, f64);
+
+impl Coverer {
+    /// 创建指定层级的覆盖器
+    pub fn new(level: usize) -> Self {
+        Self { level, storage: RegionStorage::default() }
+    }
+
+    /// 覆盖几何，返回 GeoSOT 区域
+    pub fn cover(&self, geometry: &Geometry) -> GeoSotRegion {
+        let mut codes = CodeSet::new(self.storage, self.level);
+        self.cover_into(geometry, &mut codes);
+        GeoSotRegion { codes, level: self.level }
+    }
+
+    fn cover_into(&self, geometry: &Geometry, codes: &mut CodeSet) {
+        match geometry {
+            Geometry::Point(p) => self.cover_point(*p, codes),
+            Geometry::MultiPoint(points) => points.iter().for_each(|&p| self.cover_point(p, codes)),
+            Geometry::LineString(line) => self.cover_line(line, codes),
+            Geometry::MultiLineString(lines) => lines.iter().for_each(|l| self.cover_line(l, codes)),
+            Geometry::Polygon(rings) => self.cover_polygon(rings, codes),
+            Geometry::MultiPolygon(polygons) => polygons.iter().for_each(|p| self.cover_polygon(p, codes)),
+            Geometry::GeometryCollection(items) => items.iter().for_each(|g| self.cover_into(g, codes)),
+        }
+    }
+
+    fn cover_point(&self, p: Coord, codes: &mut CodeSet) {
+        let (x, y) = to_ticks(p);
+        let lng = axis_code_at(x.floor() as i64, self.level);
+        let lat = axis_code_at(y.floor() as i64, self.level);
+        codes.insert(magic_bits(lng, lat));
+    }
+
+    fn cover_line(&self, line: &[Coord], codes: &mut CodeSet) {
+        let line: Vec<TickCoord> = line.iter().map(|&p| to_ticks(p)).collect();
+        match line.len() {
+            0 => {}
+            1 => self.cover_point(to_degrees(line[0]), codes),
+            _ => line.windows(2).for_each(|w| self.cover_segment(w[0], w[1], false, codes)),
+        }
+    }
+
+    fn cover_polygon(&self, rings: &[Vec<Coord>], codes: &mut CodeSet) {
+        let rings: Vec<Vec<TickCoord>> = rings
+            .iter()
+            .filter(|r| !r.is_empty())
+            .map(|r| {
+                let mut ring: Vec<TickCoord> = r.iter().map(|&p| to_ticks(p)).collect();
+                if ring.first() != ring.last() {
+                    ring.push(ring[0]);
+                }
+                ring
+            })
+            .collect();
+        if rings.is_empty() {
+            return;
+        }
+
+        // 边界穿过内部的网格；恰好落在网格边线上的边界不会带入外侧网格
+        for ring in &rings {
+            for w in ring.windows(2) {
+                self.cover_segment(w[0], w[1], true, codes);
+            }
+        }
+
+        // 内部网格：在每一行的中线上按奇偶规则求交点区间
+        let min_y = rings[0].iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_y = rings[0].iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        for (lat, lo, hi) in axis_cells(min_y.floor() as i64, max_y.ceil() as i64, self.level) {
+            let yc = (lo + hi) as f64 / 2.0;
+            let mut xs: Vec<f64> = Vec::new();
+            for ring in &rings {
+                for w in ring.windows(2) {
+                    let (a, b) = (w[0], w[1]);
+                    if (a.1 <= yc && yc < b.1) || (b.1 <= yc && yc < a.1) {
+                        xs.push(a.0 + (yc - a.1) * (b.0 - a.0) / (b.1 - a.1));
+                    }
+                }
+            }
+            xs.sort_by(|a, b| a.total_cmp(b));
+            for pair in xs.chunks_exact(2) {
+                self.cover_row(lat, pair[0], pair[1], true, codes);
+            }
+        }
+    }
+
+    /// 线段经过的网格：按行裁剪线段，再取该行内线段覆盖的经度区间
+    ///
+    /// `open` 为 true 时只取线段穿过其内部的网格（用于面的边界），
+    /// 否则按半开区间取线段上每个点所在的网格（用于线）。
+    fn cover_segment(&self, a: TickCoord, b: TickCoord, open: bool, codes: &mut CodeSet) {
+        let (y0, y1) = (a.1.min(b.1), a.1.max(b.1));
+        for (lat, lo, hi) in axis_cells(y0.floor() as i64, y1.floor() as i64 + 1, self.level) {
+            let (lo, hi) = (lo as f64, hi as f64);
+            let in_row = if open { lo < y1 && hi > y0 && (y0 < y1 || lo < y0) } else { hi > y0 && lo <= y1 };
+            if !in_row {
+                continue;
+            }
+            let (xa, xb) = if a.1 == b.1 {
+                (a.0, b.0)
+            } else {
+                let x_at = |y: f64| a.0 + (y.clamp(y0, y1) - a.1) * (b.0 - a.0) / (b.1 - a.1);
+                (x_at(lo), x_at(hi))
+            };
+            self.cover_row(lat, xa.min(xb), xa.max(xb), open, codes);
+        }
+    }
+
+    /// 将某一行中覆盖经度刻度区间 [x0, x1] 的网格加入结果
+    ///
+    /// `open` 为 true 时只取与开区间相交的网格，仅在边线上接触的网格不计入。
+    fn cover_row(&self, lat: u32, x0: f64, x1: f64, open: bool, codes: &mut CodeSet) {
+        for (lng, lo, hi) in axis_cells(x0.floor() as i64, x1.floor() as i64 + 1, self.level) {
+            let (lo, hi) = (lo as f64, hi as f64);
+            let hit = if open { lo < x1 && hi > x0 && (x0 < x1 || lo < x0) } else { hi > x0 && lo <= x1 };
+            if hit {
+                codes.insert(magic_bits(lng, lat));
+            }
+        }
+    }
+}
+
+fn to_ticks((lng, lat): Coord) -> TickCoord {
+    let tpd = TICKS_PER_DEGREE as f64;
+    (lng.clamp(-180.0, 180.0) * tpd, lat.clamp(-90.0, 90.0) * tpd)
+}
+
+fn to_degrees((x, y): TickCoord) -> Coord {
+    let tpd = TICKS_PER_DEGREE as f64;
+    (x / tpd, y / tpd)
+}
+
+impl GeoSotRegion {
+    /// 覆盖任意几何，返回指定层级的区域
+    pub fn cover_geometry(geometry: &Geometry, level: usize) -> Self {
+        Coverer::new(level).cover(geometry)
+    }
+
+    /// 解析 GeoJSON 文本（几何、Feature 或 FeatureCollection）并覆盖为区域
+    pub fn from_geojson(text: &str, level: usize) -> Result<Self, ParseError> {
+        Ok(Self::cover_geometry(&Geometry::from_geojson(text)?, level))
+    }
+
+    /// 解析 WKT 文本并覆盖为区域
+    pub fn from_wkt(text: &str, level: usize) -> Result<Self, ParseError> {
+        Ok(Self::cover_geometry(&Geometry::from_wkt(text)?, level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_code;
+
+    #[test]
+    fn test_cover_point() {
+        let region = GeoSotRegion::cover_geometry(&Geometry::Point((116.0, 39.0)), 20);
+        assert_eq!(region.size(), 1);
+        assert!(region.contains(get_code(116.0, 39.0, 20)));
+
+        let region = GeoSotRegion::cover_geometry(&Geometry::Point((-73.5, -33.25)), 20);
+        assert!(region.contains(get_code(-73.5, -33.25, 20)));
+    }
+
+    #[test]
+    fn test_cover_rectangle_polygon() {
+        // 15 级网格为 1 分，0.5 度见方的矩形恰好覆盖 30x30 个网格
+        let ring = vec![(116.0, 39.0), (116.5, 39.0), (116.5, 39.5), (116.0, 39.5), (116.0, 39.0)];
+        let region = GeoSotRegion::cover_geometry(&Geometry::Polygon(vec![ring]), 15);
+        assert_eq!(region.size(), 30 * 30);
+        assert!(region.contains_point(116.25, 39.25));
+        assert!(!region.contains_point(116.6, 39.25));
+    }
+
+    #[test]
+    fn test_cover_polygon_with_hole() {
+        let outer = vec![(116.0, 39.0), (116.5, 39.0), (116.5, 39.5), (116.0, 39.5)];
+        let hole = vec![(116.1, 39.1), (116.1, 39.4), (116.4, 39.4), (116.4, 39.1)];
+        let region = GeoSotRegion::cover_geometry(&Geometry::Polygon(vec![outer, hole]), 15);
+        assert!(region.contains_point(116.05, 39.25));
+        assert!(!region.contains_point(116.25, 39.25));
+        assert!(region.size() < 30 * 30);
+    }
+
+    #[test]
+    fn test_cover_triangle_is_subset_of_bbox() {
+        let triangle = vec![(116.0, 39.0), (116.5, 39.0), (116.0, 39.5)];
+        let region = GeoSotRegion::cover_geometry(&Geometry::Polygon(vec![triangle]), 15);
+        assert!(region.contains_point(116.1, 39.1));
+        assert!(!region.contains_point(116.45, 39.45));
+        // 约为矩形的一半，加上对角线经过的网格
+        assert!(region.size() > 450 && region.size() < 500);
+    }
+
+    #[test]
+    fn test_cover_line_is_connected() {
+        let line = vec![(116.0, 39.0), (116.1, 39.05)];
+        let region = GeoSotRegion::cover_geometry(&Geometry::LineString(line), 15);
+        assert!(region.contains_point(116.0, 39.0));
+        assert!(region.contains_point(116.1, 39.05));
+        // 6 列 × 3 行的对角线至少经过 6 + 3 - 1 个网格
+        assert!(region.size() >= 8);
+    }
+
+    #[test]
+    fn test_cover_across_degree_boundary() {
+        // 12 级网格为 8 分，56~60 分处的截断网格不能遗漏
+        let ring = vec![(116.9, 39.0), (117.1, 39.0), (117.1, 39.01), (116.9, 39.01)];
+        let region = GeoSotRegion::cover_geometry(&Geometry::Polygon(vec![ring]), 12);
+        assert!(region.contains_point(116.0 + 58.0 / 60.0, 39.005));
+        assert_eq!(region.size(), 3);
+    }
+
+    #[test]
+    fn test_cover_across_meridian_and_equator() {
+        let ring = vec![(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
+        let region = GeoSotRegion::cover_geometry(&Geometry::Polygon(vec![ring]), 15);
+        assert_eq!(region.size(), 60 * 60);
+        assert!(region.contains_point(-0.25, -0.25));
+        assert!(region.contains_point(0.25, 0.25));
+    }
+}
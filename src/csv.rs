@@ -0,0 +1,455 @@
+//! 大型 CSV / TSV 点文件的流式编码
+//!
+//! 按批读取记录，经 [`encode_points`](crate::encode_points) 批量编码后在每行末尾追加编码列，
+//! 内存占用只与批大小有关。原始行内容（引号、空白等）原样输出，只在末尾追加新列。
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+
+use crate::{encode_points, to_string};
+
+/// 默认每批处理的记录数
+pub const DEFAULT_BATCH_SIZE: usize = 1 << 16;
+/// 默认单条记录的最大字节数
+pub const DEFAULT_MAX_RECORD_LEN: usize = 1 << 20;
+
+/// CSV 编码错误
+#[derive(Debug)]
+pub enum CsvError {
+    Io(io::Error),
+    /// 编码选项不合法
+    InvalidOptions(&'static str),
+    /// 表头中找不到指定的列
+    MissingColumn(String),
+    /// 记录格式或坐标不合法，`line` 为记录起始行号（从 1 开始）
+    InvalidRow { line: usize, message: String },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Io(err) => write!(f, "{err}"),
+            CsvError::InvalidOptions(message) => write!(f, "invalid options: {message}"),
+            CsvError::MissingColumn(name) => write!(f, "column '{name}' not found in header"),
+            CsvError::InvalidRow { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<io::Error> for CsvError {
+    fn from(err: io::Error) -> Self {
+        CsvError::Io(err)
+    }
+}
+
+impl From<CsvError> for PyErr {
+    fn from(err: CsvError) -> PyErr {
+        match err {
+            CsvError::Io(err) => PyIOError::new_err(err.to_string()),
+            err => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// 编码统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CsvStats {
+    /// 数据行数（不含表头与空行）
+    pub rows: usize,
+    /// 坐标缺失或不合法、编码列留空的行数
+    pub invalid: usize,
+}
+
+/// 流式 CSV 编码器
+///
+/// ```
+/// use geosot::csv::CsvEncoder;
+///
+/// let input = "id,lng,lat\n1,116.3912,39.9075\n";
+/// let mut output = Vec::new();
+/// let stats = CsvEncoder::new(&[18]).encode(input.as_bytes(), &mut output).unwrap();
+/// assert_eq!(stats.rows, 1);
+/// assert!(String::from_utf8(output).unwrap().starts_with("id,lng,lat,geosot_code\n"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvEncoder {
+    /// 经度列名
+    pub lng_column: String,
+    /// 纬度列名
+    pub lat_column: String,
+    /// 字段分隔符，TSV 使用 `b'\t'`
+    pub delimiter: u8,
+    /// 编码层级，可以同时输出多个层级
+    pub levels: Vec<usize>,
+    /// 是否追加十进制编码列
+    pub code: bool,
+    /// 是否追加网格字符串列
+    pub grid: bool,
+    /// 为 true 时坐标不合法的行编码列留空，否则返回错误
+    pub skip_invalid: bool,
+    /// 每批处理的记录数
+    pub batch_size: usize,
+    /// 单条记录（含跨行的引号字段）的最大字节数，超过时返回错误，避免未闭合的引号读入整个文件
+    pub max_record_len: usize,
+}
+
+impl CsvEncoder {
+    /// 创建编码器：读取 `lng` / `lat` 列，以逗号分隔，只追加编码列
+    pub fn new(levels: &[usize]) -> Self {
+        Self {
+            lng_column: "lng".to_string(),
+            lat_column: "lat".to_string(),
+            delimiter: b',',
+            levels: levels.to_vec(),
+            code: true,
+            grid: false,
+            skip_invalid: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_record_len: DEFAULT_MAX_RECORD_LEN,
+        }
+    }
+
+    /// 追加的列名：单个层级为 `geosot_code` / `geosot_grid`，多个层级时加上层级后缀
+    pub fn column_names(&self) -> Vec<String> {
+        let suffix = |level: usize| if self.levels.len() > 1 { format!("_{level}") } else { String::new() };
+        let mut names = Vec::new();
+        for &level in &self.levels {
+            if self.code {
+                names.push(format!("geosot_code{}", suffix(level)));
+            }
+            if self.grid {
+                names.push(format!("geosot_grid{}", suffix(level)));
+            }
+        }
+        names
+    }
+
+    /// 读取 `input`，向 `output` 写出追加了编码列的 CSV
+    pub fn encode<R: BufRead, W: Write>(&self, input: R, mut output: W) -> Result<CsvStats, CsvError> {
+        if self.levels.is_empty() || self.levels.iter().any(|level| !(1..=32).contains(level)) {
+            return Err(CsvError::InvalidOptions("levels must be non-empty and within 1~32"));
+        }
+        if !self.code && !self.grid {
+            return Err(CsvError::InvalidOptions("at least one of code and grid columns is required"));
+        }
+        if self.delimiter == b'"' || self.delimiter == b'\n' || self.delimiter == b'\r' || !self.delimiter.is_ascii() {
+            return Err(CsvError::InvalidOptions("delimiter must be an ASCII character other than quote or newline"));
+        }
+
+        let delimiter = self.delimiter as char;
+        let mut records = Records { input, line: 0, max_len: self.max_record_len.max(1) };
+        let Some((line, header)) = records.next_record()? else {
+            return Ok(CsvStats::default());
+        };
+        let names = split_fields(header.trim_start_matches('\u{feff}'), delimiter)
+            .map_err(|message| CsvError::InvalidRow { line, message })?;
+        let find = |name: &str| {
+            names
+                .iter()
+                .position(|field| field.trim() == name)
+                .or_else(|| names.iter().position(|field| field.trim().eq_ignore_ascii_case(name)))
+                .ok_or_else(|| CsvError::MissingColumn(name.to_string()))
+        };
+        let columns = (find(&self.lng_column)?, find(&self.lat_column)?);
+
+        write!(output, "{header}")?;
+        for name in self.column_names() {
+            write!(output, "{delimiter}{name}")?;
+        }
+        writeln!(output)?;
+
+        let mut stats = CsvStats::default();
+        let mut batch: Vec<(String, Option<(f64, f64)>)> = Vec::with_capacity(self.batch_size.max(1));
+        while let Some((line, raw)) = records.next_record()? {
+            if raw.trim().is_empty() {
+                continue;
+            }
+            let point = match parse_point(&raw, delimiter, columns) {
+                Ok(point) => Some(point),
+                Err(_) if self.skip_invalid => {
+                    stats.invalid += 1;
+                    None
+                }
+                Err(message) => return Err(CsvError::InvalidRow { line, message }),
+            };
+            stats.rows += 1;
+            batch.push((raw, point));
+            if batch.len() >= self.batch_size.max(1) {
+                self.write_batch(&batch, &mut output)?;
+                batch.clear();
+            }
+        }
+        self.write_batch(&batch, &mut output)?;
+        output.flush()?;
+        Ok(stats)
+    }
+
+    /// 批量编码一批记录并写出
+    fn write_batch<W: Write>(&self, batch: &[(String, Option<(f64, f64)>)], output: &mut W) -> io::Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let points: Vec<(f64, f64)> = batch.iter().map(|(_, point)| point.unwrap_or((0.0, 0.0))).collect();
+        let codes: Vec<Vec<u64>> = self.levels.iter().map(|&level| encode_points(&points, level)).collect();
+
+        let delimiter = self.delimiter as char;
+        for (i, (raw, point)) in batch.iter().enumerate() {
+            write!(output, "{raw}")?;
+            for (codes, &level) in codes.iter().zip(&self.levels) {
+                let valid = point.is_some();
+                if self.code {
+                    write!(output, "{delimiter}")?;
+                    if valid {
+                        write!(output, "{}", codes[i])?;
+                    }
+                }
+                if self.grid {
+                    write!(output, "{delimiter}")?;
+                    if valid {
+                        write!(output, "{}", to_string(codes[i], level))?;
+                    }
+                }
+            }
+            writeln!(output)?;
+        }
+        Ok(())
+    }
+}
+
+/// 逐条读取 CSV 记录，带引号的字段可以跨行
+struct Records<R> {
+    input: R,
+    line: usize,
+    /// 单条记录的最大字节数
+    max_len: usize,
+}
+
+impl<R: BufRead> Records<R> {
+    /// 返回记录的起始行号与去掉换行符的原始文本
+    fn next_record(&mut self) -> Result<Option<(usize, String)>, CsvError> {
+        let start = self.line + 1;
+        let mut raw = Vec::new();
+        let mut quotes = 0;
+        loop {
+            let appended = raw.len();
+            // 多读一个字节，以区分恰好达到上限与超过上限
+            let limit = (self.max_len + 1 - appended) as u64;
+            let read = (&mut self.input).take(limit).read_until(b'\n', &mut raw)?;
+            if raw.len() > self.max_len {
+                return Err(CsvError::InvalidRow {
+                    line: start,
+                    message: format!("record exceeds {} bytes", self.max_len),
+                });
+            }
+            if read == 0 {
+                if raw.is_empty() {
+                    return Ok(None);
+                }
+                return Err(CsvError::InvalidRow { line: start, message: "unterminated quoted field".to_string() });
+            }
+            self.line += 1;
+            // 双引号成对出现（转义的 "" 也是成对的）时记录结束，只统计新读入的一行
+            quotes += raw[appended..].iter().filter(|&&b| b == b'"').count();
+            if quotes.is_multiple_of(2) {
+                break;
+            }
+            if !raw.ends_with(b"\n") {
+                return Err(CsvError::InvalidRow { line: start, message: "unterminated quoted field".to_string() });
+            }
+        }
+        let mut raw = String::from_utf8(raw).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let trimmed = raw.trim_end_matches(['\n', '\r']).len();
+        raw.truncate(trimmed);
+        Ok(Some((start, raw)))
+    }
+}
+
+/// 按 RFC 4180 拆分字段，去掉包围的引号并还原转义的 `""`
+fn split_fields(raw: &str, delimiter: char) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = raw.chars().peekable();
+    let mut quoted = false;
+    let mut at_start = true;
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => {
+                    quoted = false;
+                    if chars.peek().is_some_and(|&next| next != delimiter) {
+                        return Err("unexpected character after closing quote".to_string());
+                    }
+                }
+                _ => field.push(c),
+            }
+        } else if c == '"' && at_start {
+            quoted = true;
+            at_start = false;
+        } else if c == delimiter {
+            fields.push(std::mem::take(&mut field));
+            at_start = true;
+        } else {
+            field.push(c);
+            at_start = false;
+        }
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// 读取记录中的经纬度并检查范围
+fn parse_point(raw: &str, delimiter: char, (lng_index, lat_index): (usize, usize)) -> Result<(f64, f64), String> {
+    let fields = split_fields(raw, delimiter)?;
+    let value = |index: usize, name: &str, limit: f64| {
+        let text = fields.get(index).map(|f| f.trim()).unwrap_or("");
+        match text.parse::<f64>() {
+            Ok(v) if v.is_finite() && v.abs() <= limit => Ok(v),
+            Ok(v) => Err(format!("{name} out of range: {v}")),
+            Err(_) if text.is_empty() => Err(format!("missing {name}")),
+            Err(_) => Err(format!("invalid {name} '{text}'")),
+        }
+    };
+    Ok((value(lng_index, "longitude", 180.0)?, value(lat_index, "latitude", 90.0)?))
+}
+
+/// 对 CSV / TSV 文件追加 GeoSOT 编码列，返回 (数据行数, 坐标不合法的行数)
+/// # 参数
+/// - `input`, `output`: 输入、输出文件路径
+/// - `levels`: 编码层级，多个层级时列名带层级后缀
+/// - `lng_column`, `lat_column`: 经纬度列名
+/// - `delimiter`: 分隔符，TSV 为 `"\t"`
+/// - `code`, `grid`: 是否追加编码列 / 网格字符串列
+/// - `skip_invalid`: 坐标不合法时留空而不是报错
+#[pyfunction]
+#[pyo3(signature = (input, output, levels, lng_column = "lng", lat_column = "lat", delimiter = ",", code = true, grid = false, skip_invalid = false))]
+#[allow(clippy::too_many_arguments)]
+pub fn encode_csv(
+    py: Python<'_>,
+    input: &str,
+    output: &str,
+    levels: Vec<usize>,
+    lng_column: &str,
+    lat_column: &str,
+    delimiter: &str,
+    code: bool,
+    grid: bool,
+    skip_invalid: bool,
+) -> PyResult<(usize, usize)> {
+    let &[delimiter] = delimiter.as_bytes() else {
+        return Err(PyValueError::new_err("delimiter must be a single ASCII character"));
+    };
+    let encoder = CsvEncoder {
+        lng_column: lng_column.to_string(),
+        lat_column: lat_column.to_string(),
+        delimiter,
+        levels,
+        code,
+        grid,
+        skip_invalid,
+        batch_size: DEFAULT_BATCH_SIZE,
+        max_record_len: DEFAULT_MAX_RECORD_LEN,
+    };
+    let stats = py.allow_threads(|| -> Result<CsvStats, CsvError> {
+        let reader = BufReader::new(File::open(input)?);
+        let writer = BufWriter::new(File::create(output)?);
+        encoder.encode(reader, writer)
+    })?;
+    Ok((stats.rows, stats.invalid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_code;
+
+    fn run(encoder: &CsvEncoder, input: &str) -> Result<(CsvStats, String), CsvError> {
+        let mut output = Vec::new();
+        let stats = encoder.encode(input.as_bytes(), &mut output)?;
+        Ok((stats, String::from_utf8(output).unwrap()))
+    }
+
+    #[test]
+    fn test_encode_appends_columns() {
+        let input = "id,name,lng,lat\r\n1,\"Beijing, CN\",116.3912,39.9075\r\n\n2,\"multi\nline \"\"quoted\"\"\",-73.98,40.75\n";
+        let (stats, output) = run(&CsvEncoder::new(&[18]), input).unwrap();
+        assert_eq!(stats, CsvStats { rows: 2, invalid: 0 });
+        let expected = format!(
+            "id,name,lng,lat,geosot_code\n1,\"Beijing, CN\",116.3912,39.9075,{}\n2,\"multi\nline \"\"quoted\"\"\",-73.98,40.75,{}\n",
+            get_code(116.3912, 39.9075, 18),
+            get_code(-73.98, 40.75, 18)
+        );
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_multiple_levels_and_tsv() {
+        let mut encoder = CsvEncoder::new(&[10, 20]);
+        encoder.delimiter = b'\t';
+        encoder.grid = true;
+        encoder.lng_column = "Longitude".to_string();
+        encoder.lat_column = "Latitude".to_string();
+        encoder.batch_size = 1;
+        let (stats, output) = run(&encoder, "longitude\tlatitude\n116.39\t39.90\n121.47\t31.23\n").unwrap();
+        assert_eq!(stats.rows, 2);
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "longitude\tlatitude\tgeosot_code_10\tgeosot_grid_10\tgeosot_code_20\tgeosot_grid_20");
+        let fields: Vec<&str> = lines[2].split('\t').collect();
+        assert_eq!(fields[2], get_code(121.47, 31.23, 10).to_string());
+        assert_eq!(fields[5], to_string(get_code(121.47, 31.23, 20), 20));
+    }
+
+    #[test]
+    fn test_invalid_rows() {
+        let input = "lng,lat\n116.39,39.90\n,\n200,10\n";
+        match run(&CsvEncoder::new(&[18]), input) {
+            Err(CsvError::InvalidRow { line, message }) => assert_eq!((line, message.as_str()), (3, "missing longitude")),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let mut encoder = CsvEncoder::new(&[18]);
+        encoder.skip_invalid = true;
+        let (stats, output) = run(&encoder, input).unwrap();
+        assert_eq!(stats, CsvStats { rows: 3, invalid: 2 });
+        assert!(output.ends_with(",\n200,10,\n"));
+
+        assert!(matches!(run(&CsvEncoder::new(&[18]), "x,y\n1,2\n"), Err(CsvError::MissingColumn(_))));
+        assert!(matches!(run(&CsvEncoder::new(&[]), "lng,lat\n"), Err(CsvError::InvalidOptions(_))));
+        assert!(matches!(
+            run(&CsvEncoder::new(&[18]), "lng,lat\n\"1,2\n"),
+            Err(CsvError::InvalidRow { line: 2, .. })
+        ));
+        assert_eq!(run(&CsvEncoder::new(&[18]), "").unwrap().0, CsvStats::default());
+    }
+
+    #[test]
+    fn test_record_length_limit() {
+        let mut encoder = CsvEncoder::new(&[18]);
+        encoder.max_record_len = 64;
+        // 未闭合的引号不会一直读到文件末尾
+        let mut input = "lng,lat\n116.39,39.90\n\"open,1\n".to_string();
+        input.push_str(&"116.39,39.90\n".repeat(100));
+        match run(&encoder, &input) {
+            Err(CsvError::InvalidRow { line, message }) => assert_eq!((line, message.as_str()), (3, "record exceeds 64 bytes")),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        // 不带换行符的超长行同样受限
+        let long = format!("lng,lat\n116.39,39.{}\n", "9".repeat(100));
+        assert!(matches!(run(&encoder, &long), Err(CsvError::InvalidRow { line: 2, .. })));
+
+        // 恰好达到上限的跨行记录可以读取
+        let record = "116.39,39.90,\"a\nb\"\n";
+        encoder.max_record_len = record.len();
+        assert_eq!(run(&encoder, &format!("lng,lat\n{record}")).unwrap().0.rows, 1);
+    }
+}
@@ -0,0 +1,400 @@
+//! 区域的距离变换、按米缓冲与区域之间的距离
+//!
+//! 网格到区域的距离定义为网格中心到区域内最近网格（整个网格范围，而非中心点）的球面距离，
+//! 因此不同纬度下网格的实际大小差异会被如实计入。距离变换从区域网格出发，按 8 邻接向外传播
+//! 各网格的最近目标网格（Dijkstra 顺序），每个网格只需检查少量候选。
+//!
+//! 区域之间的距离只需检查边界网格（至少有一个 4 邻接网格不属于区域），两个区域的层级可以不同。
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::grid::GeoSotGrid;
+use crate::spatial::{level_mask, GeoSotCell, GeoSotRegion};
+use crate::utils::{haversine_distance, point_to_bounds_distance};
+
+/// 点到网格范围的最短球面距离（米），点在网格内时为 0
+pub fn point_to_cell_distance(lng: f64, lat: f64, cell: &GeoSotCell) -> f64 {
+    point_to_bounds_distance(lng, lat, cell.bounds())
+}
+
+/// 两个网格范围之间的最短球面距离（米），相交或相接时为 0
+///
+/// 两个经纬度范围不相交时，最近点对中至少有一个是某个范围的角点。
+pub fn cell_to_cell_distance(a: &GeoSotCell, b: &GeoSotCell) -> f64 {
+    let (aw, as_, ae, an) = a.bounds();
+    let (bw, bs, be, bn) = b.bounds();
+    let lat_overlap = as_ <= bn && bs <= an;
+    let lng_overlap = (bw - aw).rem_euclid(360.0) <= ae - aw || (aw - bw).rem_euclid(360.0) <= be - bw;
+    if lat_overlap && lng_overlap {
+        return 0.0;
+    }
+    let corners = |(w, s, e, n): (f64, f64, f64, f64)| [(w, s), (w, n), (e, s), (e, n)];
+    let a_to_b = corners(a.bounds()).map(|(lng, lat)| point_to_cell_distance(lng, lat, b));
+    let b_to_a = corners(b.bounds()).map(|(lng, lat)| point_to_cell_distance(lng, lat, a));
+    a_to_b.into_iter().chain(b_to_a).fold(f64::INFINITY, f64::min)
+}
+
+impl GeoSotRegion {
+    /// 距离变换：`extent` 中每个网格到本区域最近网格的球面距离（米）
+    ///
+    /// 距离先在 `extent` 与本区域的网格之间传播；`extent` 中与本区域不连通的部分，
+    /// 由其边界网格逐一搜索本区域的边界网格作为种子，再在 `extent` 内继续传播。
+    /// 本区域为空时距离为无穷大。两个区域层级不同时 panic。
+    pub fn distance_transform(&self, extent: &GeoSotRegion) -> GeoSotGrid<f64> {
+        if self.level != extent.level {
+            panic!("Cannot compute distance transform of regions with different levels");
+        }
+        let mut nearest =
+            self.propagate(self.seeds(), |cell| extent.contains(cell.code) || self.contains(cell.code), f64::INFINITY);
+
+        let targets = self.boundary_cells();
+        let seeds: HashMap<u64, (f64, u64)> = extent
+            .boundary_cells()
+            .into_iter()
+            .filter(|cell| !targets.is_empty() && !nearest.contains_key(&cell.code))
+            .map(|cell| {
+                let (lng, lat) = cell.center();
+                let (distance, source) = targets
+                    .iter()
+                    .map(|t| (point_to_cell_distance(lng, lat, t), t.code))
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+                    .unwrap();
+                (cell.code, (distance, source))
+            })
+            .collect();
+        if !seeds.is_empty() {
+            let reached = &nearest;
+            let rest = self.propagate(
+                seeds,
+                |cell| extent.contains(cell.code) && !reached.contains_key(&cell.code),
+                f64::INFINITY,
+            );
+            nearest.extend(rest);
+        }
+        GeoSotGrid::from_fn(extent, |cell| nearest.get(&cell.code).map_or(f64::INFINITY, |&(d, _)| d))
+    }
+
+    /// 按米缓冲：加入中心到本区域距离不超过 `distance_m` 的所有同层级网格
+    ///
+    /// 高纬度地区网格的东西向宽度更小，同样的距离会覆盖更多列网格。
+    /// `distance_m` 不大于 0 时返回区域副本。
+    pub fn buffer_m(&self, distance_m: f64) -> GeoSotRegion {
+        if distance_m <= 0.0 {
+            return self.clone();
+        }
+        let mut result = GeoSotRegion::with_storage(self.level, self.storage());
+        for code in self.propagate(self.seeds(), |_| true, distance_m).into_keys() {
+            result.codes.insert(code);
+        }
+        result
+    }
+
+    /// 两个区域之间的最短球面距离（米），有公共部分或相接时为 0，任一区域为空时为无穷大
+    pub fn min_distance_m(&self, other: &GeoSotRegion) -> f64 {
+        if self.shares_cells(other) {
+            return 0.0;
+        }
+        let theirs = other.boundary_cells();
+        let mut best = f64::INFINITY;
+        for a in self.boundary_cells() {
+            for b in &theirs {
+                best = best.min(cell_to_cell_distance(&a, b));
+                if best == 0.0 {
+                    return 0.0;
+                }
+            }
+        }
+        best
+    }
+
+    /// Hausdorff 距离（米）：一个区域内的网格中心到另一个区域的最远距离，取两个方向的较大值
+    ///
+    /// 两个区域都为空时为 0，只有一个为空时为无穷大。
+    pub fn hausdorff_distance_m(&self, other: &GeoSotRegion) -> f64 {
+        match (self.is_empty(), other.is_empty()) {
+            (true, true) => 0.0,
+            (false, false) => self.directed_hausdorff_m(other).max(other.directed_hausdorff_m(self)),
+            _ => f64::INFINITY,
+        }
+    }
+
+    /// 两个区域面积加权质心之间的球面距离（米），任一区域为空时为无穷大
+    pub fn centroid_distance_m(&self, other: &GeoSotRegion) -> f64 {
+        match (self.centroid(), other.centroid()) {
+            (Some((lng1, lat1)), Some((lng2, lat2))) => haversine_distance(lng1, lat1, lng2, lat2),
+            _ => f64::INFINITY,
+        }
+    }
+
+    /// 本区域网格中心到另一个区域的最远距离
+    fn directed_hausdorff_m(&self, other: &GeoSotRegion) -> f64 {
+        let boundary = other.boundary_cells();
+        self.cells()
+            .iter()
+            .map(|cell| {
+                let (lng, lat) = cell.center();
+                if other.contains_point(lng, lat) {
+                    return 0.0;
+                }
+                boundary.iter().map(|b| point_to_cell_distance(lng, lat, b)).fold(f64::INFINITY, f64::min)
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// 两个区域是否有公共部分，层级可以不同
+    ///
+    /// 不同层级的网格要么嵌套要么不相交，较细网格截取到较粗层级的编码即为包含它的粗网格。
+    fn shares_cells(&self, other: &GeoSotRegion) -> bool {
+        if self.level == other.level {
+            return !self.codes.is_disjoint(&other.codes);
+        }
+        let (fine, coarse) = if self.level > other.level { (self, other) } else { (other, self) };
+        let mask = level_mask(coarse.level);
+        fine.codes.iter().any(|code| coarse.contains(code & mask))
+    }
+
+    /// 至少有一个 4 邻接网格不属于区域（或位于两极之外）的网格
+    fn boundary_cells(&self) -> Vec<GeoSotCell> {
+        self.cells()
+            .into_iter()
+            .filter(|cell| {
+                [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                    .iter()
+                    .any(|&(dx, dy)| cell.neighbor(dx, dy).is_none_or(|n| !self.contains(n.code)))
+            })
+            .collect()
+    }
+
+    /// 区域网格自身作为距离为 0 的传播种子
+    fn seeds(&self) -> HashMap<u64, (f64, u64)> {
+        self.codes.iter().map(|code| (code, (0.0, code))).collect()
+    }
+
+    /// 从种子网格向外传播最近目标网格，返回 编码 -> (距离, 最近目标网格编码)
+    ///
+    /// 只进入 `allowed` 为真且距离不超过 `max_distance` 的网格。
+    fn propagate(
+        &self,
+        mut nearest: HashMap<u64, (f64, u64)>,
+        allowed: impl Fn(&GeoSotCell) -> bool,
+        max_distance: f64,
+    ) -> HashMap<u64, (f64, u64)> {
+        // 非负浮点数的位模式与数值顺序一致，可直接作为堆的键
+        let mut heap: BinaryHeap<_> =
+            nearest.iter().map(|(&code, &(distance, _))| Reverse((distance.to_bits(), code))).collect();
+
+        while let Some(Reverse((bits, code))) = heap.pop() {
+            let (distance, source) = nearest[&code];
+            if bits != distance.to_bits() {
+                continue;
+            }
+            let source_cell = GeoSotCell::new(source, self.level);
+            for neighbor in GeoSotCell::new(code, self.level).neighbors() {
+                if !allowed(&neighbor) {
+                    continue;
+                }
+                let (lng, lat) = neighbor.center();
+                let candidate = point_to_cell_distance(lng, lat, &source_cell);
+                if candidate > max_distance {
+                    continue;
+                }
+                match nearest.entry(neighbor.code) {
+                    Entry::Occupied(mut entry) => {
+                        if candidate < entry.get().0 {
+                            entry.insert((candidate, source));
+                            heap.push(Reverse((candidate.to_bits(), neighbor.code)));
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert((candidate, source));
+                        heap.push(Reverse((candidate.to_bits(), neighbor.code)));
+                    }
+                }
+            }
+        }
+        nearest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 沿网格边界密集采样求点到网格的最短距离
+    fn sampled_distance(lng: f64, lat: f64, cell: &GeoSotCell) -> f64 {
+        let (w, s, e, n) = cell.bounds();
+        let steps = 2000;
+        (0..=steps)
+            .flat_map(|i| {
+                let t = i as f64 / steps as f64;
+                let (x, y) = (w + (e - w) * t, s + (n - s) * t);
+                [(x, s), (x, n), (w, y), (e, y)]
+            })
+            .map(|(x, y)| haversine_distance(lng, lat, x, y))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn test_point_to_cell_distance() {
+        let cell = GeoSotCell::from_coords(116.5, 39.5, 9);
+        assert_eq!(point_to_cell_distance(116.5, 39.5, &cell), 0.0);
+        let north = point_to_cell_distance(116.5, 41.0, &cell);
+        assert!((north - haversine_distance(116.5, 41.0, 116.5, 40.0)).abs() < 1e-6);
+
+        for &(lng, lat) in &[(120.0, 39.5), (112.0, 45.0), (130.0, 70.0), (100.0, 20.0), (-170.0, 60.0)] {
+            let exact = point_to_cell_distance(lng, lat, &cell);
+            let sampled = sampled_distance(lng, lat, &cell);
+            assert!(exact <= sampled + 1e-6 && sampled - exact < 50.0, "{lng} {lat}: {exact} vs {sampled}");
+        }
+
+        // 跨越 180° 经线
+        let east = GeoSotCell::from_coords(179.5, 0.5, 9);
+        let d = point_to_cell_distance(-179.5, 0.5, &east);
+        let along_parallel = haversine_distance(-179.5, 0.5, 180.0, 0.5);
+        assert!(d <= along_parallel && along_parallel - d < 1e-3);
+    }
+
+    #[test]
+    fn test_distance_transform_matches_brute_force() {
+        let mut target = GeoSotRegion::new(14);
+        for &(lng, lat) in &[(116.10, 39.10), (116.35, 39.40), (116.36, 39.41), (116.45, 39.05)] {
+            target.add_point(lng, lat);
+        }
+        let extent = GeoSotRegion::from_rectangle(116.0, 39.0, 116.5, 39.5, 14);
+        let distances = target.distance_transform(&extent);
+        assert_eq!(distances.len(), extent.size());
+        for (cell, &d) in distances.iter() {
+            let (lng, lat) = cell.center();
+            let expected = target.cells().iter().map(|t| point_to_cell_distance(lng, lat, t)).fold(f64::INFINITY, f64::min);
+            assert!((d - expected).abs() < 1e-6, "{cell}: {d} vs {expected}");
+            assert_eq!(d == 0.0, target.contains(cell.code));
+        }
+
+        let empty = GeoSotRegion::new(14).distance_transform(&extent);
+        assert!(empty.iter().all(|(_, d)| d.is_infinite()));
+    }
+
+    #[test]
+    fn test_distance_transform_disjoint_extent() {
+        // 目标在北京，范围在上海，两者不连通
+        let target = GeoSotRegion::from_rectangle(116.3, 39.8, 116.5, 40.0, 12);
+        let extent = GeoSotRegion::from_rectangle(121.2, 31.0, 121.6, 31.4, 12);
+        let distances = target.distance_transform(&extent);
+        assert_eq!(distances.len(), extent.size());
+        for (cell, &d) in distances.iter() {
+            let (lng, lat) = cell.center();
+            let expected = target.cells().iter().map(|t| point_to_cell_distance(lng, lat, t)).fold(f64::INFINITY, f64::min);
+            assert!((d - expected).abs() < 1e-6, "{cell}: {d} vs {expected}");
+            assert!(d > 1_000_000.0);
+        }
+
+        // 范围一部分与目标相接，另一部分与目标不连通
+        let near = GeoSotRegion::from_rectangle(116.0, 39.5, 116.35, 39.85, 12);
+        let apart = GeoSotRegion::from_rectangle(117.0, 39.5, 117.2, 39.7, 12);
+        let extent = near.union(&apart);
+        for (cell, &d) in target.distance_transform(&extent).iter() {
+            let (lng, lat) = cell.center();
+            let expected = target.cells().iter().map(|t| point_to_cell_distance(lng, lat, t)).fold(f64::INFINITY, f64::min);
+            assert!((d - expected).abs() < 1e-6, "{cell}: {d} vs {expected}");
+        }
+    }
+
+    #[test]
+    fn test_buffer_m() {
+        // 10 公里缓冲在 60° 纬度覆盖的经度范围约为赤道的 2 倍
+        let width = |lat: f64| {
+            let region = GeoSotRegion::from_codes(vec![crate::get_code(10.0, lat, 16)], 16);
+            let buffered = region.buffer_m(10_000.0);
+            let lngs: Vec<f64> = buffered.cells().iter().map(|c| c.center().0).collect();
+            let max = lngs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let min = lngs.iter().copied().fold(f64::INFINITY, f64::min);
+            (buffered, max - min)
+        };
+        let (equator, equator_width) = width(0.5);
+        let (north, north_width) = width(60.0);
+        assert!(equator.size() > 100);
+        assert!((north_width / equator_width - 2.0).abs() < 0.1);
+
+        // 与逐网格计算一致
+        let source = GeoSotCell::from_coords(10.0, 60.0, 16);
+        let candidates = crate::index::rect_cells(9.7, 59.85, 10.3, 60.15, 16);
+        let mut expected: Vec<u64> = candidates
+            .iter()
+            .filter(|c| {
+                let (lng, lat) = c.center();
+                point_to_cell_distance(lng, lat, &source) <= 10_000.0
+            })
+            .map(|c| c.code)
+            .collect();
+        expected.sort();
+        assert_eq!(north.codes.iter().collect::<Vec<_>>(), expected);
+
+        let region = GeoSotRegion::from_codes(vec![source.code], 16);
+        assert_eq!(region.buffer_m(0.0), region);
+    }
+
+    #[test]
+    fn test_cell_to_cell_distance() {
+        let a = GeoSotCell::from_coords(116.5, 39.5, 9);
+        assert_eq!(cell_to_cell_distance(&a, &a), 0.0);
+        assert_eq!(cell_to_cell_distance(&a, &GeoSotCell::from_coords(117.5, 40.5, 9)), 0.0);
+        assert_eq!(cell_to_cell_distance(&a, &GeoSotCell::from_coords(116.5, 39.5, 12)), 0.0);
+        let north = GeoSotCell::from_coords(116.5, 42.5, 9);
+        assert!((cell_to_cell_distance(&a, &north) - haversine_distance(116.5, 40.0, 116.5, 42.0)).abs() < 1e-6);
+        let east = GeoSotCell::from_coords(119.5, 39.5, 9);
+        let d = cell_to_cell_distance(&a, &east);
+        assert!(d > 0.0 && d <= haversine_distance(117.0, 40.0, 119.0, 40.0));
+        assert_eq!(cell_to_cell_distance(&GeoSotCell::from_coords(179.5, 0.5, 9), &GeoSotCell::from_coords(-179.5, 0.5, 9)), 0.0);
+    }
+
+    #[test]
+    fn test_region_distances() {
+        let a = GeoSotRegion::from_rectangle(116.0, 39.0, 116.99, 39.99, 12);
+        let b = GeoSotRegion::from_rectangle(118.0, 39.0, 118.99, 39.99, 12);
+        let empty = GeoSotRegion::new(12);
+
+        let d = a.min_distance_m(&b);
+        let expected = a
+            .cells()
+            .iter()
+            .flat_map(|x| b.cells().into_iter().map(move |y| cell_to_cell_distance(x, &y)))
+            .fold(f64::INFINITY, f64::min);
+        assert!((d - expected).abs() < 1e-6);
+        assert!(d > 80_000.0 && d < 90_000.0);
+        assert_eq!(a.min_distance_m(&a), 0.0);
+        assert!(a.min_distance_m(&empty).is_infinite());
+
+        let directed = |x: &GeoSotRegion, y: &GeoSotRegion| {
+            x.cells()
+                .iter()
+                .map(|c| {
+                    let (lng, lat) = c.center();
+                    y.cells().iter().map(|t| point_to_cell_distance(lng, lat, t)).fold(f64::INFINITY, f64::min)
+                })
+                .fold(0.0, f64::max)
+        };
+        let h = a.hausdorff_distance_m(&b);
+        assert!((h - directed(&a, &b).max(directed(&b, &a))).abs() < 1e-6);
+        assert!(h > d && h < a.centroid_distance_m(&b));
+        assert_eq!(a.hausdorff_distance_m(&a), 0.0);
+        assert_eq!(empty.hausdorff_distance_m(&empty), 0.0);
+        assert!(a.hausdorff_distance_m(&empty).is_infinite());
+
+        // 不同层级的区域也可以比较
+        let coarse = GeoSotRegion::from_rectangle(116.0, 39.0, 116.99, 39.99, 9);
+        assert_eq!(a.hausdorff_distance_m(&coarse), 0.0);
+        assert!((a.min_distance_m(&b) - coarse.min_distance_m(&b)).abs() < 1e-6);
+
+        // 嵌套在另一层级区域内部、与其边界不相接的区域
+        let inner = GeoSotRegion::from_rectangle(115.0, 35.0, 115.2, 35.2, 12);
+        let outer = GeoSotRegion::from_rectangle(110.0, 30.0, 119.99, 39.99, 9);
+        assert_eq!(inner.min_distance_m(&outer), 0.0);
+        assert_eq!(outer.min_distance_m(&inner), 0.0);
+
+        let c = a.centroid_distance_m(&b);
+        assert!((c - haversine_distance(116.5, 39.5, 118.5, 39.5)).abs() < 500.0);
+        assert!(a.centroid_distance_m(&empty).is_infinite());
+    }
+}
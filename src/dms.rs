@@ -0,0 +1,401 @@
+//! 度分秒（DMS）、度-小数分（DDM）与带半球字母的坐标解析和格式化
+//!
+//! 支持的写法（可混用全角/半角符号）：
+//!
+//! - `39°54'33.2"N`、`39° 54′ 33.2″ N`、`N 39°54'33.2"`、`39°54'33.2''N`
+//! - `39°54.553'N`、`39 54.553 N`（度-小数分）
+//! - `39.9092N`、`-39.9092`、`39.9092° S`（十进制度）
+//!
+//! 经纬度成对输入时按半球字母区分经纬度；没有字母时按海图习惯视为“纬度在前”，
+//! 两个坐标之间可以用逗号、分号或空白分隔。
+
+use pyo3::prelude::*;
+
+use crate::geometry::ParseError;
+use crate::spatial::GeoSotCell;
+use crate::utils::get_cell_size_in_degree;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Degree,
+    Minute,
+    Second,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Number(f64, bool),
+    Unit(Unit),
+    Hemisphere(char),
+    Sign(f64),
+    Separator,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn tokenize(text: &str) -> Result<(Vec<Token>, usize), ParseError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (c, column) = (chars[i], i + 1);
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '0'..='9' | '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse::<f64>()
+                    .map_err(|_| ParseError::new(1, column, format!("invalid number '{text}'")))?;
+                tokens.push(Token { kind: TokenKind::Number(value, !text.contains('.')), column });
+                continue;
+            }
+            '°' | 'º' => TokenKind::Unit(Unit::Degree),
+            '\'' | '′' | '’' if chars.get(i + 1) == Some(&c) => {
+                i += 1;
+                TokenKind::Unit(Unit::Second)
+            }
+            '\'' | '′' | '’' => TokenKind::Unit(Unit::Minute),
+            '"' | '″' | '”' => TokenKind::Unit(Unit::Second),
+            'N' | 'S' | 'E' | 'W' | 'n' | 's' | 'e' | 'w' => TokenKind::Hemisphere(c.to_ascii_uppercase()),
+            '-' | '−' => TokenKind::Sign(-1.0),
+            '+' => TokenKind::Sign(1.0),
+            ',' | ';' => TokenKind::Separator,
+            _ => return Err(ParseError::new(1, column, format!("unexpected character '{c}'"))),
+        };
+        tokens.push(Token { kind, column });
+        i += 1;
+    }
+    Ok((tokens, chars.len() + 1))
+}
+
+/// 一个坐标分量的解析结果
+#[derive(Debug, Clone, Copy)]
+struct Angle {
+    value: f64,
+    hemisphere: Option<char>,
+    column: usize,
+}
+
+/// 将词法单元切分为若干个坐标，每组对应一个经度或纬度
+fn group(tokens: &[Token]) -> Vec<&[Token]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    let mut has_number = false;
+    let mut has_degree = false;
+    let mut has_hemisphere = false;
+    // 上一个坐标已由半球字母结束，紧随的分隔符不再产生空坐标
+    let mut closed = false;
+    for (i, token) in tokens.iter().enumerate() {
+        let split_before = match token.kind {
+            TokenKind::Separator => {
+                if !(closed && i == start) {
+                    groups.push(&tokens[start..i]);
+                }
+                closed = false;
+                start = i + 1;
+                has_number = false;
+                has_degree = false;
+                has_hemisphere = false;
+                continue;
+            }
+            // 已有数值后出现的前缀符号，或已有前缀字母时的第二个字母，开始一个新坐标
+            TokenKind::Sign(_) => has_number,
+            TokenKind::Hemisphere(_) => has_number && has_hemisphere,
+            TokenKind::Number(..) => {
+                has_degree && matches!(tokens.get(i + 1).map(|t| t.kind), Some(TokenKind::Unit(Unit::Degree)))
+            }
+            _ => false,
+        };
+        closed = false;
+        if split_before && i > start {
+            groups.push(&tokens[start..i]);
+            start = i;
+            has_number = false;
+            has_degree = false;
+            has_hemisphere = false;
+        }
+        match token.kind {
+            TokenKind::Number(..) => has_number = true,
+            TokenKind::Unit(Unit::Degree) => has_degree = true,
+            // 数值之后的半球字母结束当前坐标
+            TokenKind::Hemisphere(_) if has_number => {
+                groups.push(&tokens[start..=i]);
+                start = i + 1;
+                has_number = false;
+                has_degree = false;
+                closed = true;
+            }
+            TokenKind::Hemisphere(_) => has_hemisphere = true,
+            _ => {}
+        }
+    }
+    if start < tokens.len() {
+        groups.push(&tokens[start..]);
+    }
+    groups
+}
+
+/// 解析单个坐标：[符号|半球] 度 [分 [秒]] [半球]
+fn parse_angle(tokens: &[Token], end: usize) -> Result<Angle, ParseError> {
+    let column = tokens.first().map_or(end, |t| t.column);
+    let mut sign = None;
+    let mut hemisphere = None;
+    let mut parts: Vec<(f64, bool, usize)> = Vec::new();
+    let mut next_unit = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let err = |message: &str| Err(ParseError::new(1, token.column, message));
+        match token.kind {
+            TokenKind::Sign(s) if parts.is_empty() && sign.is_none() && hemisphere.is_none() => sign = Some(s),
+            TokenKind::Hemisphere(h) if hemisphere.is_none() && (parts.is_empty() || i == tokens.len() - 1) => {
+                hemisphere = Some(h)
+            }
+            TokenKind::Number(value, integer) => {
+                if next_unit > 2 {
+                    return err("too many components");
+                }
+                // 带单位时按单位定位，否则依次为度、分、秒
+                let unit = match tokens.get(i + 1).map(|t| t.kind) {
+                    Some(TokenKind::Unit(unit)) => unit as usize,
+                    _ => next_unit,
+                };
+                if unit < next_unit {
+                    return err("components must be in degree, minute, second order");
+                }
+                if let Some(&(_, prev_integer, _)) = parts.last() {
+                    if !prev_integer {
+                        return err("only the last component may have a fraction");
+                    }
+                }
+                if unit > 0 && value >= 60.0 {
+                    return err("minutes and seconds must be less than 60");
+                }
+                while parts.len() < unit {
+                    parts.push((0.0, true, token.column));
+                }
+                parts.push((value, integer, token.column));
+                next_unit = unit + 1;
+            }
+            TokenKind::Unit(_) if matches!(tokens.get(i.wrapping_sub(1)).map(|t| t.kind), Some(TokenKind::Number(..))) => {}
+            TokenKind::Unit(_) => return err("unit without a number"),
+            TokenKind::Separator => return err("unexpected separator"),
+            TokenKind::Sign(_) | TokenKind::Hemisphere(_) => return err("unexpected sign or hemisphere letter"),
+        }
+    }
+
+    if parts.is_empty() {
+        return Err(ParseError::new(1, column, "expected a coordinate"));
+    }
+    if let (Some(s), Some(h)) = (sign, hemisphere) {
+        if s < 0.0 {
+            return Err(ParseError::new(1, column, format!("negative sign conflicts with hemisphere '{h}'")));
+        }
+    }
+    let magnitude = parts.iter().zip([1.0, 60.0, 3600.0]).map(|(&(v, _, _), d)| v / d).sum::<f64>();
+    let negative = sign == Some(-1.0) || matches!(hemisphere, Some('S' | 'W'));
+    Ok(Angle { value: if negative { -magnitude } else { magnitude }, hemisphere, column })
+}
+
+fn is_latitude(hemisphere: char) -> bool {
+    matches!(hemisphere, 'N' | 'S')
+}
+
+fn check_range(angle: &Angle, latitude: bool) -> Result<f64, ParseError> {
+    let (limit, name) = if latitude { (90.0, "latitude") } else { (180.0, "longitude") };
+    if angle.value.abs() > limit {
+        return Err(ParseError::new(1, angle.column, format!("{name} out of range: {}", angle.value)));
+    }
+    Ok(angle.value)
+}
+
+fn parse_single(text: &str, latitude: bool) -> Result<f64, ParseError> {
+    let (tokens, end) = tokenize(text)?;
+    let angle = parse_angle(&tokens, end)?;
+    if let Some(h) = angle.hemisphere {
+        if is_latitude(h) != latitude {
+            return Err(ParseError::new(1, angle.column, format!("hemisphere '{h}' does not match the axis")));
+        }
+    }
+    check_range(&angle, latitude)
+}
+
+/// 解析纬度，如 `39°54'33.2"N`
+pub fn parse_latitude(text: &str) -> Result<f64, ParseError> {
+    parse_single(text, true)
+}
+
+/// 解析经度，如 `116°23'27.6"E`
+pub fn parse_longitude(text: &str) -> Result<f64, ParseError> {
+    parse_single(text, false)
+}
+
+/// 解析一对坐标，返回 (lng, lat)
+///
+/// 按半球字母区分经纬度，没有字母时视为纬度在前。
+#[pyfunction]
+pub fn parse_coordinates(text: &str) -> PyResult<(f64, f64)> {
+    Ok(parse_lnglat(text)?)
+}
+
+/// 解析一对坐标，返回 (lng, lat)，见 [`parse_coordinates`]
+pub fn parse_lnglat(text: &str) -> Result<(f64, f64), ParseError> {
+    let (tokens, end) = tokenize(text)?;
+    let mut groups = group(&tokens);
+    // "39.9 116.4" 这样没有单位与字母的两个数值各自是一个坐标
+    if groups.len() == 1 && groups[0].len() == 2 && groups[0].iter().all(|t| matches!(t.kind, TokenKind::Number(..))) {
+        let (a, b) = groups[0].split_at(1);
+        groups = vec![a, b];
+    }
+    if groups.len() != 2 {
+        let column = groups.get(2).and_then(|g| g.first()).map_or(end, |t| t.column);
+        return Err(ParseError::new(1, column, format!("expected 2 coordinates, found {}", groups.len())));
+    }
+
+    let first = parse_angle(groups[0], end)?;
+    let second = parse_angle(groups[1], end)?;
+    let first_is_lat = match (first.hemisphere, second.hemisphere) {
+        (Some(a), Some(b)) if is_latitude(a) == is_latitude(b) => {
+            return Err(ParseError::new(1, second.column, "both coordinates are on the same axis"));
+        }
+        (Some(a), _) => is_latitude(a),
+        (None, Some(b)) => !is_latitude(b),
+        (None, None) => true,
+    };
+    let (lat, lng) = if first_is_lat { (first, second) } else { (second, first) };
+    Ok((check_range(&lng, false)?, check_range(&lat, true)?))
+}
+
+/// 按度分秒格式化，如 `39°54'33.20"N`
+/// # 参数
+/// - `value`: 十进制度
+/// - `latitude`: 是否为纬度（决定半球字母）
+/// - `decimals`: 秒的小数位数
+#[pyfunction]
+#[pyo3(signature = (value, latitude, decimals = 2))]
+pub fn format_dms(value: f64, latitude: bool, decimals: usize) -> String {
+    let scale = 10f64.powi(decimals as i32);
+    // 先按秒的精度取整，避免出现 60 秒
+    let total = (value.abs() * 3600.0 * scale).round() / scale;
+    let degrees = (total / 3600.0).floor();
+    let minutes = ((total - degrees * 3600.0) / 60.0).floor();
+    let seconds = total - degrees * 3600.0 - minutes * 60.0;
+    let width = if decimals > 0 { decimals + 3 } else { 2 };
+    format!("{}°{:02}'{:0width$.decimals$}\"{}", degrees, minutes, seconds, hemisphere(value, latitude))
+}
+
+/// 按度-小数分格式化，如 `39°54.553'N`
+#[pyfunction]
+#[pyo3(signature = (value, latitude, decimals = 3))]
+pub fn format_ddm(value: f64, latitude: bool, decimals: usize) -> String {
+    let scale = 10f64.powi(decimals as i32);
+    let total = (value.abs() * 60.0 * scale).round() / scale;
+    let degrees = (total / 60.0).floor();
+    let minutes = total - degrees * 60.0;
+    let width = if decimals > 0 { decimals + 3 } else { 2 };
+    format!("{}°{:0width$.decimals$}'{}", degrees, minutes, hemisphere(value, latitude))
+}
+
+fn hemisphere(value: f64, latitude: bool) -> char {
+    match (latitude, value < 0.0) {
+        (true, false) => 'N',
+        (true, true) => 'S',
+        (false, false) => 'E',
+        (false, true) => 'W',
+    }
+}
+
+impl GeoSotCell {
+    /// 由度分秒等格式的坐标对创建网格
+    pub fn from_dms(text: &str, level: usize) -> Result<Self, ParseError> {
+        let (lng, lat) = parse_lnglat(text)?;
+        Ok(GeoSotCell::from_coords(lng, lat, level))
+    }
+
+    /// 网格中心的度分秒表示（纬度在前），秒的小数位数与网格大小匹配
+    pub fn to_dms(&self) -> String {
+        let size = get_cell_size_in_degree(self.level as i32).unwrap_or(1.0) * 3600.0;
+        // 中心位于网格的一半处，需要多保留一位才能落在网格内
+        let decimals = (-(size / 2.0).log10()).ceil().clamp(0.0, 4.0) as usize;
+        let (lng, lat) = self.center();
+        format!("{} {}", format_dms(lat, true, decimals), format_dms(lng, false, decimals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_single_values() {
+        let expected = 39.0 + 54.0 / 60.0 + 33.2 / 3600.0;
+        for text in ["39°54'33.2\"N", "39° 54′ 33.2″ N", "N 39°54'33.2\"", "39 54 33.2 N", "39°54'33.2''N", "+39°54'33.2\""] {
+            assert!(close(parse_latitude(text).unwrap(), expected), "{text}");
+        }
+        assert!(close(parse_latitude("39°54.5'S").unwrap(), -(39.0 + 54.5 / 60.0)));
+        assert!(close(parse_longitude("116.3912W").unwrap(), -116.3912));
+        assert!(close(parse_longitude("-116.3912°").unwrap(), -116.3912));
+        assert!(close(parse_latitude("39°33\"").unwrap(), 39.0 + 33.0 / 3600.0));
+    }
+
+    #[test]
+    fn test_pairs() {
+        let (lng, lat) = parse_lnglat("39°54'33.2\"N 116°23'27.6\"E").unwrap();
+        assert!(close(lat, 39.0 + 54.0 / 60.0 + 33.2 / 3600.0));
+        assert!(close(lng, 116.0 + 23.0 / 60.0 + 27.6 / 3600.0));
+
+        // 经度在前时按字母识别
+        assert_eq!(parse_lnglat("116°23'27.6\"E 39°54'33.2\"N").unwrap(), (lng, lat));
+        assert_eq!(parse_lnglat("39 54 33.2 N, 116 23 27.6 E").unwrap(), (lng, lat));
+        assert_eq!(parse_lnglat("39°54'33.2\" 116°23'27.6\"").unwrap(), (lng, lat));
+        assert_eq!(parse_lnglat("-33.857, 151.215").unwrap(), (151.215, -33.857));
+        assert_eq!(parse_lnglat("39.9 116.4").unwrap(), (116.4, 39.9));
+        assert_eq!(parse_lnglat("S33.857 E151.215").unwrap(), (151.215, -33.857));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_latitude("39°61'N").unwrap_err().column(), Some(4));
+        assert_eq!(parse_latitude("39.5°30'N").unwrap_err().column(), Some(6));
+        assert_eq!(parse_latitude("-39°S").unwrap_err().column(), Some(1));
+        assert_eq!(parse_latitude("39°54'E").unwrap_err().column(), Some(1));
+        assert_eq!(parse_latitude("91N").unwrap_err().column(), Some(1));
+        assert_eq!(parse_latitude("39x").unwrap_err().column(), Some(3));
+        assert!(parse_lnglat("39N 40S").is_err());
+        assert!(parse_lnglat("39 54 116 23").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_dms(39.909222, true, 1), "39°54'33.2\"N");
+        assert_eq!(format_dms(-116.391, false, 0), "116°23'28\"W");
+        assert_eq!(format_dms(59.999999, true, 2), "60°00'00.00\"N");
+        assert_eq!(format_ddm(39.909222, true, 3), "39°54.553'N");
+
+        let text = format_dms(-33.857, true, 2);
+        assert!(close(parse_latitude(&text).unwrap(), -33.857));
+    }
+
+    #[test]
+    fn test_cell_dms() {
+        let cell = GeoSotCell::from_dms("39°54'33.2\"N 116°23'27.6\"E", 21).unwrap();
+        assert_eq!(cell, GeoSotCell::from_coords(116.3910, 39.909222, 21));
+        // 21 级网格为 1 秒，中心落在半秒处
+        assert_eq!(cell.to_dms(), "39°54'33.5\"N 116°23'27.5\"E");
+        assert_eq!(GeoSotCell::from_dms(&cell.to_dms(), 21).unwrap(), cell);
+    }
+}
@@ -0,0 +1,222 @@
+//! 区域的范围查询：包围盒、面积加权质心、范围角点网格与最小公共祖先网格
+//!
+//! 经度方向在圆周上处理：包围盒取去掉最大经度空隙后的范围，因此跨越 180° 经线的区域
+//! 得到 `min_lng > max_lng` 的包围盒（与 `GeoSotIndex::query_bbox` 的约定一致）。
+
+use std::collections::BTreeMap;
+
+use crate::spatial::{common_prefix_level, level_mask, GeoSotCell, GeoSotRegion};
+use crate::{magic_bits, un_magic_bits};
+
+impl GeoSotRegion {
+    /// 包围盒 (min_lng, min_lat, max_lng, max_lat)，空区域返回 None
+    ///
+    /// 跨越 180° 经线时 `min_lng > max_lng`；经度方向没有空隙时为 -180 ~ 180。
+    pub fn bbox(&self) -> Option<(f64, f64, f64, f64)> {
+        let (west, east) = self.lng_extent()?;
+        let (south, north) = self.lat_extent()?;
+        Some((west.1, south.1, east.1, north.1))
+    }
+
+    /// 面积加权质心 (lng, lat)，空区域返回 None
+    ///
+    /// 以网格的球面面积为权重对网格中心的单位向量求和，跨越 180° 经线时结果仍然连续。
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        if self.is_empty() {
+            return None;
+        }
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        for cell in self.cells() {
+            let (w, s, e, n) = cell.bounds();
+            let area = (n.to_radians().sin() - s.to_radians().sin()) * (e - w).to_radians();
+            let (lng, lat) = cell.center();
+            let (lng, lat) = (lng.to_radians(), lat.to_radians());
+            x += area * lat.cos() * lng.cos();
+            y += area * lat.cos() * lng.sin();
+            z += area * lat.sin();
+        }
+        Some((y.atan2(x).to_degrees(), z.atan2(x.hypot(y)).to_degrees()))
+    }
+
+    /// 范围西南角的网格：经度取最西一列、纬度取最南一行（两轴分别取值，该网格不一定属于区域）
+    pub fn min_cell(&self) -> Option<GeoSotCell> {
+        let (west, _) = self.lng_extent()?;
+        let (south, _) = self.lat_extent()?;
+        Some(GeoSotCell::new(magic_bits(west.0, south.0), self.level))
+    }
+
+    /// 范围东北角的网格：经度取最东一列、纬度取最北一行（两轴分别取值，该网格不一定属于区域）
+    pub fn max_cell(&self) -> Option<GeoSotCell> {
+        let (_, east) = self.lng_extent()?;
+        let (_, north) = self.lat_extent()?;
+        Some(GeoSotCell::new(magic_bits(east.0, north.0), self.level))
+    }
+
+    /// 包含全部网格的最小公共祖先网格，空区域返回 None
+    ///
+    /// 第 1 级网格按经纬度符号划分四个象限，跨越 180° 经线、本初子午线或赤道的区域
+    /// 没有更小的公共祖先，返回覆盖全球的第 0 级网格（编码为 0）。
+    pub fn covering_cell(&self) -> Option<GeoSotCell> {
+        // 编码按 Z 序排列，最小与最大编码的公共前缀即全部编码的公共前缀
+        let (first, last) = (self.codes.first()?, self.codes.last()?);
+        let level = common_prefix_level(first, last).min(self.level);
+        Some(GeoSotCell::new(first & level_mask(level), level))
+    }
+
+    /// 经度方向的最西、最东一列：((列编码, 西边界), (列编码, 东边界))
+    fn lng_extent(&self) -> Option<((u32, f64), (u32, f64))> {
+        // 每一列取一个网格计算经度范围，按西边界排序
+        let mut columns: BTreeMap<u32, (f64, f64)> = BTreeMap::new();
+        for code in self.codes.iter() {
+            let lng = un_magic_bits(code).0;
+            columns.entry(lng).or_insert_with(|| {
+                let (w, _, e, _) = GeoSotCell::new(code, self.level).bounds();
+                (w, e)
+            });
+        }
+        let mut columns: Vec<(u32, f64, f64)> = columns.into_iter().map(|(c, (w, e))| (c, w, e)).collect();
+        columns.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        // 找到圆周上最大的经度空隙，范围从空隙东侧开始、到空隙西侧结束
+        let first = *columns.first()?;
+        let mut reach = first;
+        let mut gap = (0.0, reach, first);
+        for &column in &columns[1..] {
+            if column.1 - reach.2 > gap.0 {
+                gap = (column.1 - reach.2, reach, column);
+            }
+            if column.2 > reach.2 {
+                reach = column;
+            }
+        }
+        // 没有内部空隙更大时，范围从最西一列到最东一列（经度一整圈时为 -180 ~ 180）
+        if first.1 + 360.0 - reach.2 >= gap.0 {
+            gap = (first.1 + 360.0 - reach.2, reach, first);
+        }
+        let (_, east, west) = gap;
+        Some(((west.0, west.1), (east.0, east.2)))
+    }
+
+    /// 纬度方向的最南、最北一行：((行编码, 南边界), (行编码, 北边界))
+    fn lat_extent(&self) -> Option<((u32, f64), (u32, f64))> {
+        let mut south: Option<(u32, f64)> = None;
+        let mut north: Option<(u32, f64)> = None;
+        let mut rows: Vec<u32> = self.codes.iter().map(|code| un_magic_bits(code).1).collect();
+        rows.sort_unstable();
+        rows.dedup();
+        for lat in rows {
+            let (_, s, _, n) = GeoSotCell::new(magic_bits(0, lat), self.level).bounds();
+            if south.is_none_or(|(_, v)| s < v) {
+                south = Some((lat, s));
+            }
+            if north.is_none_or(|(_, v)| n > v) {
+                north = Some((lat, n));
+            }
+        }
+        Some((south?, north?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_bbox() {
+        assert_eq!(GeoSotRegion::new(9).bbox(), None);
+        let region = GeoSotRegion::from_rectangle(116.2, 39.2, 117.8, 40.8, 9);
+        assert_eq!(region.bbox(), Some((116.0, 39.0, 118.0, 41.0)));
+
+        let mut mixed = GeoSotRegion::new(9);
+        mixed.add_point(-0.5, -0.5);
+        mixed.add_point(10.5, 20.5);
+        assert_eq!(mixed.bbox(), Some((-1.0, -1.0, 11.0, 21.0)));
+
+        // 跨越 180° 经线
+        let mut wrapped = GeoSotRegion::new(9);
+        for lng in [178.5, 179.5, -179.5, -178.5] {
+            wrapped.add_point(lng, 10.5);
+        }
+        assert_eq!(wrapped.bbox(), Some((178.0, 10.0, -178.0, 11.0)));
+
+        // 一整圈纬度带
+        let mut band = GeoSotRegion::new(9);
+        for i in 0..360 {
+            band.add_point(-179.5 + i as f64, 0.5);
+        }
+        let (w, _, e, _) = band.bbox().unwrap();
+        assert_eq!((w, e), (-180.0, 180.0));
+    }
+
+    #[test]
+    fn test_centroid() {
+        assert_eq!(GeoSotRegion::new(9).centroid(), None);
+        let single = GeoSotRegion::from_codes(vec![crate::get_code(116.5, 39.5, 9)], 9);
+        let (lng, lat) = single.centroid().unwrap();
+        assert!(close(lng, 116.5) && close(lat, 39.5));
+
+        // 高纬度网格面积更小，质心偏向低纬度网格
+        let mut region = GeoSotRegion::new(9);
+        region.add_point(10.5, 0.5);
+        region.add_point(10.5, 60.5);
+        let (_, lat) = region.centroid().unwrap();
+        assert!(lat < 25.0);
+
+        let mut wrapped = GeoSotRegion::new(9);
+        wrapped.add_point(179.5, 0.5);
+        wrapped.add_point(-179.5, 0.5);
+        let (lng, lat) = wrapped.centroid().unwrap();
+        assert!(close(lng.abs(), 180.0) && (lat - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_min_max_cell() {
+        let mut region = GeoSotRegion::new(9);
+        region.add_point(116.5, 40.5);
+        region.add_point(118.5, 39.5);
+        assert_eq!(region.min_cell(), Some(GeoSotCell::from_coords(116.5, 39.5, 9)));
+        assert_eq!(region.max_cell(), Some(GeoSotCell::from_coords(118.5, 40.5, 9)));
+
+        let mut wrapped = GeoSotRegion::new(9);
+        wrapped.add_point(179.5, -0.5);
+        wrapped.add_point(-178.5, 0.5);
+        assert_eq!(wrapped.min_cell(), Some(GeoSotCell::from_coords(179.5, -0.5, 9)));
+        assert_eq!(wrapped.max_cell(), Some(GeoSotCell::from_coords(-178.5, 0.5, 9)));
+        assert_eq!(GeoSotRegion::new(9).min_cell(), None);
+    }
+
+    #[test]
+    fn test_covering_cell() {
+        let parent = GeoSotCell::from_coords(116.397, 39.916, 12);
+        let children: Vec<u64> = parent.children().iter().flat_map(|c| c.children()).map(|c| c.code).collect();
+        let region = GeoSotRegion::from_codes(children, 14);
+        assert_eq!(region.covering_cell(), Some(parent));
+
+        let single = GeoSotRegion::from_codes(vec![crate::get_code(116.397, 39.916, 20)], 20);
+        assert_eq!(single.covering_cell(), Some(GeoSotCell::from_coords(116.397, 39.916, 20)));
+
+        // 跨越 180° 经线、赤道或本初子午线（伦敦、基多）时只有全球网格能覆盖
+        let global = GeoSotCell::new(0, 0);
+        assert_eq!(global.bounds(), (-180.0, -90.0, 180.0, 90.0));
+        for (a, b) in [((179.5, 0.5), (-179.5, 0.5)), ((-78.5, 0.2), (-78.5, -0.2)), ((-0.1, 51.5), (0.1, 51.5))] {
+            let mut region = GeoSotRegion::new(9);
+            region.add_point(a.0, a.1);
+            region.add_point(b.0, b.1);
+            let ancestor = region.covering_cell().unwrap();
+            assert_eq!(ancestor, global);
+            assert!(region.cells().iter().all(|c| ancestor.is_ancestor_of(c)));
+        }
+        assert_eq!(GeoSotRegion::new(9).covering_cell(), None);
+
+        let mut region = GeoSotRegion::new(20);
+        region.add_point(116.0, 39.0);
+        region.add_point(121.0, 31.0);
+        let ancestor = region.covering_cell().unwrap();
+        assert!(region.cells().iter().all(|c| ancestor.is_ancestor_of(c)));
+        assert!(ancestor.children().iter().all(|child| !region.cells().iter().all(|c| child.is_ancestor_of(c))));
+    }
+}
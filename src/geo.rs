@@ -0,0 +1,216 @@
+//! 与 geo-types 的互相转换（`geo` 特性）
+//!
+//! - 网格 → `Rect` / `Polygon`（网格范围）、`Point`（网格中心）
+//! - 与网格范围完全一致的 `Rect` / `Polygon` → 网格（`TryFrom`）
+//! - 区域 → `MultiPolygon`（合并轮廓）
+//! - 任意 `geo_types::Geometry` ↔ [`Geometry`]，可直接交给覆盖器
+
+use geo_types::{coord, Coord as GeoCoord, LineString, MultiPolygon, Point, Polygon, Rect};
+use std::fmt;
+
+use crate::geometry::{Coord, Geometry};
+use crate::spatial::{GeoSotCell, GeoSotRegion};
+
+/// 几何与任何层级的网格范围都不一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotAGridCell;
+
+impl fmt::Display for NotAGridCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "geometry does not match the bounds of any GeoSOT cell")
+    }
+}
+
+impl std::error::Error for NotAGridCell {}
+
+impl From<GeoSotCell> for Rect<f64> {
+    fn from(cell: GeoSotCell) -> Rect<f64> {
+        let (min_lng, min_lat, max_lng, max_lat) = cell.bounds();
+        Rect::new(coord! { x: min_lng, y: min_lat }, coord! { x: max_lng, y: max_lat })
+    }
+}
+
+impl From<GeoSotCell> for Polygon<f64> {
+    fn from(cell: GeoSotCell) -> Polygon<f64> {
+        Rect::from(cell).to_polygon()
+    }
+}
+
+impl From<GeoSotCell> for Point<f64> {
+    fn from(cell: GeoSotCell) -> Point<f64> {
+        let (lng, lat) = cell.center();
+        Point::new(lng, lat)
+    }
+}
+
+impl TryFrom<Rect<f64>> for GeoSotCell {
+    type Error = NotAGridCell;
+
+    /// 查找范围与矩形一致（容差 1e-9 度）的网格，从粗到细返回第一个匹配的层级
+    fn try_from(rect: Rect<f64>) -> Result<Self, NotAGridCell> {
+        let center = rect.center();
+        let same = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        (1..=32)
+            .map(|level| GeoSotCell::from_coords(center.x, center.y, level))
+            .find(|cell| {
+                let (min_lng, min_lat, max_lng, max_lat) = cell.bounds();
+                same(min_lng, rect.min().x) && same(min_lat, rect.min().y) && same(max_lng, rect.max().x) && same(max_lat, rect.max().y)
+            })
+            .ok_or(NotAGridCell)
+    }
+}
+
+impl TryFrom<Polygon<f64>> for GeoSotCell {
+    type Error = NotAGridCell;
+
+    /// 多边形必须是无洞的轴对齐矩形
+    fn try_from(polygon: Polygon<f64>) -> Result<Self, NotAGridCell> {
+        if !polygon.interiors().is_empty() {
+            return Err(NotAGridCell);
+        }
+        let mut points: Vec<GeoCoord<f64>> = polygon.exterior().0.clone();
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() != 4 {
+            return Err(NotAGridCell);
+        }
+        let rect = Rect::new(points[0], points[2]);
+        let corners = [rect.min(), rect.max(), coord! { x: rect.min().x, y: rect.max().y }, coord! { x: rect.max().x, y: rect.min().y }];
+        if !points.iter().all(|p| corners.contains(p)) {
+            return Err(NotAGridCell);
+        }
+        GeoSotCell::try_from(rect)
+    }
+}
+
+impl GeoSotCell {
+    /// geo-types 点所在的网格
+    pub fn from_point(point: Point<f64>, level: usize) -> Self {
+        GeoSotCell::from_coords(point.x(), point.y(), level)
+    }
+}
+
+impl From<&GeoSotRegion> for MultiPolygon<f64> {
+    fn from(region: &GeoSotRegion) -> MultiPolygon<f64> {
+        match Geometry::MultiPolygon(region.outline()).into() {
+            geo_types::Geometry::MultiPolygon(polygons) => polygons,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl GeoSotRegion {
+    /// 覆盖任意 geo-types 几何，返回指定层级的区域
+    pub fn cover<G>(geometry: &G, level: usize) -> Self
+    where
+        G: Clone + Into<geo_types::Geometry<f64>>,
+    {
+        let geometry: geo_types::Geometry<f64> = geometry.clone().into();
+        Self::cover_geometry(&geometry.into(), level)
+    }
+
+    /// 每个网格转为一个 geo-types 多边形
+    pub fn to_polygons(&self) -> Vec<Polygon<f64>> {
+        self.cells().into_iter().map(Polygon::from).collect()
+    }
+}
+
+fn coords(line: &LineString<f64>) -> Vec<Coord> {
+    line.coords().map(|c| (c.x, c.y)).collect()
+}
+
+fn rings(polygon: &Polygon<f64>) -> Vec<Vec<Coord>> {
+    std::iter::once(polygon.exterior()).chain(polygon.interiors()).map(coords).collect()
+}
+
+fn line_string(coords: &[Coord]) -> LineString<f64> {
+    coords.iter().map(|&(x, y)| coord! { x: x, y: y }).collect()
+}
+
+fn polygon(rings: &[Vec<Coord>]) -> Polygon<f64> {
+    match rings.split_first() {
+        Some((exterior, interiors)) => Polygon::new(line_string(exterior), interiors.iter().map(|r| line_string(r)).collect()),
+        None => Polygon::new(LineString::new(Vec::new()), Vec::new()),
+    }
+}
+
+impl From<geo_types::Geometry<f64>> for Geometry {
+    fn from(geometry: geo_types::Geometry<f64>) -> Geometry {
+        use geo_types::Geometry as G;
+        match geometry {
+            G::Point(p) => Geometry::Point((p.x(), p.y())),
+            G::Line(l) => Geometry::LineString(vec![(l.start.x, l.start.y), (l.end.x, l.end.y)]),
+            G::LineString(l) => Geometry::LineString(coords(&l)),
+            G::Polygon(p) => Geometry::Polygon(rings(&p)),
+            G::MultiPoint(mp) => Geometry::MultiPoint(mp.iter().map(|p| (p.x(), p.y())).collect()),
+            G::MultiLineString(ml) => Geometry::MultiLineString(ml.iter().map(coords).collect()),
+            G::MultiPolygon(mp) => Geometry::MultiPolygon(mp.iter().map(rings).collect()),
+            G::GeometryCollection(gc) => Geometry::GeometryCollection(gc.into_iter().map(Geometry::from).collect()),
+            G::Rect(r) => Geometry::Polygon(rings(&r.to_polygon())),
+            G::Triangle(t) => Geometry::Polygon(rings(&t.to_polygon())),
+        }
+    }
+}
+
+impl From<Geometry> for geo_types::Geometry<f64> {
+    fn from(geometry: Geometry) -> geo_types::Geometry<f64> {
+        use geo_types::Geometry as G;
+        match geometry {
+            Geometry::Point((x, y)) => G::Point(Point::new(x, y)),
+            Geometry::MultiPoint(points) => G::MultiPoint(points.into_iter().map(Point::from).collect()),
+            Geometry::LineString(line) => G::LineString(line_string(&line)),
+            Geometry::MultiLineString(lines) => G::MultiLineString(lines.iter().map(|l| line_string(l)).collect()),
+            Geometry::Polygon(rings) => G::Polygon(polygon(&rings)),
+            Geometry::MultiPolygon(polygons) => G::MultiPolygon(polygons.iter().map(|p| polygon(p)).collect()),
+            Geometry::GeometryCollection(items) => {
+                G::GeometryCollection(items.into_iter().map(geo_types::Geometry::from).collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{line_string, point, polygon};
+
+    #[test]
+    fn test_cell_conversions() {
+        let cell = GeoSotCell::from_coords(116.3, 39.9, 15);
+        let rect = Rect::from(cell);
+        assert!((rect.width() - 1.0 / 60.0).abs() < 1e-12);
+        assert_eq!(GeoSotCell::try_from(rect), Ok(cell));
+        assert_eq!(GeoSotCell::try_from(Polygon::from(cell)), Ok(cell));
+
+        let center = Point::from(cell);
+        assert_eq!(GeoSotCell::from_point(center, 15), cell);
+
+        let off_grid = Rect::new(coord! { x: 116.3, y: 39.9 }, coord! { x: 116.31, y: 39.91 });
+        assert_eq!(GeoSotCell::try_from(off_grid), Err(NotAGridCell));
+    }
+
+    #[test]
+    fn test_cover_geo_geometries() {
+        let p = point! { x: 116.0, y: 39.0 };
+        assert_eq!(GeoSotRegion::cover(&p, 20).size(), 1);
+
+        let rect = Rect::new(coord! { x: 116.0, y: 39.0 }, coord! { x: 116.5, y: 39.5 });
+        assert_eq!(GeoSotRegion::cover(&rect, 15).size(), 30 * 30);
+
+        let poly = polygon![(x: 116.0, y: 39.0), (x: 116.5, y: 39.0), (x: 116.5, y: 39.5), (x: 116.0, y: 39.5)];
+        assert_eq!(GeoSotRegion::cover(&poly, 15), GeoSotRegion::cover(&rect, 15));
+
+        let line = line_string![(x: 116.0, y: 39.0), (x: 116.1, y: 39.0)];
+        assert_eq!(GeoSotRegion::cover(&line, 15).size(), 7);
+    }
+
+    #[test]
+    fn test_region_to_geo() {
+        let region = GeoSotRegion::cover(&Rect::new(coord! { x: 116.0, y: 39.0 }, coord! { x: 116.5, y: 39.5 }), 15);
+        let outline = MultiPolygon::from(&region);
+        assert_eq!(outline.0.len(), 1);
+        assert_eq!(GeoSotRegion::cover(&outline, 15), region);
+        assert_eq!(region.to_polygons().len(), region.size());
+    }
+}
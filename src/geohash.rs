@@ -0,0 +1,231 @@
+//! GeoSOT 网格与 Geohash 的互相转换
+//!
+//! Geohash 的每个字符携带 5 位，经度、纬度位交替出现（经度在先），
+//! 因此奇数精度的 Geohash 网格宽是高的 2 倍，偶数精度为正方形。
+
+use pyo3::prelude::*;
+
+use crate::geometry::Geometry;
+use crate::spatial::{GeoSotCell, GeoSotRegion};
+use crate::utils::get_cell_size_in_degree;
+
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// 支持的最大 Geohash 精度（60 位）
+pub const MAX_GEOHASH_PRECISION: usize = 12;
+
+/// 指定精度下经度、纬度各占的位数
+fn axis_bits(precision: usize) -> (u32, u32) {
+    let bits = 5 * precision as u32;
+    (bits.div_ceil(2), bits / 2)
+}
+
+/// 指定精度的 Geohash 网格大小 (经度跨度, 纬度跨度)，单位为度
+pub fn geohash_cell_size(precision: usize) -> (f64, f64) {
+    let (lng_bits, lat_bits) = axis_bits(precision);
+    (360.0 / (1u64 << lng_bits) as f64, 180.0 / (1u64 << lat_bits) as f64)
+}
+
+/// 由经纬度方向的网格序号拼出 Geohash 字符串
+fn geohash_from_index(x: u64, y: u64, precision: usize) -> String {
+    let (lng_bits, lat_bits) = axis_bits(precision);
+    let (mut x_pos, mut y_pos) = (lng_bits, lat_bits);
+    let mut hash = String::with_capacity(precision);
+    let mut chunk = 0usize;
+    for i in 0..5 * precision {
+        let bit = if i % 2 == 0 {
+            x_pos -= 1;
+            (x >> x_pos) & 1
+        } else {
+            y_pos -= 1;
+            (y >> y_pos) & 1
+        };
+        chunk = (chunk << 1) | bit as usize;
+        if i % 5 == 4 {
+            hash.push(BASE32[chunk] as char);
+            chunk = 0;
+        }
+    }
+    hash
+}
+
+/// 解析 Geohash，返回经纬度方向的网格序号与精度
+fn geohash_index(hash: &str) -> Option<(u64, u64, usize)> {
+    let precision = hash.len();
+    if !(1..=MAX_GEOHASH_PRECISION).contains(&precision) {
+        return None;
+    }
+    let (mut x, mut y) = (0u64, 0u64);
+    let mut i = 0;
+    for ch in hash.bytes() {
+        let value = BASE32.iter().position(|&c| c == ch.to_ascii_lowercase())?;
+        for shift in (0..5).rev() {
+            let bit = (value >> shift) as u64 & 1;
+            if i % 2 == 0 {
+                x = (x << 1) | bit;
+            } else {
+                y = (y << 1) | bit;
+            }
+            i += 1;
+        }
+    }
+    Some((x, y, precision))
+}
+
+/// 将经纬度编码为 Geohash
+/// # 参数
+/// - `lng`: 经度
+/// - `lat`: 纬度
+/// - `precision`: 字符数, 取值范围 [1~12]
+/// # Examples
+/// ```
+/// use geosot::geohash::geohash_encode;
+/// assert_eq!(geohash_encode(-5.6, 42.6, 5), "ezs42");
+/// ```
+#[pyfunction]
+pub fn geohash_encode(lng: f64, lat: f64, precision: usize) -> String {
+    let precision = precision.clamp(1, MAX_GEOHASH_PRECISION);
+    let (lng_bits, lat_bits) = axis_bits(precision);
+    let index = |v: f64, min: f64, span: f64, bits: u32| {
+        let n = 1u64 << bits;
+        (((v - min) / span * n as f64).floor().max(0.0) as u64).min(n - 1)
+    };
+    let x = index(lng, -180.0, 360.0, lng_bits);
+    let y = index(lat, -90.0, 180.0, lat_bits);
+    geohash_from_index(x, y, precision)
+}
+
+/// 获取 Geohash 网格的经纬度范围 (min_lng, min_lat, max_lng, max_lat)，格式不合法时返回 None
+#[pyfunction]
+pub fn geohash_bounds(hash: &str) -> Option<(f64, f64, f64, f64)> {
+    let (x, y, precision) = geohash_index(hash)?;
+    let (width, height) = geohash_cell_size(precision);
+    let min_lng = -180.0 + x as f64 * width;
+    let min_lat = -90.0 + y as f64 * height;
+    Some((min_lng, min_lat, min_lng + width, min_lat + height))
+}
+
+/// 网格面积的对数差，用于比较两种网格的大小
+fn size_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 * a.1) / (b.0 * b.1)).ln().abs()
+}
+
+fn geosot_cell_size(level: usize) -> (f64, f64) {
+    let size = get_cell_size_in_degree(level as i32).unwrap();
+    (size, size)
+}
+
+/// 面积与指定 GeoSOT 层级最接近的 Geohash 精度
+#[pyfunction]
+pub fn geohash_precision_for_level(level: usize) -> usize {
+    let target = geosot_cell_size(level.clamp(1, 32));
+    (1..=MAX_GEOHASH_PRECISION)
+        .min_by(|&a, &b| size_distance(geohash_cell_size(a), target).total_cmp(&size_distance(geohash_cell_size(b), target)))
+        .unwrap()
+}
+
+/// 面积与指定 Geohash 精度最接近的 GeoSOT 层级
+#[pyfunction]
+pub fn level_for_geohash_precision(precision: usize) -> usize {
+    let target = geohash_cell_size(precision.clamp(1, MAX_GEOHASH_PRECISION));
+    (1..=32)
+        .min_by(|&a, &b| size_distance(geosot_cell_size(a), target).total_cmp(&size_distance(geosot_cell_size(b), target)))
+        .unwrap()
+}
+
+impl GeoSotCell {
+    /// 覆盖该网格的所有指定精度 Geohash（只在边线上接触的不计入）
+    pub fn to_geohashes(&self, precision: usize) -> Vec<String> {
+        let precision = precision.clamp(1, MAX_GEOHASH_PRECISION);
+        let (lng_bits, lat_bits) = axis_bits(precision);
+        let (width, height) = geohash_cell_size(precision);
+        let (min_lng, min_lat, max_lng, max_lat) = self.bounds();
+
+        // 边界恰好重合时浮点误差不应带入相邻的 Geohash
+        const EPS: f64 = 1e-9;
+        let range = |min: f64, max: f64, origin: f64, span: f64, bits: u32| {
+            let n = 1u64 << bits;
+            let lo = ((min - origin) / span + EPS).floor().max(0.0) as u64;
+            let hi = ((max - origin) / span - EPS).ceil().max(lo as f64 + 1.0) as u64;
+            lo.min(n - 1)..hi.min(n)
+        };
+        let xs = range(min_lng, max_lng, -180.0, width, lng_bits);
+        let ys = range(min_lat, max_lat, -90.0, height, lat_bits);
+
+        let mut hashes: Vec<String> = ys
+            .flat_map(|y| xs.clone().map(move |x| geohash_from_index(x, y, precision)))
+            .collect();
+        hashes.sort();
+        hashes
+    }
+}
+
+impl GeoSotRegion {
+    /// 覆盖 Geohash 网格的指定层级 GeoSOT 区域，Geohash 不合法时返回 None
+    pub fn from_geohash(hash: &str, level: usize) -> Option<Self> {
+        let (min_lng, min_lat, max_lng, max_lat) = geohash_bounds(hash)?;
+        let ring = vec![(min_lng, min_lat), (max_lng, min_lat), (max_lng, max_lat), (min_lng, max_lat), (min_lng, min_lat)];
+        Some(Self::cover_geometry(&Geometry::Polygon(vec![ring]), level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        assert_eq!(geohash_encode(-5.6, 42.6, 5), "ezs42");
+        assert_eq!(geohash_encode(116.3912, 39.9075, 8), "wx4g08cq");
+
+        let (min_lng, min_lat, max_lng, max_lat) = geohash_bounds("ezs42").unwrap();
+        assert!(min_lng < -5.6 && -5.6 < max_lng && min_lat < 42.6 && 42.6 < max_lat);
+        assert_eq!(geohash_bounds("EZS42"), geohash_bounds("ezs42"));
+        assert_eq!(geohash_bounds("ezs4a"), None);
+        assert_eq!(geohash_bounds(""), None);
+
+        for precision in 1..=MAX_GEOHASH_PRECISION {
+            let hash = geohash_encode(116.3912, 39.9075, precision);
+            let (x, y, _) = geohash_index(&hash).unwrap();
+            assert_eq!(geohash_from_index(x, y, precision), hash);
+        }
+    }
+
+    #[test]
+    fn test_precision_pairs() {
+        // 16 级网格为 32 秒（约 0.0089 度），6 位 Geohash 约 0.011 x 0.0055 度
+        assert_eq!(geohash_precision_for_level(16), 6);
+        assert_eq!(level_for_geohash_precision(6), 16);
+        for level in 1..=32 {
+            let precision = geohash_precision_for_level(level);
+            assert!((1..=MAX_GEOHASH_PRECISION).contains(&precision));
+        }
+    }
+
+    #[test]
+    fn test_cell_to_geohashes() {
+        let cell = GeoSotCell::from_coords(116.3912, 39.9075, 15);
+        let hashes = cell.to_geohashes(6);
+        assert!(hashes.contains(&geohash_encode(116.3912, 39.9075, 6)));
+        let (min_lng, min_lat, max_lng, max_lat) = cell.bounds();
+        for hash in &hashes {
+            let (a, b, c, d) = geohash_bounds(hash).unwrap();
+            assert!(a < max_lng && c > min_lng && b < max_lat && d > min_lat);
+        }
+
+        // 粗精度的 Geohash 包含整个网格
+        assert_eq!(cell.to_geohashes(3), vec![geohash_encode(116.3912, 39.9075, 3)]);
+    }
+
+    #[test]
+    fn test_region_from_geohash() {
+        let hash = "wx4g0";
+        let region = GeoSotRegion::from_geohash(hash, 15).unwrap();
+        let (min_lng, min_lat, max_lng, max_lat) = geohash_bounds(hash).unwrap();
+        assert!(region.contains_point((min_lng + max_lng) / 2.0, (min_lat + max_lat) / 2.0));
+        for cell in region.cells() {
+            assert!(cell.to_geohashes(5).contains(&hash.to_string()));
+        }
+        assert!(GeoSotRegion::from_geohash("wx4a", 15).is_none());
+    }
+}
@@ -0,0 +1,546 @@
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::geometry::{Coord, Geometry, ParseError};
+use crate::spatial::{GeoSotCell, GeoSotRegion};
+use crate::utils::TICKS_PER_DEGREE;
+
+/// 刻度坐标 (经度, 纬度)
+type Point = (i64, i64);
+
+/// 多边形：第一个环为外环（逆时针），其余为洞（顺时针），环首尾闭合
+pub type Polygon = Vec<Vec<(f64, f64)>>;
+
+impl GeoSotCell {
+    /// 转为 GeoJSON Feature 字符串
+    ///
+    /// `code` 属性以十进制字符串输出，避免 JavaScript 中超过 2^53 的整数丢失精度。
+    pub fn to_geojson(&self) -> String {
+        self.geojson_feature().to_string()
+    }
+
+    /// 转为 GeoJSON Feature 对象
+    pub fn geojson_feature(&self) -> Value {
+        let (x0, y0, x1, y1) = clamp_bounds(self.tick_bounds());
+        let ring = vec![(x0, y0), (x1, y0), (x1, y1), (x0, y1), (x0, y0)];
+        json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [ring_coordinates(&ring)],
+            },
+            "properties": {
+                "code": self.code.to_string(),
+                "level": self.level,
+                "grid": self.to_string(),
+            },
+        })
+    }
+}
+
+impl GeoSotRegion {
+    /// 转为 GeoJSON FeatureCollection 字符串
+    ///
+    /// # 参数
+    /// * `dissolve` - 为 false 时每个网格输出一个 Polygon Feature；
+    ///   为 true 时将区域合并为单个 (Multi)Polygon 轮廓
+    pub fn to_geojson_feature_collection(&self, dissolve: bool) -> String {
+        self.geojson_feature_collection(dissolve).to_string()
+    }
+
+    /// 转为 GeoJSON FeatureCollection 对象
+    pub fn geojson_feature_collection(&self, dissolve: bool) -> Value {
+        let features: Vec<Value> = if dissolve {
+            self.geojson_outline().into_iter().collect()
+        } else {
+            self.codes
+                .iter()
+                .map(|code| GeoSotCell::new(code, self.level).geojson_feature())
+                .collect()
+        };
+        json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+    }
+
+    /// 将区域合并为单个 (Multi)Polygon Feature，空区域返回 None
+    pub fn geojson_outline(&self) -> Option<Value> {
+        let polygons = dissolve_ticks(self);
+        let geometry = match polygons.len() {
+            0 => return None,
+            1 => json!({
+                "type": "Polygon",
+                "coordinates": polygon_coordinates(&polygons[0]),
+            }),
+            _ => json!({
+                "type": "MultiPolygon",
+                "coordinates": polygons.iter().map(|p| polygon_coordinates(p)).collect::<Vec<_>>(),
+            }),
+        };
+        Some(json!({
+            "type": "Feature",
+            "geometry": geometry,
+            "properties": {
+                "level": self.level,
+                "cells": self.size(),
+            },
+        }))
+    }
+
+    /// 计算区域的合并轮廓
+    ///
+    /// 共享的网格边相互抵消，剩余边首尾相连得到外环与洞，
+    /// 仅在角点接触的网格会被拆成不同的多边形。
+    pub fn outline(&self) -> Vec<Polygon> {
+        dissolve_ticks(self)
+            .iter()
+            .map(|rings| rings.iter().map(|ring| ring.iter().map(|&p| to_degrees(p)).collect()).collect())
+            .collect()
+    }
+}
+
+/// 按 JSON 路径描述的结构错误，如 `$.features[0].geometry: missing member 'type'`
+type StructureError = String;
+
+fn member<'a>(object: &'a Value, key: &str, path: &str) -> Result<&'a Value, StructureError> {
+    object.get(key).ok_or_else(|| format!("{path}: missing member '{key}'"))
+}
+
+fn array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, StructureError> {
+    value.as_array().ok_or_else(|| format!("{path}: expected an array"))
+}
+
+/// GeoJSON 坐标 [经度, 纬度, (高程)]，高程忽略
+fn position(value: &Value, path: &str) -> Result<Coord, StructureError> {
+    let values = array(value, path)?;
+    let number = |i: usize| values[i].as_f64().ok_or_else(|| format!("{path}[{i}]: expected a number"));
+    if values.len() < 2 {
+        return Err(format!(
+            "{path}: invalid length {}, expected a position with at least 2 elements",
+            values.len()
+        ));
+    }
+    Ok((number(0)?, number(1)?))
+}
+
+/// 逐项解析数组成员，路径带上下标
+fn items<T>(
+    value: &Value,
+    path: &str,
+    item: impl Fn(&Value, &str) -> Result<T, StructureError>,
+) -> Result<Vec<T>, StructureError> {
+    array(value, path)?
+        .iter()
+        .enumerate()
+        .map(|(i, v)| item(v, &format!("{path}[{i}]")))
+        .collect()
+}
+
+fn positions(value: &Value, path: &str) -> Result<Vec<Coord>, StructureError> {
+    items(value, path, position)
+}
+
+fn rings(value: &Value, path: &str) -> Result<Vec<Vec<Coord>>, StructureError> {
+    items(value, path, positions)
+}
+
+/// GeoJSON 对象（RFC 7946），仅读取几何相关成员，其余成员忽略
+fn geojson_object(object: &Value, path: &str) -> Result<Geometry, StructureError> {
+    let kind = member(object, "type", path)?
+        .as_str()
+        .ok_or_else(|| format!("{path}.type: expected a string"))?;
+    let coordinates = || member(object, "coordinates", path).map(|v| (v, format!("{path}.coordinates")));
+    Ok(match kind {
+        "Point" => {
+            let (value, path) = coordinates()?;
+            Geometry::Point(position(value, &path)?)
+        }
+        "MultiPoint" => {
+            let (value, path) = coordinates()?;
+            Geometry::MultiPoint(positions(value, &path)?)
+        }
+        "LineString" => {
+            let (value, path) = coordinates()?;
+            Geometry::LineString(positions(value, &path)?)
+        }
+        "MultiLineString" => {
+            let (value, path) = coordinates()?;
+            Geometry::MultiLineString(rings(value, &path)?)
+        }
+        "Polygon" => {
+            let (value, path) = coordinates()?;
+            Geometry::Polygon(rings(value, &path)?)
+        }
+        "MultiPolygon" => {
+            let (value, path) = coordinates()?;
+            Geometry::MultiPolygon(items(value, &path, rings)?)
+        }
+        "GeometryCollection" => {
+            let geometries = member(object, "geometries", path)?;
+            Geometry::GeometryCollection(items(geometries, &format!("{path}.geometries"), geojson_object)?)
+        }
+        // 没有几何的要素视为空集合
+        "Feature" => match object.get("geometry") {
+            None | Some(Value::Null) => Geometry::GeometryCollection(Vec::new()),
+            Some(geometry) => geojson_object(geometry, &format!("{path}.geometry"))?,
+        },
+        "FeatureCollection" => {
+            let features = member(object, "features", path)?;
+            Geometry::GeometryCollection(items(features, &format!("{path}.features"), geojson_object)?)
+        }
+        other => return Err(format!("{path}.type: unknown GeoJSON type '{other}'")),
+    })
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(err: serde_json::Error) -> ParseError {
+        // serde_json 的错误信息末尾带有 " at line X column Y"，位置单独保存
+        let message = err.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(idx) => message[..idx].to_string(),
+            None => message,
+        };
+        ParseError::new(err.line(), err.column(), message)
+    }
+}
+
+impl Geometry {
+    /// 从 GeoJSON 文本解析几何
+    ///
+    /// 支持全部七种几何类型以及 Feature、FeatureCollection，
+    /// 要素集合会合并为一个 GeometryCollection。
+    /// JSON 语法错误带有行列号；结构错误没有行列号，消息中给出 JSON 路径。
+    pub fn from_geojson(text: &str) -> Result<Geometry, ParseError> {
+        let value: Value = serde_json::from_str(text)?;
+        geojson_object(&value, "$").map_err(ParseError::unpositioned)
+    }
+}
+
+fn to_degrees((x, y): Point) -> (f64, f64) {
+    (x as f64 / TICKS_PER_DEGREE as f64, y as f64 / TICKS_PER_DEGREE as f64)
+}
+
+fn clamp_bounds((x0, y0, x1, y1): (i64, i64, i64, i64)) -> (i64, i64, i64, i64) {
+    let max_x = 180 * TICKS_PER_DEGREE;
+    let max_y = 90 * TICKS_PER_DEGREE;
+    (
+        x0.clamp(-max_x, max_x),
+        y0.clamp(-max_y, max_y),
+        x1.clamp(-max_x, max_x),
+        y1.clamp(-max_y, max_y),
+    )
+}
+
+fn ring_coordinates(ring: &[Point]) -> Value {
+    Value::Array(
+        ring.iter()
+            .map(|&p| {
+                let (x, y) = to_degrees(p);
+                json!([x, y])
+            })
+            .collect(),
+    )
+}
+
+fn polygon_coordinates(rings: &[Vec<Point>]) -> Value {
+    Value::Array(rings.iter().map(|ring| ring_coordinates(ring)).collect())
+}
+
+/// 合并区域内所有网格，返回刻度坐标下的多边形（外环在前，洞在后，环首尾闭合）
+fn dissolve_ticks(region: &GeoSotRegion) -> Vec<Vec<Vec<Point>>> {
+    // 逆时针遍历每个网格的四条边，反向边成对抵消
+    let mut edges: BTreeSet<(Point, Point)> = BTreeSet::new();
+    for code in region.codes.iter() {
+        let (x0, y0, x1, y1) = clamp_bounds(GeoSotCell::new(code, region.level).tick_bounds());
+        if x0 == x1 || y0 == y1 {
+            continue;
+        }
+        let corners = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)];
+        for i in 0..4 {
+            let edge = (corners[i], corners[(i + 1) % 4]);
+            if !edges.remove(&(edge.1, edge.0)) {
+                edges.insert(edge);
+            }
+        }
+    }
+
+    let mut outgoing: BTreeMap<Point, Vec<Point>> = BTreeMap::new();
+    for &(a, b) in &edges {
+        outgoing.entry(a).or_default().push(b);
+    }
+
+    let mut outers = Vec::new();
+    let mut holes = Vec::new();
+    while let Some(ring) = trace_ring(&mut outgoing) {
+        if signed_area(&ring) > 0 {
+            outers.push(vec![ring]);
+        } else {
+            holes.push(ring);
+        }
+    }
+
+    for hole in holes {
+        let probe = hole_probe(&hole);
+        let owner = outers
+            .iter_mut()
+            .filter(|polygon| contains_doubled(&polygon[0], probe))
+            .min_by_key(|polygon| signed_area(&polygon[0]));
+        if let Some(polygon) = owner {
+            polygon.push(hole);
+        }
+    }
+    outers
+}
+
+/// 从剩余边中取出一个闭合环，在多条出边的顶点处总是选择最靠左的转向
+fn trace_ring(outgoing: &mut BTreeMap<Point, Vec<Point>>) -> Option<Vec<Point>> {
+    let start = *outgoing.keys().next()?;
+    let first = take_edge(outgoing, start, 0);
+    let mut ring = vec![start];
+    let (mut prev, mut cur) = (start, first);
+
+    loop {
+        let incoming = (cur.0 - prev.0, cur.1 - prev.1);
+        let mut candidates: Vec<Point> = outgoing.get(&cur).cloned().unwrap_or_default();
+        if cur == start {
+            candidates.push(first);
+        }
+        let next = *candidates
+            .iter()
+            .max_by_key(|&&p| turn_rank(incoming, (p.0 - cur.0, p.1 - cur.1)))
+            .expect("dangling boundary edge");
+        if cur == start && next == first {
+            break;
+        }
+        let idx = candidates.iter().position(|&p| p == next).unwrap();
+        take_edge(outgoing, cur, idx);
+        ring.push(cur);
+        prev = cur;
+        cur = next;
+    }
+
+    ring.push(start);
+    Some(simplify(ring))
+}
+
+fn take_edge(outgoing: &mut BTreeMap<Point, Vec<Point>>, from: Point, idx: usize) -> Point {
+    let ends = outgoing.get_mut(&from).unwrap();
+    let to = ends.swap_remove(idx);
+    if ends.is_empty() {
+        outgoing.remove(&from);
+    }
+    to
+}
+
+/// 转向优先级：左转 > 直行 > 右转
+fn turn_rank(a: Point, b: Point) -> i32 {
+    let cross = a.0.signum() * b.1.signum() - a.1.signum() * b.0.signum();
+    let dot = a.0.signum() * b.0.signum() + a.1.signum() * b.1.signum();
+    match (cross, dot) {
+        (c, _) if c > 0 => 2,
+        (0, d) if d > 0 => 1,
+        (c, _) if c < 0 => 0,
+        _ => -1,
+    }
+}
+
+/// 去除共线的中间点（输入与输出均首尾闭合）
+fn simplify(ring: Vec<Point>) -> Vec<Point> {
+    let n = ring.len() - 1;
+    let mut out: Vec<Point> = (0..n)
+        .filter(|&i| {
+            let prev = ring[(i + n - 1) % n];
+            let next = ring[(i + 1) % n];
+            let cur = ring[i];
+            !((prev.0 == cur.0 && cur.0 == next.0) || (prev.1 == cur.1 && cur.1 == next.1))
+        })
+        .map(|i| ring[i])
+        .collect();
+    out.push(out[0]);
+    out
+}
+
+/// 环的有向面积的两倍，逆时针为正
+fn signed_area(ring: &[Point]) -> i128 {
+    ring.windows(2)
+        .map(|w| w[0].0 as i128 * w[1].1 as i128 - w[1].0 as i128 * w[0].1 as i128)
+        .sum()
+}
+
+/// 在洞的一条水平边中点向实体一侧偏移半个刻度，得到坐标加倍后的探测点
+fn hole_probe(hole: &[Point]) -> Point {
+    let w = hole
+        .windows(2)
+        .find(|w| w[0].1 == w[1].1)
+        .expect("rectilinear ring has a horizontal edge");
+    let (a, b) = (w[0], w[1]);
+    // 环的左侧为实体：向 +x 行进时左侧在上方
+    let offset = if b.0 > a.0 { 1 } else { -1 };
+    (a.0 + b.0, a.1 * 2 + offset)
+}
+
+/// 判断坐标加倍后的探测点是否在环内（射线法，探测点纵坐标为奇数，不会落在顶点上）
+fn contains_doubled(ring: &[Point], probe: Point) -> bool {
+    let mut inside = false;
+    for w in ring.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        if a.0 != b.0 {
+            continue;
+        }
+        let (y0, y1) = (a.1.min(b.1) * 2, a.1.max(b.1) * 2);
+        if a.0 * 2 > probe.0 && y0 < probe.1 && probe.1 < y1 {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_feature() {
+        let cell = GeoSotCell::from_coords(116.0, 39.0, 15);
+        let feature = cell.geojson_feature();
+        assert_eq!(feature["properties"]["level"], 15);
+        assert_eq!(feature["properties"]["code"], cell.code.to_string());
+        assert_eq!(feature["properties"]["grid"], cell.to_string());
+
+        let ring = feature["geometry"]["coordinates"][0].as_array().unwrap();
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring[0], ring[4]);
+        let (min_lng, min_lat, max_lng, max_lat) = cell.bounds();
+        assert_eq!(ring[0], json!([min_lng, min_lat]));
+        assert_eq!(ring[2], json!([max_lng, max_lat]));
+        assert!((max_lng - min_lng - 1.0 / 60.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_feature_collection() {
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.05, 39.05, 15);
+        let collection = region.geojson_feature_collection(false);
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["features"].as_array().unwrap().len(), region.size());
+    }
+
+    #[test]
+    fn test_dissolve_rectangle() {
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.05, 39.05, 15);
+        let polygons = region.outline();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].len(), 1);
+        // 矩形合并后只剩 4 个角点
+        assert_eq!(polygons[0][0].len(), 5);
+
+        let collection = region.geojson_feature_collection(true);
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0]["geometry"]["type"], "Polygon");
+        assert_eq!(features[0]["properties"]["cells"], region.size());
+    }
+
+    #[test]
+    fn test_dissolve_hole_and_diagonal() {
+        let level = 15;
+        let step = 1.0 / 60.0;
+        let at = |i: i32, j: i32| crate::get_code(116.0 + (i as f64 + 0.5) * step, 39.0 + (j as f64 + 0.5) * step, level);
+
+        // 3x3 的环，中间留洞
+        let mut ring = GeoSotRegion::new(level);
+        for i in 0..3 {
+            for j in 0..3 {
+                if (i, j) != (1, 1) {
+                    ring.add_code(at(i, j));
+                }
+            }
+        }
+        let polygons = ring.outline();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].len(), 2);
+
+        // 仅角点接触的两个网格拆为 MultiPolygon
+        let diagonal = GeoSotRegion::from_codes(vec![at(0, 0), at(1, 1)], level);
+        assert_eq!(diagonal.outline().len(), 2);
+        let feature = diagonal.geojson_outline().unwrap();
+        assert_eq!(feature["geometry"]["type"], "MultiPolygon");
+
+        assert!(GeoSotRegion::new(level).geojson_outline().is_none());
+    }
+
+    #[test]
+    fn test_dissolve_across_degree_boundary() {
+        // 8 分网格在 56~60 分处被截断，与下一度的网格无缝拼接
+        let level = 12;
+        let mut region = GeoSotRegion::new(level);
+        for minutes in [50.0, 58.0, 62.0] {
+            region.add_point(116.0 + minutes / 60.0, 39.0);
+        }
+        assert_eq!(region.size(), 3);
+        let polygons = region.outline();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0][0].len(), 5);
+
+        // 跨越本初子午线与赤道的网格同样无缝拼接
+        let region = GeoSotRegion::from_codes(
+            [(-0.5, -0.5), (0.5, -0.5), (-0.5, 0.5), (0.5, 0.5)]
+                .iter()
+                .map(|&(x, y)| crate::get_code(x / 60.0, y / 60.0, 15))
+                .collect(),
+            15,
+        );
+        let polygons = region.outline();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0][0].len(), 5);
+        let (lng, lat) = polygons[0][0][0];
+        assert!((lng.abs() - 1.0 / 60.0).abs() < 1e-12 && (lat.abs() - 1.0 / 60.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parse_geojson() {
+        let point = Geometry::from_geojson(r#"{"type": "Point", "coordinates": [116.0, 39.0, 50.0]}"#).unwrap();
+        assert_eq!(point, Geometry::Point((116.0, 39.0)));
+
+        let collection = Geometry::from_geojson(
+            r#"{"type": "FeatureCollection", "features": [
+                {"type": "Feature", "properties": {"name": "a"},
+                 "geometry": {"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]]}},
+                {"type": "Feature", "properties": null, "geometry": null}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            collection,
+            Geometry::GeometryCollection(vec![
+                Geometry::Polygon(vec![vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]]),
+                Geometry::GeometryCollection(vec![]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_geojson_errors() {
+        let err = Geometry::from_geojson("{\"type\": \"Point\",\n \"coordinates\": [1, }").unwrap_err();
+        assert_eq!(err.line(), Some(2));
+        assert!(!err.message.contains(" at line "));
+
+        let err = Geometry::from_geojson(r#"{"type": "Point", "coordinates": [1]}"#).unwrap_err();
+        assert!(err.message.contains("at least 2"), "{err}");
+        assert!(Geometry::from_geojson(r#"{"type": "Circle"}"#).is_err());
+
+        let err = Geometry::from_geojson(
+            r#"{"type": "FeatureCollection", "features": [{"type": "Feature", "geometry": {"type": "LineString"}}]}"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.position, None);
+        assert_eq!(err.to_string(), "$.features[0].geometry: missing member 'coordinates'");
+    }
+
+    #[test]
+    fn test_geojson_round_trip() {
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.05, 39.05, 15);
+        let text = region.to_geojson_feature_collection(true);
+        assert_eq!(GeoSotRegion::from_geojson(&text, 15).unwrap(), region);
+    }
+}
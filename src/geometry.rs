@@ -0,0 +1,81 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::PyErr;
+use std::fmt;
+
+/// 经纬度坐标 (经度, 纬度)
+pub type Coord = (f64, f64);
+
+/// 简单要素几何，坐标为 WGS84 经纬度
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Point(Coord),
+    MultiPoint(Vec<Coord>),
+    LineString(Vec<Coord>),
+    MultiLineString(Vec<Vec<Coord>>),
+    /// 第一个环为外环，其余为洞
+    Polygon(Vec<Vec<Coord>>),
+    MultiPolygon(Vec<Vec<Vec<Coord>>>),
+    GeometryCollection(Vec<Geometry>),
+}
+
+impl Geometry {
+    /// 判断几何是否为空
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Geometry::Point(_) => false,
+            Geometry::MultiPoint(points) => points.is_empty(),
+            Geometry::LineString(line) => line.is_empty(),
+            Geometry::MultiLineString(lines) => lines.iter().all(|l| l.is_empty()),
+            Geometry::Polygon(rings) => rings.first().is_none_or(|r| r.is_empty()),
+            Geometry::MultiPolygon(polygons) => polygons.iter().all(|p| p.first().is_none_or(|r| r.is_empty())),
+            Geometry::GeometryCollection(items) => items.iter().all(|g| g.is_empty()),
+        }
+    }
+}
+
+/// 文本（GeoJSON / WKT / MGRS 等）解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 出错位置 (行, 列)，从 1 开始；无法对应到文本位置的错误（如 GeoJSON 结构错误）为 None
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl ParseError {
+    /// 位于第 `line` 行第 `column` 列的错误
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self { position: Some((line, column)), message: message.into() }
+    }
+
+    /// 没有文本位置的错误
+    pub fn unpositioned(message: impl Into<String>) -> Self {
+        Self { position: None, message: message.into() }
+    }
+
+    /// 出错的行号
+    pub fn line(&self) -> Option<usize> {
+        self.position.map(|(line, _)| line)
+    }
+
+    /// 出错的列号
+    pub fn column(&self) -> Option<usize> {
+        self.position.map(|(_, column)| column)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "line {line}, column {column}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for PyErr {
+    fn from(err: ParseError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
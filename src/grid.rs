@@ -0,0 +1,771 @@
+//! 网格值栅格：以 GeoSOT 网格为像元的稀疏栅格
+//!
+//! `GeoSotRegion` 只描述“哪些网格”，`GeoSotGrid<T>` 在此基础上为每个网格保存一个值，
+//! 支持逐网格变换与合并、按区域掩膜、跨层级重采样，以及导出为带仿射变换参数的稠密数组。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::get_code;
+use crate::spatial::{level_mask, GeoSotCell, GeoSotRegion};
+use crate::utils::get_cell_size_in_degree;
+
+/// 跨层级重采样方式
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Resampling {
+    /// 求和；细分时平均分配到各子网格，总量不变
+    Sum,
+    /// 取均值；细分时子网格沿用原值
+    #[default]
+    Mean,
+    /// 取离父网格中心最近的子网格的值；细分时子网格沿用原值
+    Nearest,
+}
+
+/// 焦点（邻域）统计方式
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Focal {
+    Sum,
+    Mean,
+    Max,
+    Min,
+}
+
+/// 稠密栅格，按行优先、自北向南排列
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseRaster<T> {
+    pub width: usize,
+    pub height: usize,
+    /// GDAL 风格的仿射变换参数 `[左上角经度, 像元宽度, 0, 左上角纬度, 0, -像元高度]`
+    pub geotransform: [f64; 6],
+    pub data: Vec<T>,
+}
+
+impl<T> DenseRaster<T> {
+    /// 第 `row` 行第 `col` 列的像元值
+    pub fn get(&self, col: usize, row: usize) -> Option<&T> {
+        if col < self.width && row < self.height {
+            self.data.get(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    /// 按行切分的像元值
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.data.chunks(self.width.max(1))
+    }
+}
+
+/// 层级的网格在度、分内不能均匀排列，无法导出为等间距的稠密栅格
+///
+/// 10~12 级与 16~18 级的步长（32、16、8 分或秒）不能整除 60，每度或每分末尾有一个截断网格
+/// （如 8 分网格的 56~60 分），固定大小的像元会逐渐偏离网格边界。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnevenLevelError(pub usize);
+
+impl fmt::Display for UnevenLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cells of level {} do not tile evenly; resample to a level in 1~9, 13~15 or 19~32 first",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnevenLevelError {}
+
+impl From<UnevenLevelError> for PyErr {
+    fn from(err: UnevenLevelError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// 重采样变细时结果网格数的上限
+pub const MAX_REFINED_CELLS: usize = 1 << 24;
+
+/// 重采样变细产生的网格数超过 [`MAX_REFINED_CELLS`]
+///
+/// 每细分一级网格数最多变为 4 倍，跨度较大的细分（如 10 级到 32 级）会迅速耗尽内存。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefineLimitError {
+    pub from: usize,
+    pub to: usize,
+    pub cells: usize,
+}
+
+impl fmt::Display for RefineLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "refining {} cells from level {} to level {} may produce more than {} cells",
+            self.cells, self.from, self.to, MAX_REFINED_CELLS
+        )
+    }
+}
+
+impl std::error::Error for RefineLimitError {}
+
+impl From<RefineLimitError> for PyErr {
+    fn from(err: RefineLimitError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// 层级的网格是否在度、分、秒内均匀排列（步长整除 60）
+fn tiles_evenly(level: usize) -> bool {
+    matches!(level, 1..=9 | 13..=15 | 19..=32)
+}
+
+/// 以 GeoSOT 网格为像元的稀疏栅格
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GeoSotGrid<T> {
+    pub(crate) level: usize,
+    pub(crate) values: BTreeMap<u64, T>,
+}
+
+impl<T> Default for GeoSotGrid<T> {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl<T> GeoSotGrid<T> {
+    /// 创建指定层级的空栅格，层级限制在 1~32 之内
+    pub fn new(level: usize) -> Self {
+        Self { level: level.clamp(1, 32), values: BTreeMap::new() }
+    }
+
+    /// 区域内每个网格取 `f(网格)` 的值
+    pub fn from_fn(region: &GeoSotRegion, mut f: impl FnMut(GeoSotCell) -> T) -> Self {
+        let values = region.codes.iter().map(|code| (code, f(GeoSotCell::new(code, region.level)))).collect();
+        Self { level: region.level, values }
+    }
+
+    /// 栅格层级
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// 有值的网格数量
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// 是否没有任何值
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// 设置网格的值，返回原值；网格层级与栅格不同时 panic
+    pub fn insert(&mut self, cell: GeoSotCell, value: T) -> Option<T> {
+        assert_eq!(cell.level, self.level, "Cell level does not match grid level");
+        self.values.insert(cell.code, value)
+    }
+
+    /// 设置经纬度所在网格的值，返回原值
+    pub fn insert_point(&mut self, lng: f64, lat: f64, value: T) -> Option<T> {
+        self.values.insert(get_code(lng, lat, self.level), value)
+    }
+
+    /// 删除网格的值
+    pub fn remove(&mut self, cell: &GeoSotCell) -> Option<T> {
+        if cell.level != self.level {
+            return None;
+        }
+        self.values.remove(&cell.code)
+    }
+
+    /// 网格的值
+    pub fn get(&self, cell: &GeoSotCell) -> Option<&T> {
+        if cell.level != self.level {
+            return None;
+        }
+        self.values.get(&cell.code)
+    }
+
+    /// 网格的值（可变）
+    pub fn get_mut(&mut self, cell: &GeoSotCell) -> Option<&mut T> {
+        if cell.level != self.level {
+            return None;
+        }
+        self.values.get_mut(&cell.code)
+    }
+
+    /// 经纬度所在网格的值
+    pub fn value_at(&self, lng: f64, lat: f64) -> Option<&T> {
+        self.values.get(&get_code(lng, lat, self.level))
+    }
+
+    /// 是否包含网格
+    pub fn contains(&self, cell: &GeoSotCell) -> bool {
+        self.get(cell).is_some()
+    }
+
+    /// 按编码顺序遍历 (网格, 值)
+    pub fn iter(&self) -> impl Iterator<Item = (GeoSotCell, &T)> + '_ {
+        self.values.iter().map(|(&code, value)| (GeoSotCell::new(code, self.level), value))
+    }
+
+    /// 有值的网格组成的区域
+    pub fn region(&self) -> GeoSotRegion {
+        GeoSotRegion::from_codes(self.values.keys().copied().collect(), self.level)
+    }
+
+    /// 逐网格变换值
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> GeoSotGrid<U> {
+        GeoSotGrid {
+            level: self.level,
+            values: self.values.iter().map(|(&code, value)| (code, f(value))).collect(),
+        }
+    }
+
+    /// 逐网格合并两个同层级栅格，只保留两者都有值的网格；层级不同时 panic
+    pub fn zip<U, V>(&self, other: &GeoSotGrid<U>, mut f: impl FnMut(&T, &U) -> V) -> GeoSotGrid<V> {
+        assert_eq!(self.level, other.level, "Cannot zip grids with different levels");
+        let values = self
+            .values
+            .iter()
+            .filter_map(|(&code, a)| other.values.get(&code).map(|b| (code, f(a, b))))
+            .collect();
+        GeoSotGrid { level: self.level, values }
+    }
+
+    /// 只保留落在区域内的网格
+    ///
+    /// 区域层级不高于栅格时按网格的祖先判断，区域更细时网格内只要有区域网格即保留。
+    pub fn mask(&self, region: &GeoSotRegion) -> Self
+    where
+        T: Clone,
+    {
+        let keep: Box<dyn Fn(u64) -> bool> = if region.level <= self.level {
+            let mask = level_mask(region.level);
+            Box::new(move |code| region.contains(code & mask))
+        } else {
+            let mask = level_mask(self.level);
+            let ancestors: BTreeSet<u64> = region.codes.iter().map(|code| code & mask).collect();
+            Box::new(move |code| ancestors.contains(&code))
+        };
+        let values = self
+            .values
+            .iter()
+            .filter(|(&code, _)| keep(code))
+            .map(|(&code, value)| (code, value.clone()))
+            .collect();
+        Self { level: self.level, values }
+    }
+
+    /// 导出覆盖经纬度范围的稠密栅格，像元大小为本层级的网格大小
+    ///
+    /// 像元取其中心点所在网格的值，没有值或超出 ±90° 的像元填 `nodata`。
+    /// 范围向外对齐到像元大小的整数倍；`min_lng > max_lng` 时表示跨越 180° 经线，
+    /// 导出的经度连续递增，超过 180° 的像元对应另一侧的网格。
+    /// 网格不能均匀排列的层级（10~12、16~18 级）返回错误，需先用 `resample` 换到相邻层级。
+    pub fn to_dense(
+        &self,
+        min_lng: f64,
+        min_lat: f64,
+        max_lng: f64,
+        max_lat: f64,
+        nodata: T,
+    ) -> Result<DenseRaster<T>, UnevenLevelError>
+    where
+        T: Clone,
+    {
+        if !tiles_evenly(self.level) {
+            return Err(UnevenLevelError(self.level));
+        }
+        let size = get_cell_size_in_degree(self.level as i32).expect("level is within 1..=32");
+        let span = if min_lng > max_lng { max_lng + 360.0 - min_lng } else { max_lng - min_lng };
+        let left = (min_lng / size).floor() * size;
+        let top = (max_lat / size).ceil() * size;
+        let width = ((min_lng + span - left) / size).ceil().max(1.0) as usize;
+        let height = ((top - min_lat) / size).ceil().max(1.0) as usize;
+
+        let mut data = Vec::with_capacity(width * height);
+        for row in 0..height {
+            let lat = top - (row as f64 + 0.5) * size;
+            for col in 0..width {
+                let mut lng = left + (col as f64 + 0.5) * size;
+                if lng > 180.0 {
+                    lng -= 360.0;
+                } else if lng < -180.0 {
+                    lng += 360.0;
+                }
+                let value = if (-90.0..=90.0).contains(&lat) { self.value_at(lng, lat) } else { None };
+                data.push(value.cloned().unwrap_or_else(|| nodata.clone()));
+            }
+        }
+
+        Ok(DenseRaster {
+            width,
+            height,
+            geotransform: [left, size, 0.0, top, 0.0, -size],
+            data,
+        })
+    }
+}
+
+impl GeoSotGrid<f64> {
+    /// 重采样到其他层级
+    ///
+    /// 变粗时按 `parent` 上溯合并子网格；变细时把每个网格展开为目标层级的全部后代网格，
+    /// 展开后的网格数可能超过 [`MAX_REFINED_CELLS`] 时返回错误。
+    pub fn resample(&self, level: usize, method: Resampling) -> Result<Self, RefineLimitError> {
+        let level = level.clamp(1, 32);
+        if level < self.level {
+            Ok(self.coarsen(level, method))
+        } else if level > self.level {
+            self.refine(level, method)
+        } else {
+            Ok(self.clone())
+        }
+    }
+
+    /// 焦点统计：每个有值网格取其 k 圈邻域（含自身）内有值网格的统计量
+    ///
+    /// 邻域中没有值的网格不参与计算，结果只包含原本有值的网格。
+    pub fn focal(&self, k: usize, stat: Focal) -> Self {
+        let values = self
+            .iter()
+            .map(|(cell, _)| {
+                let window = cell.k_ring(k).into_iter().filter_map(|c| self.values.get(&c.code).copied());
+                let value = match stat {
+                    Focal::Sum => window.sum(),
+                    Focal::Mean => {
+                        let (sum, count) = window.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+                        sum / count as f64
+                    }
+                    Focal::Max => window.fold(f64::NEG_INFINITY, f64::max),
+                    Focal::Min => window.fold(f64::INFINITY, f64::min),
+                };
+                (cell.code, value)
+            })
+            .collect();
+        Self { level: self.level, values }
+    }
+
+    /// Getis-Ord Gi* 热点统计量（z 得分）
+    ///
+    /// 权重为二值邻接：k 圈邻域（含自身）内有值的网格权重为 1，其余为 0，
+    /// 因此区域边缘、两极和 180° 经线附近的网格只按实际存在的邻居计算。
+    /// 显著为正表示高值聚集（热点），显著为负表示低值聚集（冷点）；
+    /// 网格少于 2 个、数值全部相同或邻域覆盖全部网格时结果为 NaN。
+    pub fn getis_ord_gi_star(&self, k: usize) -> Self {
+        let n = self.len() as f64;
+        let mean = self.values.values().sum::<f64>() / n;
+        let s = (self.values.values().map(|v| v * v).sum::<f64>() / n - mean * mean).max(0.0).sqrt();
+        let values = self
+            .iter()
+            .map(|(cell, _)| {
+                let (lag, w) = cell
+                    .k_ring(k)
+                    .into_iter()
+                    .filter_map(|c| self.values.get(&c.code))
+                    .fold((0.0, 0.0), |(sum, w), v| (sum + v, w + 1.0));
+                let denom = s * ((n * w - w * w) / (n - 1.0)).sqrt();
+                let z = if denom > 0.0 { (lag - mean * w) / denom } else { f64::NAN };
+                (cell.code, z)
+            })
+            .collect();
+        Self { level: self.level, values }
+    }
+
+    fn coarsen(&self, level: usize, method: Resampling) -> Self {
+        // (总和, 数量, 最近子网格的距离平方, 最近子网格的值)
+        let mut groups: BTreeMap<u64, (f64, usize, f64, f64)> = BTreeMap::new();
+        let mask = level_mask(level);
+        for (cell, &value) in self.iter() {
+            let code = cell.code & mask;
+            let (lng, lat) = cell.center();
+            let (plng, plat) = GeoSotCell::new(code, level).center();
+            let dist = (lng - plng).powi(2) + (lat - plat).powi(2);
+            let group = groups.entry(code).or_insert((0.0, 0, f64::INFINITY, f64::NAN));
+            group.0 += value;
+            group.1 += 1;
+            if dist < group.2 {
+                group.2 = dist;
+                group.3 = value;
+            }
+        }
+        let values = groups
+            .into_iter()
+            .map(|(code, (sum, count, _, nearest))| {
+                let value = match method {
+                    Resampling::Sum => sum,
+                    Resampling::Mean => sum / count as f64,
+                    Resampling::Nearest => nearest,
+                };
+                (code, value)
+            })
+            .collect();
+        Self { level, values }
+    }
+
+    fn refine(&self, level: usize, method: Resampling) -> Result<Self, RefineLimitError> {
+        // 每级最多 4 个子网格，按上界估计，截断网格只会更少
+        let bound = u32::try_from(level - self.level)
+            .ok()
+            .and_then(|jump| 4usize.checked_pow(jump))
+            .and_then(|factor| factor.checked_mul(self.len()));
+        if bound.is_none_or(|bound| bound > MAX_REFINED_CELLS) {
+            return Err(RefineLimitError { from: self.level, to: level, cells: self.len() });
+        }
+        let mut values = BTreeMap::new();
+        for (cell, &value) in self.iter() {
+            let mut descendants = vec![cell];
+            while descendants[0].level < level {
+                descendants = descendants.iter().flat_map(|c| c.children()).collect();
+            }
+            let value = match method {
+                Resampling::Sum => value / descendants.len() as f64,
+                Resampling::Mean | Resampling::Nearest => value,
+            };
+            values.extend(descendants.into_iter().map(|c| (c.code, value)));
+        }
+        Ok(Self { level, values })
+    }
+}
+
+/// Python 中的数值网格栅格
+#[pyclass(name = "GeoSotGrid")]
+#[derive(Clone)]
+pub struct PyGeoSotGrid {
+    inner: GeoSotGrid<f64>,
+}
+
+impl From<GeoSotGrid<f64>> for PyGeoSotGrid {
+    fn from(inner: GeoSotGrid<f64>) -> Self {
+        Self { inner }
+    }
+}
+
+impl PyGeoSotGrid {
+    fn check_level(&self, cell: &GeoSotCell) -> PyResult<()> {
+        if cell.level != self.inner.level {
+            return Err(PyValueError::new_err(format!(
+                "cell level {} does not match grid level {}",
+                cell.level, self.inner.level
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl PyGeoSotGrid {
+    /// 创建指定层级的空栅格，层级限制在 1~32 之内
+    #[new]
+    fn new(level: usize) -> Self {
+        Self { inner: GeoSotGrid::new(level) }
+    }
+
+    /// 区域内每个网格取相同的值
+    #[staticmethod]
+    fn from_region(region: &GeoSotRegion, value: f64) -> Self {
+        Self { inner: GeoSotGrid::from_fn(region, |_| value) }
+    }
+
+    /// 栅格层级
+    #[getter]
+    fn level(&self) -> usize {
+        self.inner.level
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __contains__(&self, cell: &GeoSotCell) -> bool {
+        self.inner.contains(cell)
+    }
+
+    /// 设置网格的值
+    fn set(&mut self, cell: &GeoSotCell, value: f64) -> PyResult<()> {
+        self.check_level(cell)?;
+        self.inner.insert(*cell, value);
+        Ok(())
+    }
+
+    /// 设置经纬度所在网格的值
+    fn set_point(&mut self, lng: f64, lat: f64, value: f64) {
+        self.inner.insert_point(lng, lat, value);
+    }
+
+    /// 网格的值，没有值时返回 None
+    fn get(&self, cell: &GeoSotCell) -> Option<f64> {
+        self.inner.get(cell).copied()
+    }
+
+    /// 经纬度所在网格的值
+    fn value_at(&self, lng: f64, lat: f64) -> Option<f64> {
+        self.inner.value_at(lng, lat).copied()
+    }
+
+    /// 删除网格的值并返回
+    fn remove(&mut self, cell: &GeoSotCell) -> Option<f64> {
+        self.inner.remove(cell)
+    }
+
+    /// [(网格, 值)]，按编码排序
+    fn items(&self) -> Vec<(GeoSotCell, f64)> {
+        self.inner.iter().map(|(cell, &value)| (cell, value)).collect()
+    }
+
+    /// 有值的网格组成的区域
+    fn region(&self) -> GeoSotRegion {
+        self.inner.region()
+    }
+
+    /// 对每个值调用 `func(value)`，返回新栅格
+    fn map(&self, func: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let mut values = BTreeMap::new();
+        for (cell, &value) in self.inner.iter() {
+            values.insert(cell.code, func.call1((value,))?.extract::<f64>()?);
+        }
+        Ok(Self { inner: GeoSotGrid { level: self.inner.level, values } })
+    }
+
+    /// 对两个栅格共有的网格调用 `func(a, b)`，返回新栅格
+    fn zip(&self, other: &PyGeoSotGrid, func: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if other.inner.level != self.inner.level {
+            return Err(PyValueError::new_err("Cannot zip grids with different levels"));
+        }
+        let mut values = BTreeMap::new();
+        for (cell, &a) in self.inner.iter() {
+            if let Some(&b) = other.inner.get(&cell) {
+                values.insert(cell.code, func.call1((a, b))?.extract::<f64>()?);
+            }
+        }
+        Ok(Self { inner: GeoSotGrid { level: self.inner.level, values } })
+    }
+
+    /// 只保留落在区域内的网格
+    fn mask(&self, region: &GeoSotRegion) -> Self {
+        Self { inner: self.inner.mask(region) }
+    }
+
+    /// 重采样到其他层级
+    #[pyo3(signature = (level, method = Resampling::Mean))]
+    fn resample(&self, level: usize, method: Resampling) -> PyResult<Self> {
+        Ok(Self { inner: self.inner.resample(level, method)? })
+    }
+
+    /// k 圈邻域内的焦点统计
+    #[pyo3(signature = (k, stat = Focal::Mean))]
+    fn focal(&self, k: usize, stat: Focal) -> Self {
+        Self { inner: self.inner.focal(k, stat) }
+    }
+
+    /// Getis-Ord Gi* 热点统计量（z 得分）
+    #[pyo3(signature = (k = 1))]
+    fn getis_ord_gi_star(&self, k: usize) -> Self {
+        Self { inner: self.inner.getis_ord_gi_star(k) }
+    }
+
+    /// 导出稠密栅格，返回 (按行排列的二维列表, GDAL 仿射变换参数)
+    #[pyo3(name = "to_dense", signature = (min_lng, min_lat, max_lng, max_lat, nodata = f64::NAN))]
+    fn dense_py(
+        &self,
+        min_lng: f64,
+        min_lat: f64,
+        max_lng: f64,
+        max_lat: f64,
+        nodata: f64,
+    ) -> PyResult<(Vec<Vec<f64>>, [f64; 6])> {
+        let raster = self.inner.to_dense(min_lng, min_lat, max_lng, max_lat, nodata)?;
+        Ok((raster.rows().map(|row| row.to_vec()).collect(), raster.geotransform))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_grid(level: usize) -> GeoSotGrid<f64> {
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.5, 39.5, level);
+        GeoSotGrid::from_fn(&region, |cell| {
+            let (lng, lat) = cell.center();
+            lng + lat
+        })
+    }
+
+    #[test]
+    fn test_insert_map_zip() {
+        let mut grid = GeoSotGrid::new(14);
+        let cell = GeoSotCell::from_coords(116.39, 39.91, 14);
+        assert_eq!(grid.insert(cell, 1.5), None);
+        assert_eq!(grid.insert_point(116.39, 39.91, 2.5), Some(1.5));
+        grid.insert_point(121.47, 31.23, 4.0);
+        assert_eq!(grid.len(), 2);
+        assert_eq!(GeoSotGrid::<f64>::new(0).level(), 1);
+        assert_eq!(GeoSotGrid::<f64>::new(40).level(), 32);
+        assert_eq!(GeoSotGrid::<f64>::default().level(), 1);
+        assert_eq!(grid.get(&cell), Some(&2.5));
+        assert_eq!(grid.get(&GeoSotCell::from_coords(116.39, 39.91, 13)), None);
+        assert_eq!(grid.value_at(121.47, 31.23), Some(&4.0));
+
+        let doubled = grid.map(|v| v * 2.0);
+        assert_eq!(doubled.get(&cell), Some(&5.0));
+        let mut other = GeoSotGrid::new(14);
+        other.insert(cell, 10.0);
+        let sum = grid.zip(&other, |a, b| a + b);
+        assert_eq!(sum.len(), 1);
+        assert_eq!(sum.get(&cell), Some(&12.5));
+        assert_eq!(grid.remove(&cell), Some(2.5));
+        assert_eq!(grid.region().size(), 1);
+    }
+
+    #[test]
+    fn test_mask() {
+        let grid = sample_grid(14);
+        let coarse = GeoSotRegion::from_codes(vec![get_code(116.1, 39.1, 12)], 12);
+        let masked = grid.mask(&coarse);
+        assert!(!masked.is_empty() && masked.len() < grid.len());
+        assert!(masked.iter().all(|(cell, _)| coarse.contains(cell.code & level_mask(12))));
+
+        let fine = GeoSotRegion::from_codes(vec![get_code(116.1, 39.1, 20), get_code(116.3, 39.3, 20)], 20);
+        let masked = grid.mask(&fine);
+        assert_eq!(masked.len(), 2);
+        assert!(masked.get(&GeoSotCell::from_coords(116.3, 39.3, 14)).is_some());
+    }
+
+    #[test]
+    fn test_resample() {
+        let grid = sample_grid(15);
+        let total: f64 = grid.iter().map(|(_, v)| v).sum();
+
+        let sum = grid.resample(12, Resampling::Sum).unwrap();
+        assert!((sum.iter().map(|(_, v)| v).sum::<f64>() - total).abs() < 1e-6);
+        let mean = grid.resample(12, Resampling::Mean).unwrap();
+        let nearest = grid.resample(12, Resampling::Nearest).unwrap();
+        assert_eq!(mean.len(), sum.len());
+        for (cell, &m) in mean.iter() {
+            let children: Vec<f64> = grid.iter().filter(|(c, _)| cell.is_ancestor_of(c)).map(|(_, &v)| v).collect();
+            assert!((m - children.iter().sum::<f64>() / children.len() as f64).abs() < 1e-9);
+            let n = *nearest.get(&cell).unwrap();
+            assert!(children.contains(&n));
+        }
+
+        let cell = GeoSotCell::from_coords(116.2, 39.2, 15);
+        let value = *grid.get(&cell).unwrap();
+        let refined = grid.resample(17, Resampling::Sum).unwrap();
+        let parts: Vec<f64> = refined.iter().filter(|(c, _)| cell.is_ancestor_of(c)).map(|(_, &v)| v).collect();
+        assert_eq!(parts.len(), 16);
+        assert!((parts.iter().sum::<f64>() - value).abs() < 1e-9);
+        assert_eq!(grid.resample(17, Resampling::Nearest).unwrap().value_at(116.2, 39.2), Some(&value));
+        assert_eq!(grid.resample(15, Resampling::Mean).unwrap(), grid);
+
+        // 10 级细分到 32 级每个网格会展开为 4^22 个后代
+        let mut coarse = GeoSotGrid::new(10);
+        coarse.insert_point(116.2, 39.2, 1.0);
+        assert_eq!(
+            coarse.resample(32, Resampling::Mean),
+            Err(RefineLimitError { from: 10, to: 32, cells: 1 })
+        );
+        assert_eq!(coarse.resample(14, Resampling::Mean).unwrap().len(), 256);
+    }
+
+    #[test]
+    fn test_focal() {
+        let mut grid = GeoSotGrid::new(15);
+        let center = GeoSotCell::from_coords(116.397, 39.916, 15);
+        for (i, cell) in center.k_ring(1).into_iter().enumerate() {
+            grid.insert(cell, i as f64);
+        }
+        let east = center.neighbor(1, 0).unwrap();
+        let far = east.neighbor(1, 0).unwrap();
+        grid.insert(far, 100.0);
+
+        assert_eq!(grid.focal(1, Focal::Sum).get(&center), Some(&36.0));
+        assert_eq!(grid.focal(1, Focal::Mean).get(&center), Some(&4.0));
+        assert_eq!(grid.focal(1, Focal::Max).get(&center), Some(&8.0));
+        assert_eq!(grid.focal(1, Focal::Min).get(&center), Some(&0.0));
+        // 邻域中没有值的网格不参与计算
+        let east_value = *grid.get(&east).unwrap();
+        let east_window: Vec<f64> = east.k_ring(1).iter().filter_map(|c| grid.get(c).copied()).collect();
+        assert_eq!(east_window.len(), 7);
+        assert_eq!(grid.focal(1, Focal::Max).get(&east), Some(&100.0));
+        assert_eq!(grid.focal(0, Focal::Mean).get(&east), Some(&east_value));
+        assert_eq!(grid.focal(1, Focal::Sum).len(), grid.len());
+    }
+
+    #[test]
+    fn test_getis_ord_gi_star() {
+        // 20x20 的低值背景中放一块 3x3 的高值
+        let origin = GeoSotCell::from_coords(116.0, 39.0, 15);
+        let mut grid = GeoSotGrid::new(15);
+        let mut row = origin;
+        for y in 0..20 {
+            let mut cell = row;
+            for x in 0..20 {
+                let hot = (8..11).contains(&x) && (8..11).contains(&y);
+                grid.insert(cell, if hot { 10.0 } else { ((x * 7 + y * 3) % 5) as f64 * 0.1 });
+                cell = cell.neighbor(1, 0).unwrap();
+            }
+            row = row.neighbor(0, 1).unwrap();
+        }
+        let gi = grid.getis_ord_gi_star(1);
+        let (hot, _) = gi.iter().max_by(|a, b| a.1.total_cmp(b.1)).unwrap();
+        let hot_center = (0..9).fold(origin, |c, _| c.neighbor(1, 1).unwrap());
+        assert_eq!(hot, hot_center);
+        assert!(*gi.get(&hot_center).unwrap() > 10.0);
+
+        // 与公式直接计算对比（角点网格只有 4 个邻域网格）
+        let values: Vec<f64> = grid.iter().map(|(_, &v)| v).collect();
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let s = (values.iter().map(|v| v * v).sum::<f64>() / n - mean * mean).sqrt();
+        let lag: f64 = origin.k_ring(1).iter().filter_map(|c| grid.get(c)).sum();
+        let expected = (lag - mean * 4.0) / (s * ((n * 4.0 - 16.0) / (n - 1.0)).sqrt());
+        assert!((gi.get(&origin).unwrap() - expected).abs() < 1e-9);
+        assert!(*gi.get(&origin).unwrap() < 0.0);
+
+        let uniform = GeoSotGrid::from_fn(&GeoSotRegion::from_rectangle(116.0, 39.0, 116.1, 39.1, 15), |_| 1.0);
+        assert!(uniform.getis_ord_gi_star(1).iter().all(|(_, v)| v.is_nan()));
+    }
+
+    #[test]
+    fn test_to_dense() {
+        let mut grid = GeoSotGrid::new(9);
+        grid.insert_point(116.5, 39.5, 1.0);
+        grid.insert_point(118.5, 39.5, 2.0);
+        let raster = grid.to_dense(116.0, 38.0, 119.0, 40.0, -1.0).unwrap();
+        assert_eq!(raster.geotransform, [116.0, 1.0, 0.0, 40.0, 0.0, -1.0]);
+        assert_eq!((raster.width, raster.height), (3, 2));
+        assert_eq!(raster.rows().next().unwrap(), &[1.0, -1.0, 2.0]);
+        assert_eq!(raster.get(0, 1), Some(&-1.0));
+        assert_eq!(raster.get(3, 0), None);
+
+        // 跨越 180° 经线
+        let mut grid = GeoSotGrid::new(9);
+        grid.insert_point(179.5, 0.5, 1.0);
+        grid.insert_point(-179.5, 0.5, 2.0);
+        let raster = grid.to_dense(179.0, 0.0, -179.0, 1.0, 0.0).unwrap();
+        assert_eq!((raster.width, raster.height), (2, 1));
+        assert_eq!(raster.data, vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_to_dense_minute_levels() {
+        // 2 分网格整除 60 分，每个像元恰好对应一个网格
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.99, 39.99, 14);
+        let grid = GeoSotGrid::from_fn(&region, |cell| cell.code as f64);
+        let raster = grid.to_dense(116.0, 39.0, 116.99, 39.99, f64::NAN).unwrap();
+        assert_eq!((raster.width, raster.height), (30, 30));
+        assert_eq!(raster.geotransform[3], 40.0);
+        let sampled: BTreeSet<u64> = raster.data.iter().map(|&v| v as u64).collect();
+        assert_eq!(sampled, region.codes.iter().collect());
+
+        // 8 分网格每度末尾是 56~60 分的截断网格，像元无法与网格对齐
+        let coarse = grid.resample(12, Resampling::Mean).unwrap();
+        assert_eq!(coarse.to_dense(116.0, 39.0, 116.99, 39.99, f64::NAN), Err(UnevenLevelError(12)));
+        assert_eq!(GeoSotGrid::<f64>::new(17).to_dense(0.0, 0.0, 1.0, 1.0, 0.0), Err(UnevenLevelError(17)));
+    }
+}
@@ -0,0 +1,658 @@
+//! 以 GeoSOT 编码为键的内存点索引
+//!
+//! 每个条目按所在网格的编码保存在有序集合中。同一祖先网格下的所有编码是连续区间
+//! （对齐编码的前缀相同），查询时先用若干较粗的网格覆盖查询范围、扫描对应的编码区间，
+//! 再按精确几何过滤候选条目。
+
+use pyo3::exceptions::PyKeyError;
+use pyo3::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::ops::RangeInclusive;
+
+use crate::spatial::{level_mask, GeoSotCell, GeoSotRegion};
+use crate::utils::{axis_cells, get_cell_size_in_degree, haversine_distance, EARTH_RADIUS_M, TICKS_PER_DEGREE};
+use crate::{get_code, magic_bits, un_magic_bits};
+
+/// 索引条目编号，插入时分配，删除后不会复用
+pub type ItemId = u64;
+
+/// 查询范围跨越的网格数超过该值时改用更粗的扫描层级
+const MAX_SCAN_CELLS_PER_AXIS: f64 = 4.0;
+
+/// 最近邻搜索在同一层级扩展的最大圈数，候选仍不足时改用更粗的层级
+const MAX_RINGS_PER_LEVEL: usize = 4;
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    lng: f64,
+    lat: f64,
+    code: u64,
+    item: T,
+}
+
+/// 以 GeoSOT 编码为键的点索引
+#[derive(Debug, Clone)]
+pub struct GeoSotIndex<T> {
+    level: usize,
+    keys: BTreeSet<(u64, ItemId)>,
+    entries: HashMap<ItemId, Entry<T>>,
+    next_id: ItemId,
+}
+
+impl<T> GeoSotIndex<T> {
+    /// 创建索引，条目按指定层级的网格编码组织
+    ///
+    /// 层级越细，范围查询的候选过滤越精确，但粗范围需要扫描的编码区间也越稀疏。
+    pub fn new(level: usize) -> Self {
+        Self {
+            level: level.clamp(1, 32),
+            keys: BTreeSet::new(),
+            entries: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// 索引层级
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// 条目数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 插入条目，返回其编号
+    pub fn insert(&mut self, lng: f64, lat: f64, item: T) -> ItemId {
+        let id = self.next_id;
+        self.next_id += 1;
+        let code = get_code(lng, lat, self.level);
+        self.keys.insert((code, id));
+        self.entries.insert(id, Entry { lng, lat, code, item });
+        id
+    }
+
+    /// 删除条目，返回被删除的对象
+    pub fn remove(&mut self, id: ItemId) -> Option<T> {
+        let entry = self.entries.remove(&id)?;
+        self.keys.remove(&(entry.code, id));
+        Some(entry.item)
+    }
+
+    /// 移动条目到新位置，条目不存在时返回 false
+    pub fn update(&mut self, id: ItemId, lng: f64, lat: f64) -> bool {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return false;
+        };
+        let code = get_code(lng, lat, self.level);
+        if code != entry.code {
+            self.keys.remove(&(entry.code, id));
+            self.keys.insert((code, id));
+        }
+        entry.lng = lng;
+        entry.lat = lat;
+        entry.code = code;
+        true
+    }
+
+    /// 获取条目
+    pub fn get(&self, id: ItemId) -> Option<&T> {
+        self.entries.get(&id).map(|entry| &entry.item)
+    }
+
+    /// 获取条目的可变引用
+    pub fn get_mut(&mut self, id: ItemId) -> Option<&mut T> {
+        self.entries.get_mut(&id).map(|entry| &mut entry.item)
+    }
+
+    /// 条目位置 (lng, lat)
+    pub fn position(&self, id: ItemId) -> Option<(f64, f64)> {
+        self.entries.get(&id).map(|entry| (entry.lng, entry.lat))
+    }
+
+    /// 是否包含条目
+    pub fn contains(&self, id: ItemId) -> bool {
+        self.entries.contains_key(&id)
+    }
+
+    /// 按编码顺序遍历全部条目 (编号, 经度, 纬度, 对象)
+    pub fn iter(&self) -> impl Iterator<Item = (ItemId, f64, f64, &T)> {
+        self.keys.iter().map(|&(_, id)| {
+            let entry = &self.entries[&id];
+            (id, entry.lng, entry.lat, &entry.item)
+        })
+    }
+
+    /// 位置与给定坐标完全相同的条目
+    pub fn query_point(&self, lng: f64, lat: f64) -> Vec<(ItemId, &T)> {
+        let code = get_code(lng, lat, self.level);
+        self.scan(std::iter::once(code..=code), |entry| entry.lng == lng && entry.lat == lat)
+    }
+
+    /// 位于经纬度范围内（含边界）的条目
+    ///
+    /// `min_lng > max_lng` 时视为跨越 180° 经线的范围。
+    pub fn query_bbox(&self, min_lng: f64, min_lat: f64, max_lng: f64, max_lat: f64) -> Vec<(ItemId, &T)> {
+        let boxes = split_antimeridian(min_lng, min_lat, max_lng, max_lat);
+        let ranges = boxes.iter().flat_map(|&(a, b, c, d)| self.rect_ranges(a, b, c, d)).collect::<Vec<_>>();
+        self.scan(ranges.into_iter(), |entry| {
+            boxes.iter().any(|&(a, b, c, d)| (a..=c).contains(&entry.lng) && (b..=d).contains(&entry.lat))
+        })
+    }
+
+    /// 与中心点的球面距离不超过 `radius_m` 米的条目
+    pub fn query_radius(&self, lng: f64, lat: f64, radius_m: f64) -> Vec<(ItemId, &T)> {
+        if radius_m < 0.0 {
+            return Vec::new();
+        }
+        let (min_lng, min_lat, max_lng, max_lat) = radius_bbox(lng, lat, radius_m);
+        let boxes = split_antimeridian(min_lng, min_lat, max_lng, max_lat);
+        let ranges = boxes.iter().flat_map(|&(a, b, c, d)| self.rect_ranges(a, b, c, d)).collect::<Vec<_>>();
+        self.scan(ranges.into_iter(), |entry| haversine_distance(lng, lat, entry.lng, entry.lat) <= radius_m)
+    }
+
+    /// 位于区域内的条目（条目在区域层级下所在的网格属于区域）
+    pub fn query_region(&self, region: &GeoSotRegion) -> Vec<(ItemId, &T)> {
+        let level = region.level.min(self.level);
+        let mut ranges: Vec<RangeInclusive<u64>> = Vec::new();
+        for code in region.codes.iter() {
+            let range = prefix_range(code & level_mask(level), level);
+            // 区域编码有序，相邻或重叠的区间合并后再扫描
+            match ranges.last_mut() {
+                Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                    *last = *last.start()..=(*last.end()).max(*range.end());
+                }
+                _ => ranges.push(range),
+            }
+        }
+        self.scan(ranges.into_iter(), |entry| region.contains_point(entry.lng, entry.lat))
+    }
+
+    /// 距离查询点最近的 k 个条目 [(编号, 球面距离（米）, 对象)]，按距离升序排列
+    ///
+    /// 从查询点所在网格开始逐圈向外扫描相邻网格；已扫描范围边界到查询点的最短距离
+    /// 超过当前第 k 近的距离时提前结束。数据稀疏、扩展多圈仍找不到足够候选时改用更粗的层级。
+    pub fn nearest(&self, lng: f64, lat: f64, k: usize) -> Vec<(ItemId, f64, &T)> {
+        if k == 0 || self.is_empty() {
+            return Vec::new();
+        }
+        // get_code 舍入到最近的刻度，边界附近的条目可能落在相邻网格，留出两个刻度的余量
+        let margin = 2.0 * (1.0 / TICKS_PER_DEGREE as f64).to_radians() * EARTH_RADIUS_M;
+
+        let mut best: Vec<(f64, ItemId)> = Vec::with_capacity(k + 1);
+        let mut seen = HashSet::new();
+        let mut level = self.level;
+        let mut rings = Rings::new(lng, lat, level);
+        let mut cells = rings.cells();
+        let mut radius = 0;
+        loop {
+            for cell in &cells {
+                let range = prefix_range(cell.code, cell.level);
+                for &(_, id) in self.keys.range((*range.start(), 0)..=(*range.end(), ItemId::MAX)) {
+                    if !seen.insert(id) {
+                        continue;
+                    }
+                    let entry = &self.entries[&id];
+                    let distance = haversine_distance(lng, lat, entry.lng, entry.lat);
+                    if best.len() < k || distance < best[k - 1].0 {
+                        let at = best.partition_point(|&(d, _)| d <= distance);
+                        best.insert(at, (distance, id));
+                        best.truncate(k);
+                    }
+                }
+            }
+
+            if rings.covers_globe() || (best.len() == k && best[k - 1].0 <= rings.outside_distance(lng, lat) - margin) {
+                break;
+            }
+            if best.len() < k && radius >= MAX_RINGS_PER_LEVEL && level > 1 {
+                level -= 1;
+                rings = Rings::new(lng, lat, level);
+                cells = rings.cells();
+                radius = 0;
+            } else {
+                cells = rings.expand();
+                radius += 1;
+            }
+        }
+        best.into_iter().map(|(distance, id)| (id, distance, &self.entries[&id].item)).collect()
+    }
+
+    /// 扫描编码区间内的条目并按精确几何过滤
+    fn scan(&self, ranges: impl Iterator<Item = RangeInclusive<u64>>, filter: impl Fn(&Entry<T>) -> bool) -> Vec<(ItemId, &T)> {
+        let mut result = Vec::new();
+        let mut seen = BTreeSet::new();
+        for range in ranges {
+            for &(_, id) in self.keys.range((*range.start(), 0)..=(*range.end(), ItemId::MAX)) {
+                let entry = &self.entries[&id];
+                if filter(entry) && seen.insert(id) {
+                    result.push((id, &entry.item));
+                }
+            }
+        }
+        result
+    }
+
+    /// 覆盖经纬度范围的扫描区间
+    fn rect_ranges(&self, min_lng: f64, min_lat: f64, max_lng: f64, max_lat: f64) -> Vec<RangeInclusive<u64>> {
+        let extent = (max_lng - min_lng).max(max_lat - min_lat);
+        // 选择每个方向只需少量网格即可覆盖范围的最细层级
+        let level = (1..=self.level)
+            .rev()
+            .find(|&level| get_cell_size_in_degree(level as i32).unwrap() * MAX_SCAN_CELLS_PER_AXIS >= extent)
+            .unwrap_or(1);
+        rect_cells(min_lng, min_lat, max_lng, max_lat, level)
+            .into_iter()
+            .map(|cell| prefix_range(cell.code, cell.level))
+            .collect()
+    }
+}
+
+/// 最近邻搜索已扫描的矩形范围，由若干列（经度编码）与行（纬度编码）组成
+struct Rings {
+    level: usize,
+    /// 自西向东的列
+    columns: VecDeque<u32>,
+    /// 自南向北的行
+    rows: VecDeque<u32>,
+    /// 查询点所在的行，经度方向在这一行上步进（靠近极点的行可能越过 ±90°）
+    origin_row: u32,
+    /// 列已绕经度方向一周
+    wrapped: bool,
+    north_closed: bool,
+    south_closed: bool,
+}
+
+impl Rings {
+    fn new(lng: f64, lat: f64, level: usize) -> Self {
+        let (x, y) = un_magic_bits(get_code(lng, lat, level));
+        Self {
+            level,
+            columns: VecDeque::from([x]),
+            rows: VecDeque::from([y]),
+            origin_row: y,
+            wrapped: false,
+            north_closed: false,
+            south_closed: false,
+        }
+    }
+
+    fn cell(&self, x: u32, y: u32) -> GeoSotCell {
+        GeoSotCell::new(magic_bits(x, y), self.level)
+    }
+
+    /// 范围内的全部网格
+    fn cells(&self) -> Vec<GeoSotCell> {
+        self.rows.iter().flat_map(|&y| self.columns.iter().map(move |&x| self.cell(x, y))).collect()
+    }
+
+    /// 向外扩展一圈，返回新增的网格
+    fn expand(&mut self) -> Vec<GeoSotCell> {
+        let (x0, y0) = (self.columns[0], self.origin_row);
+        let old_rows: Vec<u32> = self.rows.iter().copied().collect();
+
+        let mut new_columns = Vec::new();
+        for dx in [-1, 1] {
+            if self.wrapped {
+                break;
+            }
+            let edge = if dx < 0 { self.columns[0] } else { self.columns[self.columns.len() - 1] };
+            let next = self.cell(edge, y0).neighbor(dx, 0).map(|cell| un_magic_bits(cell.code).0);
+            match next {
+                Some(x) if !self.columns.contains(&x) => {
+                    if dx < 0 {
+                        self.columns.push_front(x);
+                    } else {
+                        self.columns.push_back(x);
+                    }
+                    new_columns.push(x);
+                }
+                _ => self.wrapped = true,
+            }
+        }
+
+        let mut new_rows = Vec::new();
+        for dy in [-1, 1] {
+            let closed = if dy < 0 { self.south_closed } else { self.north_closed };
+            if closed {
+                continue;
+            }
+            let edge = if dy < 0 { self.rows[0] } else { self.rows[self.rows.len() - 1] };
+            match self.cell(x0, edge).neighbor(0, dy) {
+                Some(cell) => {
+                    let y = un_magic_bits(cell.code).1;
+                    if dy < 0 {
+                        self.rows.push_front(y);
+                    } else {
+                        self.rows.push_back(y);
+                    }
+                    new_rows.push(y);
+                }
+                None if dy < 0 => self.south_closed = true,
+                None => self.north_closed = true,
+            }
+        }
+
+        let mut cells: Vec<GeoSotCell> =
+            new_rows.iter().flat_map(|&y| self.columns.iter().map(move |&x| (x, y))).map(|(x, y)| self.cell(x, y)).collect();
+        cells.extend(old_rows.iter().flat_map(|&y| new_columns.iter().map(move |&x| (x, y))).map(|(x, y)| self.cell(x, y)));
+        cells
+    }
+
+    fn covers_globe(&self) -> bool {
+        self.wrapped && self.north_closed && self.south_closed
+    }
+
+    /// 查询点到范围之外任意位置的最短球面距离（米）
+    ///
+    /// 到纬线边界的最短距离沿经线方向；到经线边界的最短距离为到该经线所在大圆的距离。
+    fn outside_distance(&self, lng: f64, lat: f64) -> f64 {
+        let (west, south, _, _) = self.cell(self.columns[0], self.rows[0]).bounds();
+        let (_, _, east, north) = self.cell(self.columns[self.columns.len() - 1], self.rows[self.rows.len() - 1]).bounds();
+        let mut bound = f64::INFINITY;
+        if !self.south_closed {
+            bound = bound.min((lat - south).to_radians() * EARTH_RADIUS_M);
+        }
+        if !self.north_closed {
+            bound = bound.min((north - lat).to_radians() * EARTH_RADIUS_M);
+        }
+        if !self.wrapped {
+            for d_lng in [(lng - west).rem_euclid(360.0), (east - lng).rem_euclid(360.0)] {
+                let d = (lat.to_radians().cos() * d_lng.min(90.0).to_radians().sin()).asin();
+                bound = bound.min(d * EARTH_RADIUS_M);
+            }
+        }
+        bound.max(0.0)
+    }
+}
+
+/// 以网格为前缀的全部细层级编码区间
+fn prefix_range(code: u64, level: usize) -> RangeInclusive<u64> {
+    code..=code | !level_mask(level)
+}
+
+/// 与闭合经纬度范围相交（含边界接触）的指定层级网格
+///
+/// `get_code` 会把坐标舍入到最近的刻度，范围两端各多取一个刻度，保证边界上的点所在网格也被包含。
+pub(crate) fn rect_cells(min_lng: f64, min_lat: f64, max_lng: f64, max_lat: f64, level: usize) -> Vec<GeoSotCell> {
+    let ticks = |deg: f64| (deg * TICKS_PER_DEGREE as f64).floor() as i64;
+    let xs = axis_cells(ticks(min_lng.clamp(-180.0, 180.0)) - 1, ticks(max_lng.clamp(-180.0, 180.0)) + 2, level);
+    let ys = axis_cells(ticks(min_lat.clamp(-90.0, 90.0)) - 1, ticks(max_lat.clamp(-90.0, 90.0)) + 2, level);
+    ys.iter()
+        .flat_map(|&(y, _, _)| xs.iter().map(move |&(x, _, _)| GeoSotCell::new(magic_bits(x, y), level)))
+        .collect()
+}
+
+/// 将跨越 180° 经线的范围（`min_lng > max_lng`）拆成两段
+fn split_antimeridian(min_lng: f64, min_lat: f64, max_lng: f64, max_lat: f64) -> Vec<(f64, f64, f64, f64)> {
+    if min_lat > max_lat {
+        Vec::new()
+    } else if min_lng > max_lng {
+        vec![(min_lng, min_lat, 180.0, max_lat), (-180.0, min_lat, max_lng, max_lat)]
+    } else {
+        vec![(min_lng, min_lat, max_lng, max_lat)]
+    }
+}
+
+/// 包含球面圆的经纬度范围，跨越 180° 经线时 `min_lng > max_lng`
+pub(crate) fn radius_bbox(lng: f64, lat: f64, radius_m: f64) -> (f64, f64, f64, f64) {
+    let d_lat = (radius_m / EARTH_RADIUS_M).to_degrees();
+    let (min_lat, max_lat) = (lat - d_lat, lat + d_lat);
+    // 圆覆盖极点时经度方向不受限制
+    if min_lat <= -90.0 || max_lat >= 90.0 {
+        return (-180.0, min_lat.max(-90.0), 180.0, max_lat.min(90.0));
+    }
+    let ratio = (radius_m / EARTH_RADIUS_M).sin() / lat.to_radians().cos();
+    if ratio >= 1.0 {
+        return (-180.0, min_lat, 180.0, max_lat);
+    }
+    let d_lng = ratio.asin().to_degrees();
+    let wrap = |v: f64| if v < -180.0 { v + 360.0 } else if v > 180.0 { v - 360.0 } else { v };
+    (wrap(lng - d_lng), min_lat, wrap(lng + d_lng), max_lat)
+}
+
+/// Python 中的点索引，可保存任意对象
+#[pyclass(name = "GeoSotIndex")]
+pub struct PyGeoSotIndex {
+    inner: GeoSotIndex<PyObject>,
+}
+
+impl PyGeoSotIndex {
+    fn results(py: Python<'_>, items: Vec<(ItemId, &PyObject)>) -> Vec<(ItemId, PyObject)> {
+        items.into_iter().map(|(id, item)| (id, item.clone_ref(py))).collect()
+    }
+}
+
+#[pymethods]
+impl PyGeoSotIndex {
+    /// 创建索引，条目按指定层级的网格编码组织
+    #[new]
+    fn new(level: usize) -> Self {
+        Self { inner: GeoSotIndex::new(level) }
+    }
+
+    /// 索引层级
+    #[getter]
+    fn level(&self) -> usize {
+        self.inner.level()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __contains__(&self, id: ItemId) -> bool {
+        self.inner.contains(id)
+    }
+
+    /// 插入对象，返回条目编号
+    fn insert(&mut self, lng: f64, lat: f64, item: PyObject) -> ItemId {
+        self.inner.insert(lng, lat, item)
+    }
+
+    /// 删除条目并返回对象，条目不存在时抛出 KeyError
+    fn remove(&mut self, id: ItemId) -> PyResult<PyObject> {
+        self.inner.remove(id).ok_or_else(|| PyKeyError::new_err(id))
+    }
+
+    /// 移动条目到新位置，条目不存在时抛出 KeyError
+    fn update(&mut self, id: ItemId, lng: f64, lat: f64) -> PyResult<()> {
+        if self.inner.update(id, lng, lat) {
+            Ok(())
+        } else {
+            Err(PyKeyError::new_err(id))
+        }
+    }
+
+    /// 获取对象，条目不存在时返回 None
+    fn get(&self, py: Python<'_>, id: ItemId) -> Option<PyObject> {
+        self.inner.get(id).map(|item| item.clone_ref(py))
+    }
+
+    /// 条目位置 (lng, lat)
+    fn position(&self, id: ItemId) -> Option<(f64, f64)> {
+        self.inner.position(id)
+    }
+
+    /// 位置与给定坐标完全相同的条目 [(编号, 对象)]
+    fn query_point(&self, py: Python<'_>, lng: f64, lat: f64) -> Vec<(ItemId, PyObject)> {
+        Self::results(py, self.inner.query_point(lng, lat))
+    }
+
+    /// 位于经纬度范围内的条目，min_lng > max_lng 时视为跨越 180° 经线
+    fn query_bbox(&self, py: Python<'_>, min_lng: f64, min_lat: f64, max_lng: f64, max_lat: f64) -> Vec<(ItemId, PyObject)> {
+        Self::results(py, self.inner.query_bbox(min_lng, min_lat, max_lng, max_lat))
+    }
+
+    /// 与中心点的球面距离不超过 radius_m 米的条目
+    fn query_radius(&self, py: Python<'_>, lng: f64, lat: f64, radius_m: f64) -> Vec<(ItemId, PyObject)> {
+        Self::results(py, self.inner.query_radius(lng, lat, radius_m))
+    }
+
+    /// 位于区域内的条目
+    fn query_region(&self, py: Python<'_>, region: &GeoSotRegion) -> Vec<(ItemId, PyObject)> {
+        Self::results(py, self.inner.query_region(region))
+    }
+
+    /// 距离查询点最近的 k 个条目 [(编号, 球面距离（米）, 对象)]，按距离升序排列
+    fn nearest(&self, py: Python<'_>, lng: f64, lat: f64, k: usize) -> Vec<(ItemId, f64, PyObject)> {
+        self.inner
+            .nearest(lng, lat, k)
+            .into_iter()
+            .map(|(id, distance, item)| (id, distance, item.clone_ref(py)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 确定性的伪随机点
+    fn points(n: usize) -> Vec<(f64, f64)> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..n).map(|_| (next() * 360.0 - 180.0, next() * 180.0 - 90.0)).collect()
+    }
+
+    fn ids<T>(mut result: Vec<(ItemId, &T)>) -> Vec<ItemId> {
+        result.sort_by_key(|&(id, _)| id);
+        result.into_iter().map(|(id, _)| id).collect()
+    }
+
+    fn build(level: usize, points: &[(f64, f64)]) -> GeoSotIndex<usize> {
+        let mut index = GeoSotIndex::new(level);
+        for (i, &(lng, lat)) in points.iter().enumerate() {
+            assert_eq!(index.insert(lng, lat, i), i as ItemId);
+        }
+        index
+    }
+
+    #[test]
+    fn test_bbox_matches_brute_force() {
+        let points = points(5000);
+        let index = build(20, &points);
+        for &(a, b, c, d) in &[(100.0, 20.0, 130.0, 50.0), (-1.0, -1.0, 1.0, 1.0), (-180.0, -90.0, 180.0, 90.0), (10.0, 10.0, 10.5, 10.5)] {
+            let expected: Vec<ItemId> = (0..points.len() as ItemId)
+                .filter(|&i| {
+                    let (lng, lat) = points[i as usize];
+                    (a..=c).contains(&lng) && (b..=d).contains(&lat)
+                })
+                .collect();
+            assert_eq!(ids(index.query_bbox(a, b, c, d)), expected);
+        }
+
+        // 跨越 180° 经线
+        let expected: Vec<ItemId> = (0..points.len() as ItemId)
+            .filter(|&i| {
+                let (lng, lat) = points[i as usize];
+                (lng >= 170.0 || lng <= -170.0) && (-10.0..=10.0).contains(&lat)
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(ids(index.query_bbox(170.0, -10.0, -170.0, 10.0)), expected);
+    }
+
+    #[test]
+    fn test_radius_matches_brute_force() {
+        let points = points(5000);
+        let index = build(18, &points);
+        for &(lng, lat, r) in &[(116.4, 39.9, 1_000_000.0), (179.9, 0.0, 500_000.0), (0.0, 89.0, 800_000.0), (0.0, 0.0, 0.0)] {
+            let expected: Vec<ItemId> = (0..points.len() as ItemId)
+                .filter(|&i| {
+                    let (x, y) = points[i as usize];
+                    haversine_distance(lng, lat, x, y) <= r
+                })
+                .collect();
+            assert_eq!(ids(index.query_radius(lng, lat, r)), expected);
+        }
+    }
+
+    #[test]
+    fn test_point_and_region_queries() {
+        let mut index = GeoSotIndex::new(20);
+        let a = index.insert(116.3912, 39.9075, "a");
+        let b = index.insert(116.3912, 39.9075, "b");
+        let c = index.insert(116.3913, 39.9075, "c");
+        assert_eq!(ids(index.query_point(116.3912, 39.9075)), vec![a, b]);
+
+        // 区域层级粗于、细于索引层级时结果一致
+        let coarse = GeoSotRegion::from_codes(vec![get_code(116.3912, 39.9075, 12)], 12);
+        assert_eq!(ids(index.query_region(&coarse)), vec![a, b, c]);
+        let fine = GeoSotRegion::from_codes(vec![get_code(116.3913, 39.9075, 26)], 26);
+        assert_eq!(ids(index.query_region(&fine)), vec![c]);
+        assert!(index.query_region(&GeoSotRegion::new(20)).is_empty());
+    }
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        let points = points(3000);
+        let index = build(16, &points);
+        let queries = [(116.4, 39.9, 10), (179.95, 0.0, 5), (-179.95, -45.0, 7), (0.0, 89.9, 3), (12.3, -89.99, 4), (0.0, 0.0, 1)];
+        for &(lng, lat, k) in &queries {
+            let mut expected: Vec<(f64, ItemId)> = points
+                .iter()
+                .enumerate()
+                .map(|(i, &(x, y))| (haversine_distance(lng, lat, x, y), i as ItemId))
+                .collect();
+            expected.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let result = index.nearest(lng, lat, k);
+            assert_eq!(result.len(), k);
+            for ((id, distance, &item), &(d, expected_id)) in result.iter().zip(&expected) {
+                assert_eq!((*id, item as ItemId), (expected_id, expected_id));
+                assert!((distance - d).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearest_sparse_and_small() {
+        // 细层级下相距很远的少量条目，需要逐级放大搜索范围
+        let mut index = GeoSotIndex::new(26);
+        let a = index.insert(116.4, 39.9, "beijing");
+        let b = index.insert(-73.98, 40.75, "new york");
+        let c = index.insert(151.21, -33.87, "sydney");
+        let result = index.nearest(121.47, 31.23, 2);
+        assert_eq!(result.iter().map(|&(id, _, _)| id).collect::<Vec<_>>(), vec![a, c]);
+        assert!((result[0].1 - haversine_distance(121.47, 31.23, 116.4, 39.9)).abs() < 1e-6);
+
+        // k 大于条目数时返回全部条目
+        let all = index.nearest(0.0, 0.0, 10);
+        assert_eq!(all.len(), 3);
+        assert!(all.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(all.iter().any(|&(id, _, _)| id == b));
+
+        assert!(index.nearest(0.0, 0.0, 0).is_empty());
+        assert!(GeoSotIndex::<()>::new(10).nearest(0.0, 0.0, 3).is_empty());
+    }
+
+    #[test]
+    fn test_insert_remove_update() {
+        let mut index = GeoSotIndex::new(16);
+        let a = index.insert(116.0, 39.0, 1);
+        let b = index.insert(121.0, 31.0, 2);
+        assert_eq!(index.len(), 2);
+
+        assert!(index.update(a, 121.0, 31.0));
+        assert_eq!(index.position(a), Some((121.0, 31.0)));
+        assert!(index.query_bbox(115.0, 38.0, 117.0, 40.0).is_empty());
+        assert_eq!(ids(index.query_bbox(120.0, 30.0, 122.0, 32.0)), vec![a, b]);
+
+        *index.get_mut(b).unwrap() = 20;
+        assert_eq!(index.remove(b), Some(20));
+        assert_eq!(index.remove(b), None);
+        assert!(!index.update(b, 0.0, 0.0));
+        assert_eq!(index.iter().map(|(id, _, _, &v)| (id, v)).collect::<Vec<_>>(), vec![(a, 1)]);
+        assert_eq!(index.insert(0.0, 0.0, 3), 2);
+    }
+}
@@ -4,6 +4,31 @@ use std::f64;
 
 pub mod utils;
 pub mod spatial;
+pub mod storage;
+pub mod geojson;
+pub mod geometry;
+pub mod coverer;
+pub mod wkt;
+pub mod binary;
+pub mod wkb;
+pub mod geohash;
+pub mod tile;
+pub mod mgrs;
+pub mod dms;
+pub mod csv;
+pub mod index;
+pub mod aggregate;
+pub mod grid;
+pub mod morphology;
+pub mod distance;
+pub mod topology;
+pub mod extent;
+#[cfg(feature = "serde")]
+pub mod serialization;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+#[cfg(feature = "geo")]
+pub mod geo;
 
 /// 将十进制经纬度获取 geomgrid 值
 /// 二维莫顿码
@@ -20,6 +45,27 @@ pub fn get_code(lng: f64, lat: f64, precision: usize) -> u64 {
     magic_bits(lng, lat)
 }
 
+/// 批量将经纬度编码为 geomgrid 值，结果顺序与输入一致
+/// # 参数
+/// - `points`: (经度, 纬度) 列表
+/// - `precision`: 精度,取值范围 [1~32]
+#[pyfunction]
+pub fn get_codes(points: Vec<(f64, f64)>, precision: usize) -> Vec<u64> {
+    encode_points(&points, precision)
+}
+
+/// 批量编码的切片版本，启用 `parallel` 特性时并行计算
+pub fn encode_points(points: &[(f64, f64)], precision: usize) -> Vec<u64> {
+    #[cfg(feature = "parallel")]
+    {
+        crate::parallel::encode_points(points, precision)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        points.iter().map(|&(lng, lat)| get_code(lng, lat, precision)).collect()
+    }
+}
+
 /// 将 geomgrid 编码转为经纬度
 /// # 参数
 /// - `code`: geomgrid 编码
@@ -96,7 +142,7 @@ pub fn code2dec(x: u32) -> f64 {
 /// # 参数
 /// - `lng`: 经度
 /// - `lat`: 纬度
-fn magic_bits(lng: u32, lat: u32) -> u64 {
+pub fn magic_bits(lng: u32, lat: u32) -> u64 {
     split_by_bits(lng) | (split_by_bits(lat) << 1)
 }
 
@@ -140,10 +186,13 @@ fn round(x: f64, y: i32) -> f64 {
 /// 将 geomgrid 转为文本形式
 /// # 参数
 /// - `code`: geomgrid 编码
-/// - `level`: 精度等级, 取值范围 [1~32]
+/// - `level`: 精度等级, 取值范围 [1~32]；第 0 级（全球网格）输出 `"G"`
 #[pyfunction]
 pub fn to_string(code: u64, level: usize) -> String {
     let mut str_out = String::from("G");
+    if level == 0 {
+        return str_out;
+    }
     let level = level - 1;
     for i in (31 - level..=31).rev() {
         let v = (code >> (i * 2)) & 0x3;
@@ -161,6 +210,54 @@ pub fn to_string(code: u64, level: usize) -> String {
     str_out
 }
 
+/// 将文本形式的网格编码解析为 geomgrid 编码与精度等级，是 `to_string` 的逆运算
+///
+/// 分隔符必须出现在 `to_string` 输出的位置上，度、分、秒超出范围的编码（如 `G3333`）视为不合法。
+/// # 参数
+/// - `grid`: 网格字符串，如 `G001023122-203103-131010.33003300330`
+/// # 返回
+/// - `Option<(u64, usize)>`: 编码与精度等级，格式不合法时返回 `None`
+/// # Examples
+/// ```
+/// use geosot::{from_string, to_string};
+/// let code: u64 = 339638376531246140;
+/// assert_eq!(from_string(&to_string(code, 32)), Some((code, 32)));
+/// assert_eq!(from_string("G-0-1.2"), None);
+/// assert_eq!(from_string("G3333"), None);
+/// ```
+#[pyfunction]
+pub fn from_string(grid: &str) -> Option<(u64, usize)> {
+    let digits = grid.strip_prefix('G')?;
+    let mut code = 0u64;
+    let mut level = 0usize;
+    for ch in digits.chars() {
+        match ch {
+            '0'..='3' => {
+                if level == 32 {
+                    return None;
+                }
+                let v = ch as u64 - '0' as u64;
+                code |= v << ((31 - level) * 2);
+                level += 1;
+            }
+            '-' | '.' => (),
+            _ => return None,
+        }
+    }
+    if level == 0 {
+        return None;
+    }
+    // 分隔符的个数与位置由层级唯一确定
+    if to_string(code, level) != grid {
+        return None;
+    }
+    let (lng, lat) = un_magic_bits(code);
+    if !(spatial::valid_axis_code(lng, 180) && spatial::valid_axis_code(lat, 90)) {
+        return None;
+    }
+    Some((code, level))
+}
+
 
 ///  根据 geomgrid 分离出经度和纬度的编码
 /// # 参数
@@ -203,15 +300,40 @@ pub fn merge_by_bits(m: u64) -> u32 {
 #[pymodule]
 fn geosot(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(get_code, m)?)?;
+    m.add_function(wrap_pyfunction!(get_codes, m)?)?;
     m.add_function(wrap_pyfunction!(decode_by_geomgrid, m)?)?;
     m.add_function(wrap_pyfunction!(dec2code, m)?)?;
     m.add_function(wrap_pyfunction!(code2dec, m)?)?;
     m.add_function(wrap_pyfunction!(split_by_bits, m)?)?;
     m.add_function(wrap_pyfunction!(to_string, m)?)?;
+    m.add_function(wrap_pyfunction!(from_string, m)?)?;
     m.add_function(wrap_pyfunction!(un_magic_bits, m)?)?;
     m.add_function(wrap_pyfunction!(merge_by_bits, m)?)?;
+    m.add_function(wrap_pyfunction!(geohash::geohash_encode, m)?)?;
+    m.add_function(wrap_pyfunction!(geohash::geohash_bounds, m)?)?;
+    m.add_function(wrap_pyfunction!(geohash::geohash_precision_for_level, m)?)?;
+    m.add_function(wrap_pyfunction!(geohash::level_for_geohash_precision, m)?)?;
+    m.add_function(wrap_pyfunction!(tile::lnglat_to_tile, m)?)?;
+    m.add_function(wrap_pyfunction!(tile::tile_bounds, m)?)?;
+    m.add_function(wrap_pyfunction!(tile::zoom_for_level, m)?)?;
+    m.add_function(wrap_pyfunction!(mgrs::mgrs_to_lnglat, m)?)?;
+    m.add_function(wrap_pyfunction!(mgrs::lnglat_to_mgrs, m)?)?;
+    m.add_function(wrap_pyfunction!(mgrs::utm_to_lnglat, m)?)?;
+    m.add_function(wrap_pyfunction!(mgrs::level_for_mgrs_precision, m)?)?;
+    m.add_function(wrap_pyfunction!(mgrs::mgrs_precision_for_level, m)?)?;
+    m.add_function(wrap_pyfunction!(dms::parse_coordinates, m)?)?;
+    m.add_function(wrap_pyfunction!(dms::format_dms, m)?)?;
+    m.add_function(wrap_pyfunction!(dms::format_ddm, m)?)?;
+    m.add_function(wrap_pyfunction!(csv::encode_csv, m)?)?;
+    m.add_class::<index::PyGeoSotIndex>()?;
+    m.add_class::<aggregate::PyGeoSotAggregator>()?;
+    m.add_class::<grid::PyGeoSotGrid>()?;
+    m.add_class::<grid::Resampling>()?;
+    m.add_class::<grid::Focal>()?;
+    m.add_class::<morphology::Connectivity>()?;
     m.add_class::<spatial::GeoSotCell>()?;
     m.add_class::<spatial::GeoSotRegion>()?;
+    m.add_class::<storage::RegionStorage>()?;
     m.add_wrapped(wrap_pymodule!(spatial::spatial_analysis))?;
     Ok(())
 }
\ No newline at end of file
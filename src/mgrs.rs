@@ -0,0 +1,584 @@
+//! MGRS / UTM 坐标与 GeoSOT 的互相转换（WGS84）
+//!
+//! UTM 正反算使用 Krüger 级数（6 阶），在带内精度优于 1 毫米。
+//! 仅支持 UTM 覆盖的 80°S ~ 84°N，极区的 UPS 不在此列。
+//! 挪威（32V）与斯瓦尔巴（31X ~ 37X）的特殊分带按标准处理。
+
+use pyo3::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::geometry::ParseError;
+use crate::spatial::GeoSotCell;
+use crate::utils::{get_cell_size_in_degree, WGS84_A as A, WGS84_F as F};
+
+/// UTM 中央经线比例因子
+const K0: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500_000.0;
+const FALSE_NORTHING: f64 = 10_000_000.0;
+
+/// 纬度带字母，每带 8 度，X 带为 72°N ~ 84°N
+const LAT_BANDS: &[u8] = b"CDEFGHJKLMNPQRSTUVWXX";
+/// 100 km 方格的列字母，按带号循环
+const COL_LETTERS: [&[u8]; 3] = [b"ABCDEFGH", b"JKLMNPQR", b"STUVWXYZ"];
+/// 100 km 方格的行字母，奇偶带号错开 5 个字母
+const ROW_LETTERS: [&[u8]; 2] = [b"ABCDEFGHJKLMNPQRSTUV", b"FGHJKLMNPQRSTUVABCDE"];
+
+/// UTM 坐标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Utm {
+    /// 带号 [1~60]
+    pub zone: u8,
+    /// 是否为北半球
+    pub north: bool,
+    /// 东坐标（米，含 500 km 东偏）
+    pub easting: f64,
+    /// 北坐标（米，南半球含 10000 km 北偏）
+    pub northing: f64,
+}
+
+/// Krüger 级数系数：(A, α, β)
+fn kruger() -> (f64, [f64; 6], [f64; 6]) {
+    let n = F / (2.0 - F);
+    let (n2, n3, n4, n5, n6) = (n * n, n.powi(3), n.powi(4), n.powi(5), n.powi(6));
+    let a = A / (1.0 + n) * (1.0 + n2 / 4.0 + n4 / 64.0 + n6 / 256.0);
+    let alpha = [
+        n / 2.0 - 2.0 / 3.0 * n2 + 5.0 / 16.0 * n3 + 41.0 / 180.0 * n4 - 127.0 / 288.0 * n5 + 7891.0 / 37800.0 * n6,
+        13.0 / 48.0 * n2 - 3.0 / 5.0 * n3 + 557.0 / 1440.0 * n4 + 281.0 / 630.0 * n5 - 1983433.0 / 1935360.0 * n6,
+        61.0 / 240.0 * n3 - 103.0 / 140.0 * n4 + 15061.0 / 26880.0 * n5 + 167603.0 / 181440.0 * n6,
+        49561.0 / 161280.0 * n4 - 179.0 / 168.0 * n5 + 6601661.0 / 7257600.0 * n6,
+        34729.0 / 80640.0 * n5 - 3418889.0 / 1995840.0 * n6,
+        212378941.0 / 319334400.0 * n6,
+    ];
+    let beta = [
+        n / 2.0 - 2.0 / 3.0 * n2 + 37.0 / 96.0 * n3 - 1.0 / 360.0 * n4 - 81.0 / 512.0 * n5 + 96199.0 / 604800.0 * n6,
+        1.0 / 48.0 * n2 + 1.0 / 15.0 * n3 - 437.0 / 1440.0 * n4 + 46.0 / 105.0 * n5 - 1118711.0 / 3870720.0 * n6,
+        17.0 / 480.0 * n3 - 37.0 / 840.0 * n4 - 209.0 / 4480.0 * n5 + 5569.0 / 90720.0 * n6,
+        4397.0 / 161280.0 * n4 - 11.0 / 504.0 * n5 - 830251.0 / 7257600.0 * n6,
+        4583.0 / 161280.0 * n5 - 108847.0 / 3991680.0 * n6,
+        20648693.0 / 638668800.0 * n6,
+    ];
+    (a, alpha, beta)
+}
+
+/// 带号的中央经线（度）
+fn central_meridian(zone: u8) -> f64 {
+    (zone as f64 - 1.0) * 6.0 - 180.0 + 3.0
+}
+
+/// 经纬度所在的 UTM 带号，考虑挪威与斯瓦尔巴的特殊分带
+fn zone_for(lng: f64, lat: f64) -> u8 {
+    let mut zone = (((lng + 180.0) / 6.0).floor() as i64 + 1).clamp(1, 60) as u8;
+    if (56.0..64.0).contains(&lat) && (3.0..12.0).contains(&lng) {
+        zone = 32;
+    }
+    if (72.0..=84.0).contains(&lat) && lng >= 0.0 {
+        zone = match lng {
+            l if l < 9.0 => 31,
+            l if l < 21.0 => 33,
+            l if l < 33.0 => 35,
+            l if l < 42.0 => 37,
+            _ => zone,
+        };
+    }
+    zone
+}
+
+/// 纬度带字母，超出 80°S ~ 84°N 时返回 None
+fn lat_band(lat: f64) -> Option<char> {
+    if !(-80.0..=84.0).contains(&lat) {
+        return None;
+    }
+    let idx = ((lat / 8.0).floor() + 10.0).clamp(0.0, 20.0) as usize;
+    Some(LAT_BANDS[idx] as char)
+}
+
+impl Utm {
+    /// 经纬度转 UTM，超出 80°S ~ 84°N 时返回 None
+    pub fn from_lnglat(lng: f64, lat: f64) -> Option<Utm> {
+        lat_band(lat)?;
+        let zone = zone_for(lng, lat);
+        Some(Self::from_lnglat_in_zone(lng, lat, zone))
+    }
+
+    /// 在指定带号下计算 UTM 坐标（允许超出带的范围）
+    pub fn from_lnglat_in_zone(lng: f64, lat: f64, zone: u8) -> Utm {
+        let (a, alpha, _) = kruger();
+        let e = (F * (2.0 - F)).sqrt();
+        let phi = lat.to_radians();
+        let lambda = (lng - central_meridian(zone)).to_radians();
+
+        let tau = phi.tan();
+        let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+        let tau_p = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+        let xi_p = tau_p.atan2(lambda.cos());
+        let eta_p = (lambda.sin() / (tau_p * tau_p + lambda.cos().powi(2)).sqrt()).asinh();
+
+        let (mut xi, mut eta) = (xi_p, eta_p);
+        for (j, a_j) in alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi += a_j * (k * xi_p).sin() * (k * eta_p).cosh();
+            eta += a_j * (k * xi_p).cos() * (k * eta_p).sinh();
+        }
+
+        let north = lat >= 0.0;
+        Utm {
+            zone,
+            north,
+            easting: K0 * a * eta + FALSE_EASTING,
+            northing: K0 * a * xi + if north { 0.0 } else { FALSE_NORTHING },
+        }
+    }
+
+    /// UTM 转经纬度 (lng, lat)
+    pub fn to_lnglat(&self) -> (f64, f64) {
+        let (a, _, beta) = kruger();
+        let e = (F * (2.0 - F)).sqrt();
+        let x = self.easting - FALSE_EASTING;
+        let y = self.northing - if self.north { 0.0 } else { FALSE_NORTHING };
+        let (eta, xi) = (x / (K0 * a), y / (K0 * a));
+
+        let (mut xi_p, mut eta_p) = (xi, eta);
+        for (j, b_j) in beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_p -= b_j * (k * xi).sin() * (k * eta).cosh();
+            eta_p -= b_j * (k * xi).cos() * (k * eta).sinh();
+        }
+
+        let sinh_eta = eta_p.sinh();
+        let (sin_xi, cos_xi) = xi_p.sin_cos();
+        let tau_p = sin_xi / (sinh_eta * sinh_eta + cos_xi * cos_xi).sqrt();
+
+        // 牛顿迭代由共形纬度求大地纬度
+        let mut tau = tau_p;
+        for _ in 0..10 {
+            let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+            let tau_i = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+            let delta = (tau_p - tau_i) / (1.0 + tau_i * tau_i).sqrt() * (1.0 + (1.0 - e * e) * tau * tau)
+                / ((1.0 - e * e) * (1.0 + tau * tau).sqrt());
+            tau += delta;
+            if delta.abs() < 1e-12 {
+                break;
+            }
+        }
+
+        let lat = tau.atan().to_degrees();
+        let lng = central_meridian(self.zone) + sinh_eta.atan2(cos_xi).to_degrees();
+        (lng, lat)
+    }
+}
+
+impl fmt::Display for Utm {
+    /// 形如 `31 N 448252 5411933`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hemisphere = if self.north { 'N' } else { 'S' };
+        write!(f, "{} {} {:.0} {:.0}", self.zone, hemisphere, self.easting, self.northing)
+    }
+}
+
+/// 逐字符扫描文本，记录列号（从 1 开始），空白会被跳过
+struct Scanner {
+    chars: Vec<(usize, char)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Scanner {
+    fn new(text: &str) -> Self {
+        let chars: Vec<(usize, char)> = text
+            .chars()
+            .enumerate()
+            .map(|(i, c)| (i + 1, c.to_ascii_uppercase()))
+            .collect();
+        let end = chars.len() + 1;
+        Self { chars, pos: 0, end }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|(_, c)| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn column(&self) -> usize {
+        self.chars.get(self.pos).map_or(self.end, |&(col, _)| col)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(1, self.column(), message)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_spaces();
+        self.chars.get(self.pos).map(|&(_, c)| c)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    /// 连续的数字（不跳过中间的空白）
+    fn digits(&mut self) -> String {
+        self.skip_spaces();
+        let mut digits = String::new();
+        while let Some(&(_, c)) = self.chars.get(self.pos).filter(|(_, c)| c.is_ascii_digit()) {
+            digits.push(c);
+            self.pos += 1;
+        }
+        digits
+    }
+
+    /// 连续的数字与小数点
+    fn number(&mut self) -> Result<f64, ParseError> {
+        self.skip_spaces();
+        let start = self.pos;
+        let mut text = String::new();
+        while let Some(&(_, c)) = self.chars.get(self.pos).filter(|(_, c)| c.is_ascii_digit() || *c == '.') {
+            text.push(c);
+            self.pos += 1;
+        }
+        text.parse().map_err(|_| {
+            self.pos = start;
+            self.error("expected number")
+        })
+    }
+
+    /// 带号 [1~60]
+    fn zone(&mut self) -> Result<u8, ParseError> {
+        let column = self.column_after_spaces();
+        let digits = self.digits();
+        match digits.parse::<u8>() {
+            Ok(zone) if (1..=60).contains(&zone) && digits.len() <= 2 => Ok(zone),
+            _ => Err(ParseError::new(1, column, "zone must be between 1 and 60")),
+        }
+    }
+
+    fn column_after_spaces(&mut self) -> usize {
+        self.skip_spaces();
+        self.column()
+    }
+
+    fn finish(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.error(format!("unexpected character '{c}'"))),
+        }
+    }
+}
+
+impl FromStr for Utm {
+    type Err = ParseError;
+
+    /// 解析形如 `31N 448252 5411933` 或 `31 S 334873.2 6252266.1` 的 UTM 坐标
+    fn from_str(text: &str) -> Result<Self, ParseError> {
+        let mut scanner = Scanner::new(text);
+        let zone = scanner.zone()?;
+        let north = match scanner.peek() {
+            Some('N') => true,
+            Some('S') => false,
+            _ => return Err(scanner.error("expected hemisphere 'N' or 'S'")),
+        };
+        scanner.next();
+        let easting = scanner.number()?;
+        let northing = scanner.number()?;
+        scanner.finish()?;
+        Ok(Utm { zone, north, easting, northing })
+    }
+}
+
+/// MGRS 坐标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mgrs {
+    /// 对应的 UTM 坐标（方格西南角）
+    pub utm: Utm,
+    /// 纬度带字母
+    pub band: char,
+    /// 东、北坐标各自的位数 [0~5]，分辨率为 10^(5 - precision) 米
+    pub precision: usize,
+}
+
+impl Mgrs {
+    /// 经纬度转 MGRS，超出 80°S ~ 84°N 或东坐标不在 100 km 列字母范围内时返回 None
+    pub fn from_lnglat(lng: f64, lat: f64, precision: usize) -> Option<Mgrs> {
+        let band = lat_band(lat)?;
+        let utm = Utm::from_lnglat(lng, lat)?;
+        if !(100_000.0..900_000.0).contains(&utm.easting) {
+            return None;
+        }
+        let resolution = 10f64.powi(5 - precision.min(5) as i32);
+        // MGRS 向下截断到方格西南角
+        let snap = |v: f64| (v / resolution).floor() * resolution;
+        Some(Mgrs {
+            utm: Utm { easting: snap(utm.easting), northing: snap(utm.northing), ..utm },
+            band,
+            precision: precision.min(5),
+        })
+    }
+
+    /// 方格边长（米）
+    pub fn resolution(&self) -> f64 {
+        10f64.powi(5 - self.precision as i32)
+    }
+
+    /// 方格西南角的经纬度 (lng, lat)
+    pub fn to_lnglat(&self) -> (f64, f64) {
+        self.utm.to_lnglat()
+    }
+
+    /// 方格中心的经纬度 (lng, lat)
+    pub fn center(&self) -> (f64, f64) {
+        let half = self.resolution() / 2.0;
+        Utm { easting: self.utm.easting + half, northing: self.utm.northing + half, ..self.utm }.to_lnglat()
+    }
+
+    /// 方格中心所在、大小与 MGRS 精度最接近的 GeoSOT 网格
+    pub fn to_cell(&self) -> GeoSotCell {
+        let (lng, lat) = self.center();
+        GeoSotCell::from_coords(lng, lat, level_for_mgrs_precision(self.precision))
+    }
+}
+
+impl fmt::Display for Mgrs {
+    /// 形如 `31U DQ 48251 11932`
+    ///
+    /// 东坐标必须在 100 000 ~ 899 999 米之间，否则没有对应的 100 km 列字母：
+    /// 调试构建中断言失败，发布构建返回 `fmt::Error`，不会写出错误的方格。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Utm { zone, easting, northing, .. } = self.utm;
+        debug_assert!(
+            (100_000.0..900_000.0).contains(&easting),
+            "MGRS easting {easting} is outside 100 000 ~ 899 999"
+        );
+        let col = (easting / 100_000.0).floor() as usize;
+        let row = (northing / 100_000.0).floor() as usize % 20;
+        let col_letter = match col {
+            1..=8 => COL_LETTERS[(zone as usize - 1) % 3][col - 1] as char,
+            _ => return Err(fmt::Error),
+        };
+        let row_letter = ROW_LETTERS[(zone as usize - 1) % 2][row] as char;
+        write!(f, "{}{} {}{}", zone, self.band, col_letter, row_letter)?;
+        if self.precision > 0 {
+            let scale = self.resolution();
+            let e = ((easting % 100_000.0) / scale).floor() as u64;
+            let n = ((northing % 100_000.0) / scale).floor() as u64;
+            write!(f, " {:0width$} {:0width$}", e, n, width = self.precision)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Mgrs {
+    type Err = ParseError;
+
+    /// 解析形如 `31U DQ 48251 11932` 或 `31UDQ4825111932` 的 MGRS 坐标，大小写均可
+    fn from_str(text: &str) -> Result<Self, ParseError> {
+        let mut scanner = Scanner::new(text);
+        let zone = scanner.zone()?;
+
+        let band = match scanner.peek() {
+            Some(c) if c != 'Y' && c != 'Z' && LAT_BANDS.contains(&(c as u8)) => c,
+            _ => return Err(scanner.error("expected latitude band letter (C-X, excluding I and O)")),
+        };
+        scanner.next();
+
+        let col_letters = COL_LETTERS[(zone as usize - 1) % 3];
+        let col = match scanner.peek().and_then(|c| col_letters.iter().position(|&l| l as char == c)) {
+            Some(col) => col + 1,
+            None => return Err(scanner.error(format!("invalid 100 km column letter for zone {zone}"))),
+        };
+        scanner.next();
+
+        let row_letters = ROW_LETTERS[(zone as usize - 1) % 2];
+        let row = match scanner.peek().and_then(|c| row_letters.iter().position(|&l| l as char == c)) {
+            Some(row) => row,
+            None => return Err(scanner.error("invalid 100 km row letter")),
+        };
+        scanner.next();
+
+        // 数字部分可以是连续的 2n 位，也可以用空白分成两段 n 位
+        let column = scanner.column_after_spaces();
+        let mut digits = scanner.digits();
+        let second = scanner.digits();
+        let split_unevenly = !second.is_empty() && second.len() != digits.len();
+        digits.push_str(&second);
+        scanner.finish()?;
+        if split_unevenly || !digits.len().is_multiple_of(2) || digits.len() > 10 {
+            return Err(ParseError::new(1, column, "easting and northing must have the same number of digits (0-5)"));
+        }
+        let precision = digits.len() / 2;
+        let scale = 10f64.powi(5 - precision as i32);
+        let (e, n) = digits.split_at(precision);
+        let e = e.parse::<f64>().unwrap_or(0.0) * scale;
+        let n = n.parse::<f64>().unwrap_or(0.0) * scale;
+
+        // 行字母每 2000 km 循环一次，用纬度带的南界确定所在的循环
+        let band_lat = (LAT_BANDS.iter().position(|&b| b as char == band).unwrap() as f64 - 10.0) * 8.0;
+        let band_northing = Utm::from_lnglat_in_zone(central_meridian(zone), band_lat, zone).northing;
+        let band_northing = (band_northing / 100_000.0).floor() * 100_000.0;
+        let mut northing = row as f64 * 100_000.0 + n;
+        while northing < band_northing {
+            northing += 2_000_000.0;
+        }
+
+        Ok(Mgrs {
+            utm: Utm { zone, north: band >= 'N', easting: col as f64 * 100_000.0 + e, northing },
+            band,
+            precision,
+        })
+    }
+}
+
+fn level_size_m(level: usize) -> f64 {
+    get_cell_size_in_degree(level as i32).unwrap() * 111_320.0
+}
+
+/// 网格边长（按纬度方向的米数）与 MGRS 分辨率最接近的 GeoSOT 层级
+#[pyfunction]
+pub fn level_for_mgrs_precision(precision: usize) -> usize {
+    let resolution = 10f64.powi(5 - precision.min(5) as i32);
+    (1..=32)
+        .min_by(|&a, &b| {
+            let d = |l: usize| (level_size_m(l) / resolution).ln().abs();
+            d(a).total_cmp(&d(b))
+        })
+        .unwrap()
+}
+
+/// 分辨率与 GeoSOT 层级最接近的 MGRS 精度 [0~5]
+#[pyfunction]
+pub fn mgrs_precision_for_level(level: usize) -> usize {
+    let size = level_size_m(level.clamp(1, 32));
+    (0..=5)
+        .min_by(|&a, &b| {
+            let d = |p: i32| (size / 10f64.powi(5 - p)).ln().abs();
+            d(a).total_cmp(&d(b))
+        })
+        .unwrap() as usize
+}
+
+/// 解析 MGRS 字符串，返回方格中心的经纬度 (lng, lat)
+#[pyfunction]
+pub fn mgrs_to_lnglat(text: &str) -> PyResult<(f64, f64)> {
+    Ok(text.parse::<Mgrs>()?.center())
+}
+
+/// 经纬度转 MGRS 字符串，超出 80°S ~ 84°N 时返回 None
+#[pyfunction]
+pub fn lnglat_to_mgrs(lng: f64, lat: f64, precision: usize) -> Option<String> {
+    Mgrs::from_lnglat(lng, lat, precision).map(|m| m.to_string())
+}
+
+/// 解析 UTM 字符串（如 `31N 448252 5411933`），返回经纬度 (lng, lat)
+#[pyfunction]
+pub fn utm_to_lnglat(text: &str) -> PyResult<(f64, f64)> {
+    Ok(text.parse::<Utm>()?.to_lnglat())
+}
+
+impl GeoSotCell {
+    /// 由 MGRS 字符串创建网格：取方格中心，层级与 MGRS 精度匹配
+    pub fn from_mgrs(text: &str) -> Result<Self, ParseError> {
+        Ok(text.parse::<Mgrs>()?.to_cell())
+    }
+
+    /// 网格中心的 MGRS 字符串，精度与层级匹配；超出 UTM 范围时返回 None
+    pub fn to_mgrs(&self) -> Option<String> {
+        let (lng, lat) = self.center();
+        lnglat_to_mgrs(lng, lat, mgrs_precision_for_level(self.level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utm_round_trip() {
+        let utm = Utm::from_lnglat(2.2945, 48.8582).unwrap();
+        assert_eq!(utm.to_string(), "31 N 448252 5411933");
+        let (lng, lat) = utm.to_lnglat();
+        assert!((lng - 2.2945).abs() < 1e-9 && (lat - 48.8582).abs() < 1e-9);
+
+        let origin = Utm::from_lnglat(0.0, 0.0).unwrap();
+        assert_eq!(origin.to_string(), "31 N 166021 0");
+
+        let south = Utm::from_lnglat(151.215, -33.857).unwrap();
+        assert!(!south.north);
+        let (lng, lat) = south.to_lnglat();
+        assert!((lng - 151.215).abs() < 1e-9 && (lat + 33.857).abs() < 1e-9);
+
+        let parsed: Utm = "31N 448252 5411933".parse().unwrap();
+        assert_eq!((parsed.zone, parsed.north), (31, true));
+        assert!(Utm::from_lnglat(0.0, 85.0).is_none());
+    }
+
+    #[test]
+    fn test_special_zones() {
+        assert_eq!(Utm::from_lnglat(5.0, 60.0).unwrap().zone, 32);
+        assert_eq!(Utm::from_lnglat(10.0, 78.0).unwrap().zone, 33);
+        assert_eq!(Utm::from_lnglat(5.0, 50.0).unwrap().zone, 31);
+    }
+
+    #[test]
+    fn test_mgrs_format_and_parse() {
+        let mgrs = Mgrs::from_lnglat(2.2945, 48.8582, 5).unwrap();
+        assert_eq!(mgrs.to_string(), "31U DQ 48251 11932");
+        assert_eq!(Mgrs::from_lnglat(0.0, 0.0, 5).unwrap().to_string(), "31N AA 66021 00000");
+
+        for text in ["31U DQ 48251 11932", "31udq4825111932", " 31U DQ 4825111932 "] {
+            let parsed: Mgrs = text.parse().unwrap();
+            assert_eq!(parsed, mgrs, "{text}");
+        }
+
+        // 南半球与多个 2000 km 循环
+        for &(lng, lat) in &[(151.215, -33.857), (-70.0, -79.5), (20.0, 83.0), (-0.5, 0.5)] {
+            let mgrs = Mgrs::from_lnglat(lng, lat, 5).unwrap();
+            let parsed: Mgrs = mgrs.to_string().parse().unwrap();
+            assert_eq!(parsed, mgrs);
+            let (l, t) = parsed.center();
+            assert!((l - lng).abs() < 1e-4 && (t - lat).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "outside 100 000 ~ 899 999")]
+    fn test_easting_out_of_range() {
+        let mut mgrs = Mgrs::from_lnglat(2.2945, 48.8582, 5).unwrap();
+        mgrs.utm.easting = 950_000.0;
+        let _ = mgrs.to_string();
+    }
+
+    #[test]
+    fn test_mgrs_errors() {
+        let err = "31U DI 48251 11932".parse::<Mgrs>().unwrap_err();
+        assert_eq!(err.column(), Some(6));
+        let err = "61U DQ".parse::<Mgrs>().unwrap_err();
+        assert_eq!(err.column(), Some(1));
+        let err = "31U DQ 4825 11932".parse::<Mgrs>().unwrap_err();
+        assert_eq!(err.column(), Some(8));
+        // 两段位数不同，即使总位数为偶数也不合法
+        let err = "31U DQ 482 51119".parse::<Mgrs>().unwrap_err();
+        assert_eq!(err.column(), Some(8));
+        assert!(err.message.contains("same number of digits"));
+        let err = "31U DQ 48251 11932 x".parse::<Mgrs>().unwrap_err();
+        assert_eq!(err.column(), Some(20));
+    }
+
+    #[test]
+    fn test_cell_conversion() {
+        assert_eq!(level_for_mgrs_precision(5), 26);
+        assert_eq!(level_for_mgrs_precision(4), 23);
+        assert_eq!(mgrs_precision_for_level(23), 4);
+
+        let cell = GeoSotCell::from_mgrs("31U DQ 48251 11932").unwrap();
+        assert_eq!(cell.level, 26);
+        let (lng, lat) = cell.center();
+        assert!((lng - 2.2945).abs() < 3e-5 && (lat - 48.8582).abs() < 3e-5);
+
+        let coarse = GeoSotCell::from_mgrs("31U DQ 48 11").unwrap();
+        assert_eq!(coarse.level, level_for_mgrs_precision(2));
+        assert_eq!(coarse.to_mgrs().unwrap(), "31U DQ 48 11");
+    }
+}
@@ -0,0 +1,165 @@
+//! 区域的形态学运算：膨胀、腐蚀、开运算、闭运算
+//!
+//! 结构元素为同层级网格的真实邻接关系（`GeoSotCell::neighbor`），
+//! 经度方向跨越 ±180° 时绕回；两极之外没有网格，不视为区域外部。
+
+use pyo3::prelude::*;
+
+use crate::spatial::{GeoSotCell, GeoSotRegion};
+
+/// 结构元素的邻接方式
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Connectivity {
+    /// 4 邻接：只共享边
+    Four,
+    /// 8 邻接：共享边或角点
+    #[default]
+    Eight,
+}
+
+impl Connectivity {
+    /// 一步之内的网格偏移 (dx, dy)
+    pub fn offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            Connectivity::Four => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+            Connectivity::Eight => &[(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)],
+        }
+    }
+
+    /// 网格一步之内的相邻网格，不含自身
+    pub fn neighbors(&self, cell: &GeoSotCell) -> impl Iterator<Item = GeoSotCell> + '_ {
+        let cell = *cell;
+        self.offsets().iter().filter_map(move |&(dx, dy)| cell.neighbor(dx, dy))
+    }
+}
+
+impl GeoSotRegion {
+    /// 膨胀 k 步：加入与区域距离不超过 k 步的所有网格
+    ///
+    /// 8 邻接时结果为每个网格的 k 圈方阵之并，4 邻接时为菱形邻域之并。
+    pub fn dilate(&self, k: usize, connectivity: Connectivity) -> GeoSotRegion {
+        let mut result = self.clone();
+        let mut frontier = self.cells();
+        for _ in 0..k {
+            let mut next = Vec::new();
+            for cell in &frontier {
+                for neighbor in connectivity.neighbors(cell) {
+                    if result.codes.insert(neighbor.code) {
+                        next.push(neighbor);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        result
+    }
+
+    /// 腐蚀 k 步：只保留 k 步之内的邻居全部属于区域的网格
+    pub fn erode(&self, k: usize, connectivity: Connectivity) -> GeoSotRegion {
+        let mut result = self.clone();
+        for _ in 0..k {
+            let removed: Vec<u64> = result
+                .codes
+                .iter()
+                .filter(|&code| {
+                    let cell = GeoSotCell::new(code, self.level);
+                    connectivity.neighbors(&cell).any(|n| !result.codes.contains(&n.code))
+                })
+                .collect();
+            if removed.is_empty() {
+                break;
+            }
+            for code in removed {
+                result.codes.remove(&code);
+            }
+        }
+        result
+    }
+
+    /// 开运算：先腐蚀后膨胀，去除小于结构元素的孤立网格和细小突起
+    pub fn open(&self, k: usize, connectivity: Connectivity) -> GeoSotRegion {
+        self.erode(k, connectivity).dilate(k, connectivity)
+    }
+
+    /// 闭运算：先膨胀后腐蚀，填补小于结构元素的空洞和缝隙
+    pub fn close(&self, k: usize, connectivity: Connectivity) -> GeoSotRegion {
+        self.dilate(k, connectivity).erode(k, connectivity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::RegionStorage;
+
+    /// 以 (lng, lat) 所在网格为左下角的 w x h 网格块
+    fn block(lng: f64, lat: f64, w: usize, h: usize, level: usize) -> GeoSotRegion {
+        let mut region = GeoSotRegion::new(level);
+        let mut row = GeoSotCell::from_coords(lng, lat, level);
+        for y in 0..h {
+            if y > 0 {
+                row = row.neighbor(0, 1).unwrap();
+            }
+            let mut cell = row;
+            for _ in 0..w {
+                region.add_code(cell.code);
+                cell = cell.neighbor(1, 0).unwrap();
+            }
+        }
+        region
+    }
+
+    #[test]
+    fn test_dilate_erode() {
+        let square = block(116.0, 39.0, 5, 5, 15);
+        assert_eq!(square.dilate(1, Connectivity::Eight).size(), 49);
+        assert_eq!(square.dilate(1, Connectivity::Four).size(), 45);
+        assert_eq!(square.dilate(2, Connectivity::Eight).size(), 81);
+        assert_eq!(square.dilate(0, Connectivity::Eight), square);
+        assert!(square.dilate(1, Connectivity::Eight).is_superset(&square));
+
+        let inner = block(116.0 + 1.0 / 60.0, 39.0 + 1.0 / 60.0, 3, 3, 15);
+        assert_eq!(square.erode(1, Connectivity::Eight), inner);
+        assert_eq!(square.erode(1, Connectivity::Four), inner);
+        assert_eq!(square.erode(3, Connectivity::Eight).size(), 0);
+        assert_eq!(square.dilate(2, Connectivity::Four).erode(2, Connectivity::Four), square);
+    }
+
+    #[test]
+    fn test_open_close() {
+        let square = block(116.0, 39.0, 5, 5, 15);
+
+        // 开运算去掉孤立噪点
+        let mut noisy = square.clone();
+        noisy.add_point(116.2, 39.2);
+        assert_eq!(noisy.open(1, Connectivity::Eight), square);
+
+        // 闭运算填补单个网格的空洞
+        let mut holed = square.clone();
+        holed.codes.remove(&crate::get_code(116.0 + 2.0 / 60.0, 39.0 + 2.0 / 60.0, 15));
+        assert_eq!(holed.size(), 24);
+        assert_eq!(holed.close(1, Connectivity::Eight), square);
+        assert_eq!(holed.close(1, Connectivity::Four), square);
+    }
+
+    #[test]
+    fn test_antimeridian_pole_and_storage() {
+        let edge = block(179.5, 10.5, 1, 1, 9);
+        let dilated = edge.dilate(1, Connectivity::Four);
+        assert_eq!(dilated.size(), 5);
+        assert!(dilated.contains_point(-179.5, 10.5));
+
+        // 极地网格北侧没有网格，不会因此被腐蚀
+        let cap = block(-179.5, 88.5, 360, 2, 9);
+        assert_eq!(cap.erode(1, Connectivity::Four).size(), 360);
+
+        let bitmap = block(116.0, 39.0, 5, 5, 15).to_storage(RegionStorage::Bitmap);
+        let dilated = bitmap.dilate(1, Connectivity::Eight);
+        assert_eq!(dilated.storage(), RegionStorage::Bitmap);
+        assert_eq!(dilated.size(), 49);
+    }
+}
@@ -0,0 +1,186 @@
+//! 并行区域构建与集合运算（`parallel` 特性）
+//!
+//! 所有函数的结果与串行路径完全一致：覆盖生成按行拆分后再合并，
+//! 集合运算按编码区间切分后分别归并，最终拼接成有序集合。
+
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+use crate::spatial::GeoSotRegion;
+
+/// 集合规模低于该阈值时直接走串行路径，避免线程调度开销
+pub const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+/// 集合运算类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOp {
+    Intersection,
+    Union,
+    Difference,
+    SymmetricDifference,
+}
+
+/// 判断两个集合是否值得并行计算
+pub fn worth_parallel(a: &BTreeSet<u64>, b: &BTreeSet<u64>) -> bool {
+    a.len() + b.len() >= PARALLEL_THRESHOLD
+}
+
+/// 按行并行生成矩形覆盖
+///
+/// # 参数
+/// * `rows` - 每一行的纬度采样值
+/// * `min_lng`, `max_lng` - 经度范围
+/// * `step` - 采样步长（度）
+/// * `level` - 精度等级
+pub fn cover_rows(rows: &[f64], min_lng: f64, max_lng: f64, step: f64, level: usize) -> BTreeSet<u64> {
+    rows.par_iter()
+        .flat_map_iter(|&lat| crate::spatial::row_codes(lat, min_lng, max_lng, step, level))
+        .collect()
+}
+
+/// 并行批量编码经纬度，点数低于阈值时串行计算，结果顺序与输入一致
+pub fn encode_points(points: &[(f64, f64)], level: usize) -> Vec<u64> {
+    if points.len() < PARALLEL_THRESHOLD {
+        return points.iter().map(|&(lng, lat)| crate::get_code(lng, lat, level)).collect();
+    }
+    points.par_iter().map(|&(lng, lat)| crate::get_code(lng, lat, level)).collect()
+}
+
+/// 并行计算两个有序编码集合的集合运算
+///
+/// 以 `a`、`b` 的合并规模切分为若干编码区间，每个区间独立归并，
+/// 区间之间互不重叠且有序，因此拼接后与串行结果完全一致。
+pub fn set_op(a: &BTreeSet<u64>, b: &BTreeSet<u64>, op: SetOp) -> BTreeSet<u64> {
+    let pivots = split_points(a, b);
+
+    let mut bounds = Vec::with_capacity(pivots.len() + 1);
+    let mut lower = Bound::Unbounded;
+    for &pivot in &pivots {
+        bounds.push((lower, Bound::Excluded(pivot)));
+        lower = Bound::Included(pivot);
+    }
+    bounds.push((lower, Bound::Unbounded));
+
+    let chunks: Vec<Vec<u64>> = bounds
+        .into_par_iter()
+        .map(|range| merge(a.range(range), b.range(range), op))
+        .collect();
+
+    chunks.into_iter().flatten().collect()
+}
+
+/// 从较大的集合中均匀抽取切分点
+fn split_points(a: &BTreeSet<u64>, b: &BTreeSet<u64>) -> Vec<u64> {
+    let larger = if a.len() >= b.len() { a } else { b };
+    let chunks = rayon::current_num_threads() * 4;
+    let chunk_size = (larger.len() / chunks).max(PARALLEL_THRESHOLD / 4);
+    larger.iter().step_by(chunk_size).skip(1).copied().collect()
+}
+
+/// 归并两个有序迭代器
+fn merge<'a, I, J>(a: I, b: J, op: SetOp) -> Vec<u64>
+where
+    I: Iterator<Item = &'a u64>,
+    J: Iterator<Item = &'a u64>,
+{
+    let (keep_a, keep_both, keep_b) = match op {
+        SetOp::Intersection => (false, true, false),
+        SetOp::Union => (true, true, true),
+        SetOp::Difference => (true, false, false),
+        SetOp::SymmetricDifference => (true, false, true),
+    };
+
+    let mut out = Vec::new();
+    let mut a = a.peekable();
+    let mut b = b.peekable();
+    loop {
+        match (a.peek(), b.peek()) {
+            (Some(&&x), Some(&&y)) if x < y => {
+                if keep_a {
+                    out.push(x);
+                }
+                a.next();
+            }
+            (Some(&&x), Some(&&y)) if x > y => {
+                if keep_b {
+                    out.push(y);
+                }
+                b.next();
+            }
+            (Some(&&x), Some(_)) => {
+                if keep_both {
+                    out.push(x);
+                }
+                a.next();
+                b.next();
+            }
+            (Some(_), None) => {
+                if keep_a {
+                    out.extend(a);
+                }
+                break;
+            }
+            (None, Some(_)) => {
+                if keep_b {
+                    out.extend(b);
+                }
+                break;
+            }
+            (None, None) => break,
+        }
+    }
+    out
+}
+
+/// 并行统计区域中相邻（共享边或角点）的网格对数量
+pub fn count_adjacent_pairs(region: &GeoSotRegion) -> usize {
+    // 每对相邻网格从两侧各计一次
+    region.cells().par_iter().map(|cell| region.neighbor_count(cell)).sum::<usize>() / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_op_matches_serial() {
+        let a: BTreeSet<u64> = (0..100_000u64).map(|x| x * 3).collect();
+        let b: BTreeSet<u64> = (0..100_000u64).map(|x| x * 5).collect();
+
+        assert_eq!(set_op(&a, &b, SetOp::Intersection), a.intersection(&b).copied().collect());
+        assert_eq!(set_op(&a, &b, SetOp::Union), a.union(&b).copied().collect());
+        assert_eq!(set_op(&a, &b, SetOp::Difference), a.difference(&b).copied().collect());
+        assert_eq!(
+            set_op(&a, &b, SetOp::SymmetricDifference),
+            a.symmetric_difference(&b).copied().collect()
+        );
+    }
+
+    #[test]
+    fn test_cover_rows_matches_serial() {
+        let step = 1.0 / 1800.0;
+        let rows: Vec<f64> = (0..50).map(|i| 39.0 + i as f64 * step).collect();
+        let serial: BTreeSet<u64> = rows
+            .iter()
+            .flat_map(|&lat| crate::spatial::row_codes(lat, 116.0, 116.1, step, 20))
+            .collect();
+        assert_eq!(cover_rows(&rows, 116.0, 116.1, step, 20), serial);
+    }
+
+    #[test]
+    fn test_encode_points_matches_serial() {
+        let points: Vec<(f64, f64)> = (0..50_000).map(|i| (-180.0 + i as f64 * 0.0072, -90.0 + i as f64 * 0.0036)).collect();
+        let serial: Vec<u64> = points.iter().map(|&(lng, lat)| crate::get_code(lng, lat, 24)).collect();
+        assert_eq!(encode_points(&points, 24), serial);
+    }
+
+    #[test]
+    fn test_set_op_empty() {
+        let a: BTreeSet<u64> = (0..10u64).collect();
+        let empty = BTreeSet::new();
+        assert_eq!(set_op(&a, &empty, SetOp::Union), a);
+        assert!(set_op(&a, &empty, SetOp::Intersection).is_empty());
+        assert!(set_op(&empty, &empty, SetOp::SymmetricDifference).is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+//! serde 序列化支持（`serde` 特性）
+//!
+//! 默认情况下 `GeoSotCell` 序列化为 `{"code": .., "level": ..}`，
+//! `GeoSotRegion` 序列化为 `{"level": .., "codes": [..]}`，
+//! `GeoSotGrid<T>` 序列化为 `{"level": .., "values": {编码: 值}}`。
+//! 反序列化时检查层级在 1~32 之内、编码在本层级之外的低位为 0。
+//! 需要其他表示时，可在字段上使用本模块提供的 `with` 模块：
+//!
+//! ```
+//! use geosot::serialization::{cell_as_code, cell_as_grid, region_as_grid};
+//! use geosot::spatial::{GeoSotCell, GeoSotRegion};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Site {
+//!     // "G001310322-000000-00000"
+//!     #[serde(with = "cell_as_grid")]
+//!     cell: GeoSotCell,
+//!     // 526498943937282048，反序列化时按 20 级解析
+//!     #[serde(serialize_with = "cell_as_code::serialize", deserialize_with = "cell_as_code::deserialize::<_, 20>")]
+//!     anchor: GeoSotCell,
+//!     // ["G001310322-000000-00000", ...]
+//!     #[serde(with = "region_as_grid")]
+//!     coverage: GeoSotRegion,
+//! }
+//! ```
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::collections::BTreeMap;
+
+use crate::grid::GeoSotGrid;
+use crate::spatial::{level_mask, GeoSotCell, GeoSotRegion};
+use crate::storage::{CodeSet, RegionStorage};
+
+/// 检查层级在 1~32 之内
+fn checked_level<E: serde::de::Error>(level: usize) -> Result<usize, E> {
+    if !(1..=32).contains(&level) {
+        return Err(E::custom(format!("level must be between 1 and 32, got {level}")));
+    }
+    Ok(level)
+}
+
+/// 检查层级范围与编码对齐，通过后构造网格
+fn checked_cell<E: serde::de::Error>(code: u64, level: usize) -> Result<GeoSotCell, E> {
+    checked_level::<E>(level)?;
+    if code & !level_mask(level) != 0 {
+        return Err(E::custom(format!("code {code} is not aligned to level {level}")));
+    }
+    Ok(GeoSotCell::new(code, level))
+}
+
+/// `GeoSotCell` 的默认反序列化结构
+#[derive(Deserialize)]
+struct CellRepr {
+    code: u64,
+    level: usize,
+}
+
+impl<'de> Deserialize<'de> for GeoSotCell {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let CellRepr { code, level } = CellRepr::deserialize(deserializer)?;
+        checked_cell(code, level)
+    }
+}
+
+/// `GeoSotRegion` 的默认序列化结构
+#[derive(Serialize, Deserialize)]
+struct RegionRepr {
+    level: usize,
+    #[serde(default, skip_serializing_if = "is_default_storage")]
+    storage: RegionStorage,
+    codes: Vec<u64>,
+}
+
+fn is_default_storage(storage: &RegionStorage) -> bool {
+    *storage == RegionStorage::default()
+}
+
+impl Serialize for GeoSotRegion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RegionRepr {
+            level: self.level,
+            storage: self.storage(),
+            codes: self.codes.iter().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GeoSotRegion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = RegionRepr::deserialize(deserializer)?;
+        checked_level::<D::Error>(repr.level)?;
+        for &code in &repr.codes {
+            checked_cell::<D::Error>(code, repr.level)?;
+        }
+        let mut codes = CodeSet::new(repr.storage, repr.level);
+        codes.extend(repr.codes);
+        Ok(GeoSotRegion { codes, level: repr.level })
+    }
+}
+
+/// `GeoSotGrid<T>` 的反序列化结构，与派生的 `Serialize` 字段一致
+#[derive(Deserialize)]
+struct GridRepr<T> {
+    level: usize,
+    values: BTreeMap<u64, T>,
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for GeoSotGrid<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let GridRepr { level, values } = GridRepr::deserialize(deserializer)?;
+        checked_level::<D::Error>(level)?;
+        for &code in values.keys() {
+            checked_cell::<D::Error>(code, level)?;
+        }
+        Ok(GeoSotGrid { level, values })
+    }
+}
+
+fn parse_grid<E: serde::de::Error>(grid: &str) -> Result<GeoSotCell, E> {
+    GeoSotCell::from_grid_string(grid).ok_or_else(|| E::custom(format!("invalid GeoSOT grid string: {grid}")))
+}
+
+/// 将 `GeoSotCell` 表示为网格字符串，如 `"G001310322-000000-00000"`
+pub mod cell_as_grid {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(cell: &GeoSotCell, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&cell.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GeoSotCell, D::Error> {
+        let grid = String::deserialize(deserializer)?;
+        parse_grid(&grid)
+    }
+}
+
+/// 将 `GeoSotCell` 表示为数值编码
+///
+/// 编码本身不携带精度等级，反序列化时需要通过 `LEVEL` 指定：
+/// `deserialize_with = "cell_as_code::deserialize::<_, 20>"`。
+pub mod cell_as_code {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(cell: &GeoSotCell, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(cell.code)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const LEVEL: usize>(
+        deserializer: D,
+    ) -> Result<GeoSotCell, D::Error> {
+        let code = u64::deserialize(deserializer)?;
+        checked_cell(code, LEVEL)
+    }
+}
+
+/// 将 `GeoSotRegion` 表示为网格字符串列表，所有网格必须处于同一精度等级
+///
+/// 层级从网格字符串推断，空列表无法确定层级，因此空区域在序列化时即报错，
+/// 需要保存空区域时请使用默认表示。
+pub mod region_as_grid {
+    use super::*;
+    use serde::ser::Error as _;
+
+    pub fn serialize<S: Serializer>(region: &GeoSotRegion, serializer: S) -> Result<S::Ok, S::Error> {
+        if region.is_empty() {
+            return Err(S::Error::custom("cannot write an empty region as a grid list: its level would be lost"));
+        }
+        serializer.collect_seq(region.to_strings())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GeoSotRegion, D::Error> {
+        let grids = Vec::<String>::deserialize(deserializer)?;
+        let mut cells = grids.iter().map(|grid| parse_grid::<D::Error>(grid));
+        let first = match cells.next() {
+            Some(cell) => cell?,
+            None => return Err(D::Error::custom("cannot infer level of an empty grid list")),
+        };
+        let mut region = GeoSotRegion::new(first.level);
+        region.add_code(first.code);
+        for cell in cells {
+            let cell = cell?;
+            if cell.level != region.level {
+                return Err(D::Error::custom(format!(
+                    "mixed levels in grid list: {} and {}",
+                    region.level, cell.level
+                )));
+            }
+            region.add_code(cell.code);
+        }
+        Ok(region)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Site {
+        #[serde(with = "cell_as_grid")]
+        grid: GeoSotCell,
+        #[serde(serialize_with = "cell_as_code::serialize", deserialize_with = "cell_as_code::deserialize::<_, 20>")]
+        code: GeoSotCell,
+        plain: GeoSotCell,
+    }
+
+    #[test]
+    fn test_cell_representations() {
+        let cell = GeoSotCell::from_coords(116.0, 39.0, 20);
+        let site = Site { grid: cell, code: cell, plain: cell };
+        let json = serde_json::to_value(&site).unwrap();
+
+        assert_eq!(json["grid"], cell.to_string());
+        assert_eq!(json["code"], cell.code);
+        assert_eq!(json["plain"]["code"], cell.code);
+        assert_eq!(json["plain"]["level"], 20);
+
+        let back: Site = serde_json::from_value(json).unwrap();
+        assert_eq!(back, site);
+    }
+
+    #[test]
+    fn test_invalid_grid_string() {
+        let err = serde_json::from_str::<Site>(r#"{"grid": "X12", "code": 0, "plain": {"code": 0, "level": 1}}"#);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_invalid_level_and_code() {
+        let cell = GeoSotCell::from_coords(116.0, 39.0, 20);
+        assert!(serde_json::from_str::<GeoSotCell>(r#"{"code": 0, "level": 0}"#).is_err());
+        assert!(serde_json::from_str::<GeoSotCell>(r#"{"code": 0, "level": 33}"#).is_err());
+        let unaligned = serde_json::json!({ "code": cell.code | 1, "level": 20 });
+        let err = serde_json::from_value::<GeoSotCell>(unaligned).unwrap_err();
+        assert!(err.to_string().contains("not aligned"), "{err}");
+
+        let code_field = serde_json::json!({ "grid": cell.to_string(), "code": cell.code | 1, "plain": cell });
+        assert!(serde_json::from_value::<Site>(code_field).is_err());
+
+        assert!(serde_json::from_str::<GeoSotRegion>(r#"{"level": 0, "codes": []}"#).is_err());
+        let unaligned = serde_json::json!({ "level": 20, "codes": [cell.code, cell.code | 1] });
+        assert!(serde_json::from_value::<GeoSotRegion>(unaligned).is_err());
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        let mut grid = GeoSotGrid::new(20);
+        let cell = GeoSotCell::from_coords(116.0, 39.0, 20);
+        grid.insert(cell, 1.5);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(serde_json::from_str::<GeoSotGrid<f64>>(&json).unwrap(), grid);
+
+        assert!(serde_json::from_str::<GeoSotGrid<f64>>(r#"{"level": 0, "values": {}}"#).is_err());
+        let mut values = BTreeMap::new();
+        values.insert((cell.code | 1).to_string(), 1.5);
+        let unaligned = serde_json::json!({ "level": 20, "values": values });
+        let err = serde_json::from_value::<GeoSotGrid<f64>>(unaligned).unwrap_err();
+        assert!(err.to_string().contains("not aligned"), "{err}");
+    }
+
+    #[test]
+    fn test_region_round_trip() {
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.01, 39.01, 20);
+        let json = serde_json::to_string(&region).unwrap();
+        assert!(!json.contains("storage"));
+        assert_eq!(serde_json::from_str::<GeoSotRegion>(&json).unwrap(), region);
+
+        let bitmap = region.to_storage(RegionStorage::Bitmap);
+        let back: GeoSotRegion = serde_json::from_str(&serde_json::to_string(&bitmap).unwrap()).unwrap();
+        assert_eq!(back.storage(), RegionStorage::Bitmap);
+        assert_eq!(back, region);
+    }
+
+    #[test]
+    fn test_region_as_grid() {
+        #[derive(Serialize, Deserialize)]
+        struct Coverage {
+            #[serde(with = "region_as_grid")]
+            region: GeoSotRegion,
+        }
+
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.01, 39.01, 20);
+        let json = serde_json::to_value(Coverage { region: region.clone() }).unwrap();
+        assert_eq!(json["region"][0], region.to_strings()[0]);
+
+        let back: Coverage = serde_json::from_value(json).unwrap();
+        assert_eq!(back.region, region);
+
+        let mixed = serde_json::json!({ "region": ["G0013", "G00131"] });
+        assert!(serde_json::from_value::<Coverage>(mixed).is_err());
+
+        // 空区域无法推断层级，写出时即报错；默认表示可以正常往返
+        let empty = GeoSotRegion::new(20);
+        assert!(serde_json::to_value(Coverage { region: empty.clone() }).is_err());
+        let json = serde_json::to_string(&empty).unwrap();
+        assert_eq!(serde_json::from_str::<GeoSotRegion>(&json).unwrap(), empty);
+    }
+}
@@ -1,10 +1,21 @@
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use std::collections::{HashSet, BTreeSet};
-use crate::{get_code, decode_by_geomgrid, to_string};
+use pyo3::types::PyBytes;
+use std::collections::BTreeSet;
+use std::fmt;
+use crate::{get_code, decode_by_geomgrid, from_string, magic_bits, to_string, un_magic_bits};
+use crate::utils::{
+    axis_code_at, axis_index, axis_tick_range, haversine_distance, initial_bearing, lng_cells_around, vincenty_distance,
+    TICKS_PER_DEGREE,
+};
+use crate::grid::PyGeoSotGrid;
+use crate::morphology::Connectivity;
+use crate::storage::{CodeSet, RegionStorage};
 
 // GeoSot网格单元，包含编码和精度级别
 #[pyclass]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct GeoSotCell {
     #[pyo3(get, set)]
     pub code: u64,
@@ -32,41 +43,106 @@ impl GeoSotCell {
         to_string(self.code, self.level)
     }
 
+    /// 从网格字符串创建GeoSot单元，格式不合法时返回 None
+    #[staticmethod]
+    pub fn from_grid_string(grid: &str) -> Option<Self> {
+        from_string(grid).map(|(code, level)| Self::new(code, level))
+    }
+
+    /// 获取网格的经纬度范围 (min_lng, min_lat, max_lng, max_lat)
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        let (x0, y0, x1, y1) = self.tick_bounds();
+        let deg = |t: i64| t as f64 / TICKS_PER_DEGREE as f64;
+        (
+            deg(x0).clamp(-180.0, 180.0),
+            deg(y0).clamp(-90.0, 90.0),
+            deg(x1).clamp(-180.0, 180.0),
+            deg(y1).clamp(-90.0, 90.0),
+        )
+    }
+
+    /// 获取网格中心点 (lng, lat)
+    pub fn center(&self) -> (f64, f64) {
+        let (min_lng, min_lat, max_lng, max_lat) = self.bounds();
+        ((min_lng + max_lng) / 2.0, (min_lat + max_lat) / 2.0)
+    }
+
+    /// 转为 GeoJSON Feature（Polygon），属性包含 code、level 与网格字符串
+    #[pyo3(name = "to_geojson")]
+    fn geojson_py(&self) -> String {
+        self.to_geojson()
+    }
+
+    /// 覆盖该网格的所有指定精度 Geohash
+    #[pyo3(name = "to_geohashes")]
+    fn geohashes_py(&self, precision: usize) -> Vec<String> {
+        self.to_geohashes(precision)
+    }
+
+    /// 与该网格相交的全部 XYZ 瓦片 (x, y)
+    #[pyo3(name = "to_tiles")]
+    fn tiles_py(&self, zoom: u8) -> Vec<(u32, u32)> {
+        self.to_tiles(zoom)
+    }
+
+    /// 由 MGRS 字符串创建网格，解析失败时抛出 ValueError
+    #[staticmethod]
+    #[pyo3(name = "from_mgrs")]
+    fn from_mgrs_py(text: &str) -> PyResult<Self> {
+        Ok(Self::from_mgrs(text)?)
+    }
+
+    /// 网格中心的 MGRS 字符串，超出 UTM 范围时返回 None
+    #[pyo3(name = "to_mgrs")]
+    fn mgrs_py(&self) -> Option<String> {
+        self.to_mgrs()
+    }
+
+    /// 由度分秒等格式的坐标对创建网格，解析失败时抛出 ValueError
+    #[staticmethod]
+    #[pyo3(name = "from_dms")]
+    fn from_dms_py(text: &str, level: usize) -> PyResult<Self> {
+        Ok(Self::from_dms(text, level)?)
+    }
+
+    /// 网格中心的度分秒表示（纬度在前）
+    #[pyo3(name = "to_dms")]
+    fn dms_py(&self) -> String {
+        self.to_dms()
+    }
+
     /// 获取父级单元（降低一级精度）
     pub fn parent(&self) -> Option<Self> {
         if self.level <= 1 {
             return None;
         }
         let parent_level = self.level - 1;
-        let _shift = (32 - parent_level) * 2;
-        let parent_code = (self.code >> 2) << 2;  // 清除最低2位
-        Some(Self::new(parent_code, parent_level))
+        Some(Self::new(self.code & level_mask(parent_level), parent_level))
     }
 
     /// 获取子级单元（增加一级精度）
+    ///
+    /// 分、秒字段只使用 0~59，截断网格（如 56~60 分）中落在有效范围之外的子网格不会返回，
+    /// 因此子网格可能少于 4 个。
     pub fn children(&self) -> Vec<Self> {
         if self.level >= 32 {
             return vec![];
         }
         let child_level = self.level + 1;
-        let mut children = Vec::new();
-
-        // 每个单元有4个子单元（00, 01, 10, 11）
-        for i in 0..4 {
-            let child_code = (self.code << 2) | i;
-            children.push(Self::new(child_code, child_level));
-        }
-        children
+        let shift = (32 - child_level) * 2;
+        // 每个单元有4个子单元，低位为经度、高位为纬度
+        (0..4u64)
+            .map(|i| Self::new(self.code | (i << shift), child_level))
+            .filter(|child| {
+                let (lng, lat) = un_magic_bits(child.code);
+                valid_axis_code(lng, 180) && valid_axis_code(lat, 90)
+            })
+            .collect()
     }
 
     /// 检查是否为另一个单元的祖先
     pub fn is_ancestor_of(&self, other: &Self) -> bool {
-        if self.level >= other.level {
-            return false;
-        }
-        let level_diff = other.level - self.level;
-        let shifted_other = other.code >> (level_diff * 2);
-        shifted_other == self.code
+        self.level < other.level && other.code & level_mask(self.level) == self.code
     }
 
     /// 检查是否为另一个单元的后代
@@ -74,19 +150,190 @@ impl GeoSotCell {
         other.is_ancestor_of(self)
     }
 
-    /// 检查两个单元是否相邻
+    /// 与另一个单元的最小公共祖先（包括自身），没有公共祖先时返回 None
+    ///
+    /// 一方是另一方的祖先时返回该祖先。第 1 级网格按经纬度符号划分四个象限，
+    /// 位于不同象限的两个单元没有公共祖先。
+    pub fn common_ancestor(&self, other: &Self) -> Option<Self> {
+        let level = common_prefix_level(self.code, other.code).min(self.level).min(other.level);
+        if level == 0 {
+            return None;
+        }
+        Some(Self::new(self.code & level_mask(level), level))
+    }
+
+    /// 从本单元向上到最小公共祖先的层级数，没有公共祖先时返回 None
+    ///
+    /// 同层级的两个单元互换后结果相同；本单元就是公共祖先时为 0。
+    pub fn hierarchy_distance(&self, other: &Self) -> Option<usize> {
+        self.common_ancestor(other).map(|ancestor| self.level - ancestor.level)
+    }
+
+    /// 检查两个单元是否相邻（共享边或角点）
     pub fn is_adjacent_to(&self, other: &Self) -> bool {
+        self.level == other.level && self.neighbors().contains(other)
+    }
+
+    /// 获取同层级的相邻网格（共享边或角点，最多 8 个）
+    ///
+    /// 经度方向跨越 ±180° 时绕回，纬度方向在两极处没有相邻网格。
+    pub fn neighbors(&self) -> Vec<Self> {
+        let mut cells = Vec::with_capacity(8);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if let Some(cell) = self.neighbor(dx, dy).filter(|c| c != self && !cells.contains(c)) {
+                    cells.push(cell);
+                }
+            }
+        }
+        cells
+    }
+
+    /// 获取 k 圈以内的同层级网格（以自身为中心、边长 2k+1 的方阵，含自身）
+    ///
+    /// 经度方向跨越 ±180° 时绕回并去重，纬度方向在两极处截断。
+    pub fn k_ring(&self, k: usize) -> Vec<Self> {
+        let mut row = vec![*self];
+        for d in [-1, 1] {
+            let mut cell = *self;
+            for _ in 0..k {
+                match cell.neighbor(d, 0) {
+                    Some(next) if !row.contains(&next) => {
+                        row.push(next);
+                        cell = next;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        let mut cells = row.clone();
+        for start in row {
+            for d in [-1, 1] {
+                let mut cell = start;
+                for _ in 0..k {
+                    match cell.neighbor(0, d) {
+                        Some(next) => {
+                            cells.push(next);
+                            cell = next;
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    /// 两个网格中心之间的球面大圆距离（haversine），单位为米
+    pub fn distance_m(&self, other: &Self) -> f64 {
+        let ((lng1, lat1), (lng2, lat2)) = (self.center(), other.center());
+        haversine_distance(lng1, lat1, lng2, lat2)
+    }
+
+    /// 两个网格中心之间的 WGS84 椭球面距离（Vincenty），单位为米，迭代不收敛时返回 None
+    pub fn vincenty_distance_m(&self, other: &Self) -> Option<f64> {
+        let ((lng1, lat1), (lng2, lat2)) = (self.center(), other.center());
+        vincenty_distance(lng1, lat1, lng2, lat2)
+    }
+
+    /// 从本网格中心指向另一个网格中心的大圆初始方位角（度），正北为 0，顺时针取值 [0, 360)
+    pub fn bearing_to(&self, other: &Self) -> f64 {
+        let ((lng1, lat1), (lng2, lat2)) = (self.center(), other.center());
+        initial_bearing(lng1, lat1, lng2, lat2)
+    }
+
+    /// 同层级网格之间的网格步数（曼哈顿距离），层级不同时返回 None
+    ///
+    /// 经度方向取绕地球两个方向中较短的一个；截断网格各算一步，与 `neighbor` 的步进一致。
+    pub fn grid_distance(&self, other: &Self) -> Option<u64> {
         if self.level != other.level {
-            return false;
+            return None;
         }
-        // 简化的相邻检查，实际实现需要考虑Morton编码的特性
-        let diff = if self.code > other.code {
-            self.code - other.code
-        } else {
-            other.code - self.code
+        let (lng1, lat1) = un_magic_bits(self.code);
+        let (lng2, lat2) = un_magic_bits(other.code);
+        let dx = (axis_index(lng1, self.level) - axis_index(lng2, self.level)).unsigned_abs();
+        let dy = (axis_index(lat1, self.level) - axis_index(lat2, self.level)).unsigned_abs();
+        let around = lng_cells_around(self.level) as u64;
+        Some(dx.min(around.saturating_sub(dx)) + dy)
+    }
+}
+
+/// 指定层级的编码掩码，保留前 `level` 对比特
+pub(crate) fn level_mask(level: usize) -> u64 {
+    match level {
+        0 => 0,
+        1..=31 => !0u64 << ((32 - level) * 2),
+        _ => !0u64,
+    }
+}
+
+/// 两个编码公共前缀的层级数，即异或结果的前导零个数除以 2
+pub(crate) fn common_prefix_level(a: u64, b: u64) -> usize {
+    (a ^ b).leading_zeros() as usize / 2
+}
+
+/// 32 位经度或纬度编码的度、分、秒字段是否在有效范围内
+pub(crate) fn valid_axis_code(code: u32, max_degree: u32) -> bool {
+    (code >> 23) & 0xFF <= max_degree && (code >> 17) & 0x3F < 60 && (code >> 11) & 0x3F < 60
+}
+
+/// 网格在一个轴上包含的刻度闭区间
+///
+/// 正半球网格为 [lo, hi)，负半球网格为 (-hi, -lo]，但刻度 0 始终属于正半球。
+fn inclusive_ticks(lo: i64, hi: i64) -> (i64, i64) {
+    if lo >= 0 {
+        (lo, hi - 1)
+    } else if hi == 0 {
+        (lo + 1, -1)
+    } else {
+        (lo + 1, hi)
+    }
+}
+
+impl GeoSotCell {
+    /// 获取网格范围 (min_lng, min_lat, max_lng, max_lat)，单位为刻度（1/2048 秒）
+    ///
+    /// 同一层级相邻网格的刻度边界严格相等，适合做精确的几何拼接。
+    pub fn tick_bounds(&self) -> (i64, i64, i64, i64) {
+        let (lng, lat) = un_magic_bits(self.code);
+        let (x0, x1) = axis_tick_range(lng, self.level);
+        let (y0, y1) = axis_tick_range(lat, self.level);
+        (x0, y0, x1, y1)
+    }
+
+    /// 按网格步数偏移 (dx, dy) 的同层级网格，dx、dy 取值为 -1、0、1
+    ///
+    /// 经度越过 ±180° 时绕回另一侧，纬度越过 ±90° 时返回 None。
+    pub fn neighbor(&self, dx: i32, dy: i32) -> Option<Self> {
+        const HALF_TURN: i64 = 180 * TICKS_PER_DEGREE;
+        const QUARTER_TURN: i64 = 90 * TICKS_PER_DEGREE;
+        let (x0, y0, x1, y1) = self.tick_bounds();
+        let step = |lo: i64, hi: i64, d: i32| {
+            let (lo, hi) = inclusive_ticks(lo, hi);
+            match d.signum() {
+                -1 => lo - 1,
+                1 => hi + 1,
+                _ => lo,
+            }
         };
-        // 相邻单元的编码差值应该是特定的值
-        diff == 1 || diff == 2 || diff == 4 || diff == 8
+        let mut x = step(x0, x1, dx);
+        if x <= -HALF_TURN {
+            x += 2 * HALF_TURN - 1;
+        } else if x >= HALF_TURN {
+            x -= 2 * HALF_TURN - 1;
+        }
+        let y = step(y0, y1, dy);
+        if y <= -QUARTER_TURN || y >= QUARTER_TURN {
+            return None;
+        }
+        let code = magic_bits(axis_code_at(x, self.level), axis_code_at(y, self.level));
+        Some(Self::new(code, self.level))
+    }
+}
+
+impl fmt::Display for GeoSotCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", to_string(self.code, self.level))
     }
 }
 
@@ -94,9 +341,8 @@ impl GeoSotCell {
 #[pyclass]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GeoSotRegion {
-    /// 编码集合，使用 BTreeSet 保持有序
-    #[pyo3(get, set)]
-    pub codes: BTreeSet<u64>,
+    /// 编码集合，按升序保存，可选 BTreeSet 或压缩位图存储
+    pub codes: CodeSet,
     /// 精度等级
     #[pyo3(get, set)]
     pub level: usize,
@@ -108,11 +354,48 @@ impl GeoSotRegion {
     #[new]
     pub fn new(level: usize) -> Self {
         Self {
-            codes: BTreeSet::new(),
+            codes: CodeSet::default(),
+            level,
+        }
+    }
+
+    /// 创建使用指定存储方式的空区域
+    #[staticmethod]
+    pub fn with_storage(level: usize, storage: RegionStorage) -> Self {
+        Self {
+            codes: CodeSet::new(storage, level),
             level,
         }
     }
 
+    /// 当前区域的存储方式
+    #[getter]
+    pub fn storage(&self) -> RegionStorage {
+        self.codes.storage()
+    }
+
+    /// 转换为指定存储方式的区域，编码内容不变
+    pub fn to_storage(&self, storage: RegionStorage) -> Self {
+        Self {
+            codes: self.codes.to_storage(storage, self.level),
+            level: self.level,
+        }
+    }
+
+    /// 编码集合（Python 中为 set）
+    #[getter(codes)]
+    fn codes_py(&self) -> BTreeSet<u64> {
+        self.codes.to_btree_set()
+    }
+
+    /// 设置编码集合，保留当前存储方式
+    #[setter(codes)]
+    fn set_codes_py(&mut self, codes: BTreeSet<u64>) {
+        let mut new_codes = CodeSet::new(self.storage(), self.level);
+        new_codes.extend(codes);
+        self.codes = new_codes;
+    }
+
     /// 从编码向量创建区域
     #[staticmethod]
     pub fn from_codes(codes: Vec<u64>, level: usize) -> Self {
@@ -130,42 +413,97 @@ impl GeoSotRegion {
 
         // 计算步长 - 使用更精确的步长计算
         let step = match level {
-            l if l >= 0 && l <= 9 => 2.0f64.powf(9.0 - l as f64),
-            l if l >= 10 && l <= 15 => 2.0f64.powf(15.0 - l as f64) / 60.0,
-            l if l >= 16 && l <= 32 => 2.0f64.powf(21.0 - l as f64) / 3600.0,
+            l if l <= 9 => 2.0f64.powf(9.0 - l as f64),
+            l if (10..=15).contains(&l) => 2.0f64.powf(15.0 - l as f64) / 60.0,
+            l if (16..=32).contains(&l) => 2.0f64.powf(21.0 - l as f64) / 3600.0,
             _ => 0.001, // 默认步长
         };
 
-        // 遍历矩形区域内的所有网格点
+        // 先生成每一行的纬度采样值，串行与并行路径按行遍历相同的网格点
+        let mut rows = Vec::new();
         let mut lat = min_lat;
         while lat <= max_lat {
-            let mut lng = min_lng;
-            while lng <= max_lng {
-                let code = get_code(lng, lat, level);
-                region.codes.insert(code);
-                lng += step;
-            }
+            rows.push(lat);
             lat += step;
         }
 
+        #[cfg(feature = "parallel")]
+        {
+            region.codes = crate::parallel::cover_rows(&rows, min_lng, max_lng, step, level).into();
+        }
+        #[cfg(not(feature = "parallel"))]
+        for lat in rows {
+            region.codes.extend(row_codes(lat, min_lng, max_lng, step, level));
+        }
+
         region
     }
 
     /// 从多边形创建 GeoSOT 编码集合（简化版本，使用包围盒）
     #[staticmethod]
-    pub fn from_polygon(points: Vec<(f64, f64)>, level: usize) -> Self {
-        if points.is_empty() {
-            return Self::new(level);
-        }
+    #[pyo3(name = "from_polygon")]
+    pub fn from_polygon_py(points: Vec<(f64, f64)>, level: usize) -> Self {
+        Self::from_polygon(&points, level)
+    }
 
-        // 计算包围盒
-        let min_lng = points.iter().map(|(lng, _)| *lng).fold(f64::INFINITY, f64::min);
-        let max_lng = points.iter().map(|(lng, _)| *lng).fold(f64::NEG_INFINITY, f64::max);
-        let min_lat = points.iter().map(|(_, lat)| *lat).fold(f64::INFINITY, f64::min);
-        let max_lat = points.iter().map(|(_, lat)| *lat).fold(f64::NEG_INFINITY, f64::max);
+    /// 解析 GeoJSON 文本并覆盖为指定层级的区域，解析失败时抛出 ValueError
+    #[staticmethod]
+    #[pyo3(name = "from_geojson")]
+    pub fn from_geojson_py(text: &str, level: usize) -> PyResult<Self> {
+        Ok(Self::from_geojson(text, level)?)
+    }
 
-        // 使用包围盒创建区域（实际应用中可以加入点在多边形内的判断）
-        Self::from_rectangle(min_lng, min_lat, max_lng, max_lat, level)
+    /// 解析 WKT 文本并覆盖为指定层级的区域，解析失败时抛出 ValueError
+    #[staticmethod]
+    #[pyo3(name = "from_wkt")]
+    pub fn from_wkt_py(text: &str, level: usize) -> PyResult<Self> {
+        Ok(Self::from_wkt(text, level)?)
+    }
+
+    /// 覆盖 Geohash 网格的指定层级区域，Geohash 不合法时返回 None
+    #[staticmethod]
+    #[pyo3(name = "from_geohash")]
+    pub fn from_geohash_py(hash: &str, level: usize) -> Option<Self> {
+        Self::from_geohash(hash, level)
+    }
+
+    /// 覆盖 XYZ 瓦片的指定层级区域，瓦片编号越界时返回 None
+    #[staticmethod]
+    #[pyo3(name = "from_tile")]
+    pub fn from_tile_py(zoom: u8, x: u32, y: u32, level: usize) -> Option<Self> {
+        Self::from_tile(zoom, x, y, level)
+    }
+
+    /// 与区域相交的全部 XYZ 瓦片 (x, y)
+    #[pyo3(name = "to_tiles")]
+    pub fn tiles_py(&self, zoom: u8) -> BTreeSet<(u32, u32)> {
+        self.to_tiles(zoom)
+    }
+
+    /// 编码为紧凑二进制格式（bytes），层级无效或编码未对齐时抛出 ValueError
+    #[pyo3(name = "to_bytes")]
+    pub fn bytes_py<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new(py, &self.to_bytes()?))
+    }
+
+    /// 从紧凑二进制格式解码，数据损坏时抛出 ValueError
+    #[staticmethod]
+    #[pyo3(name = "from_bytes")]
+    pub fn from_bytes_py(data: &[u8]) -> PyResult<Self> {
+        Ok(Self::from_bytes(data)?)
+    }
+
+    /// 将合并轮廓编码为 WKB MultiPolygon（bytes）
+    #[pyo3(name = "to_wkb")]
+    pub fn wkb_py<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.to_wkb())
+    }
+
+    /// 解码 WKB 并覆盖为指定层级的区域
+    #[staticmethod]
+    #[pyo3(name = "from_wkb")]
+    pub fn from_wkb_py(data: &[u8], level: usize) -> PyResult<Self> {
+        Ok(Self::from_wkb(data, level)?)
     }
 
     /// 添加单个编码
@@ -202,12 +540,27 @@ impl GeoSotRegion {
 
     /// 获取区域的字符串表示
     pub fn to_strings(&self) -> Vec<String> {
-        self.codes.iter().map(|&code| to_string(code, self.level)).collect()
+        self.codes.iter().map(|code| to_string(code, self.level)).collect()
+    }
+
+    /// 遍历区域内的网格
+    pub fn cells(&self) -> Vec<GeoSotCell> {
+        self.codes.iter().map(|code| GeoSotCell::new(code, self.level)).collect()
     }
 
     /// 获取区域的经纬度点集合
     pub fn to_coordinates(&self) -> Vec<(f64, f64)> {
-        self.codes.iter().map(|&code| decode_by_geomgrid(code)).collect()
+        self.codes.iter().map(decode_by_geomgrid).collect()
+    }
+
+    /// 转为 GeoJSON FeatureCollection
+    ///
+    /// # 参数
+    /// * `dissolve` - 为 false 时每个网格输出一个 Polygon Feature；
+    ///   为 true 时将区域合并为单个 (Multi)Polygon 轮廓
+    #[pyo3(name = "to_geojson_feature_collection", signature = (dissolve = false))]
+    fn geojson_feature_collection_py(&self, dissolve: bool) -> String {
+        self.to_geojson_feature_collection(dissolve)
     }
 
     /// 计算两个区域的交集
@@ -223,13 +576,8 @@ impl GeoSotRegion {
             panic!("Cannot compute intersection of regions with different levels");
         }
 
-        let intersection_codes: BTreeSet<u64> = self.codes
-            .intersection(&other.codes)
-            .cloned()
-            .collect();
-
         GeoSotRegion {
-            codes: intersection_codes,
+            codes: self.codes.intersection(&other.codes),
             level: self.level,
         }
     }
@@ -246,13 +594,8 @@ impl GeoSotRegion {
             panic!("Cannot compute union of regions with different levels");
         }
 
-        let union_codes: BTreeSet<u64> = self.codes
-            .union(&other.codes)
-            .cloned()
-            .collect();
-
         GeoSotRegion {
-            codes: union_codes,
+            codes: self.codes.union(&other.codes),
             level: self.level,
         }
     }
@@ -269,13 +612,8 @@ impl GeoSotRegion {
             panic!("Cannot compute difference of regions with different levels");
         }
 
-        let difference_codes: BTreeSet<u64> = self.codes
-            .difference(&other.codes)
-            .cloned()
-            .collect();
-
         GeoSotRegion {
-            codes: difference_codes,
+            codes: self.codes.difference(&other.codes),
             level: self.level,
         }
     }
@@ -292,13 +630,8 @@ impl GeoSotRegion {
             panic!("Cannot compute symmetric difference of regions with different levels");
         }
 
-        let sym_diff_codes: BTreeSet<u64> = self.codes
-            .symmetric_difference(&other.codes)
-            .cloned()
-            .collect();
-
         GeoSotRegion {
-            codes: sym_diff_codes,
+            codes: self.codes.symmetric_difference(&other.codes),
             level: self.level,
         }
     }
@@ -373,14 +706,181 @@ impl GeoSotRegion {
         }
         self.codes.is_disjoint(&other.codes)
     }
+
+    /// 膨胀 k 步
+    #[pyo3(name = "dilate", signature = (k, connectivity = Connectivity::Eight))]
+    fn dilate_py(&self, k: usize, connectivity: Connectivity) -> Self {
+        self.dilate(k, connectivity)
+    }
+
+    /// 腐蚀 k 步
+    #[pyo3(name = "erode", signature = (k, connectivity = Connectivity::Eight))]
+    fn erode_py(&self, k: usize, connectivity: Connectivity) -> Self {
+        self.erode(k, connectivity)
+    }
+
+    /// 开运算（先腐蚀后膨胀）
+    #[pyo3(name = "open", signature = (k, connectivity = Connectivity::Eight))]
+    fn open_py(&self, k: usize, connectivity: Connectivity) -> Self {
+        self.open(k, connectivity)
+    }
+
+    /// 闭运算（先膨胀后腐蚀）
+    #[pyo3(name = "close", signature = (k, connectivity = Connectivity::Eight))]
+    fn close_py(&self, k: usize, connectivity: Connectivity) -> Self {
+        self.close(k, connectivity)
+    }
+
+    /// 距离变换：`extent` 中每个网格到本区域的球面距离（米），层级不同时抛出 ValueError
+    #[pyo3(name = "distance_transform")]
+    fn distance_transform_py(&self, extent: &GeoSotRegion) -> PyResult<PyGeoSotGrid> {
+        self.check_same_level(extent)?;
+        Ok(self.distance_transform(extent).into())
+    }
+
+    /// 按米缓冲
+    #[pyo3(name = "buffer_m")]
+    fn buffer_m_py(&self, distance_m: f64) -> Self {
+        self.buffer_m(distance_m)
+    }
+
+    /// DE-9IM 交集矩阵，如 "212101212"；以下空间关系在层级不同时都抛出 ValueError
+    #[pyo3(name = "relate")]
+    fn relate_py(&self, other: &GeoSotRegion) -> PyResult<String> {
+        self.check_same_level(other)?;
+        Ok(self.relate(other).to_string())
+    }
+
+    /// 是否匹配 DE-9IM 模式
+    #[pyo3(name = "relate_pattern")]
+    fn relate_pattern_py(&self, other: &GeoSotRegion, pattern: &str) -> PyResult<bool> {
+        self.check_same_level(other)?;
+        Ok(self.relate_pattern(other, pattern))
+    }
+
+    /// 相接：没有公共网格，但边界共享边或角点
+    #[pyo3(name = "touches")]
+    fn touches_py(&self, other: &GeoSotRegion) -> PyResult<bool> {
+        self.check_same_level(other)?;
+        Ok(self.touches(other))
+    }
+
+    /// 重叠：有公共网格，且双方都有不属于对方的网格
+    #[pyo3(name = "overlaps")]
+    fn overlaps_py(&self, other: &GeoSotRegion) -> PyResult<bool> {
+        self.check_same_level(other)?;
+        Ok(self.overlaps(other))
+    }
+
+    /// 在另一个区域之内
+    #[pyo3(name = "within")]
+    fn within_py(&self, other: &GeoSotRegion) -> PyResult<bool> {
+        self.check_same_level(other)?;
+        Ok(self.within(other))
+    }
+
+    /// 包含另一个区域
+    #[pyo3(name = "contains_region")]
+    fn contains_region_py(&self, other: &GeoSotRegion) -> PyResult<bool> {
+        self.check_same_level(other)?;
+        Ok(self.contains_region(other))
+    }
+
+    /// 覆盖另一个区域
+    #[pyo3(name = "covers")]
+    fn covers_py(&self, other: &GeoSotRegion) -> PyResult<bool> {
+        self.check_same_level(other)?;
+        Ok(self.covers(other))
+    }
+
+    /// 被另一个区域覆盖
+    #[pyo3(name = "covered_by")]
+    fn covered_by_py(&self, other: &GeoSotRegion) -> PyResult<bool> {
+        self.check_same_level(other)?;
+        Ok(self.covered_by(other))
+    }
+
+    /// 穿过：本区域在对方之外的部分被对方分割成更多块
+    #[pyo3(name = "crosses")]
+    fn crosses_py(&self, other: &GeoSotRegion) -> PyResult<bool> {
+        self.check_same_level(other)?;
+        Ok(self.crosses(other))
+    }
+
+    /// 包围盒 (min_lng, min_lat, max_lng, max_lat)，跨越 180° 经线时 min_lng > max_lng
+    #[pyo3(name = "bbox")]
+    fn bbox_py(&self) -> Option<(f64, f64, f64, f64)> {
+        self.bbox()
+    }
+
+    /// 面积加权质心 (lng, lat)
+    #[pyo3(name = "centroid")]
+    fn centroid_py(&self) -> Option<(f64, f64)> {
+        self.centroid()
+    }
+
+    /// 范围西南角的网格
+    #[pyo3(name = "min_cell")]
+    fn min_cell_py(&self) -> Option<GeoSotCell> {
+        self.min_cell()
+    }
+
+    /// 范围东北角的网格
+    #[pyo3(name = "max_cell")]
+    fn max_cell_py(&self) -> Option<GeoSotCell> {
+        self.max_cell()
+    }
+
+    /// 包含全部网格的最小公共祖先网格，跨越象限时为第 0 级全球网格，空区域返回 None
+    #[pyo3(name = "covering_cell")]
+    fn covering_cell_py(&self) -> Option<GeoSotCell> {
+        self.covering_cell()
+    }
+}
+
+impl GeoSotRegion {
+    /// Python 包装中组合两个区域前检查层级，不同时抛出 ValueError，而不是在 Rust 中 panic
+    fn check_same_level(&self, other: &GeoSotRegion) -> PyResult<()> {
+        if self.level != other.level {
+            return Err(PyValueError::new_err(format!(
+                "regions have different levels: {} and {}",
+                self.level, other.level
+            )));
+        }
+        Ok(())
+    }
+
+    /// 从多边形创建 GeoSOT 编码集合（简化版本，使用包围盒）
+    pub fn from_polygon(points: &[(f64, f64)], level: usize) -> Self {
+        if points.is_empty() {
+            return Self::new(level);
+        }
+
+        // 计算包围盒
+        let min_lng = points.iter().map(|(lng, _)| *lng).fold(f64::INFINITY, f64::min);
+        let max_lng = points.iter().map(|(lng, _)| *lng).fold(f64::NEG_INFINITY, f64::max);
+        let min_lat = points.iter().map(|(_, lat)| *lat).fold(f64::INFINITY, f64::min);
+        let max_lat = points.iter().map(|(_, lat)| *lat).fold(f64::NEG_INFINITY, f64::max);
+
+        // 使用包围盒创建区域（实际应用中可以加入点在多边形内的判断）
+        Self::from_rectangle(min_lng, min_lat, max_lng, max_lat, level)
+    }
+
+    /// 网格的 8 个相邻网格中属于本区域的个数
+    pub(crate) fn neighbor_count(&self, cell: &GeoSotCell) -> usize {
+        cell.neighbors().iter().filter(|n| self.contains(n.code)).count()
+    }
 }
 
+/// 空间分析指标
 #[pymodule]
-pub fn spatial_analysis(m: &Bound<'_, PyModule>) -> PyResult<()> {
+pub mod spatial_analysis {
+    use pyo3::prelude::*;
+    use super::GeoSotRegion;
+
     /// 计算两个区域的 Jaccard 相似度
-    #[pyfn(m)]
-    #[pyo3(name = "jaccard_similarity")]
-    fn jaccard_similarity_py(region1: &GeoSotRegion, region2: &GeoSotRegion) -> f64 {
+    #[pyfunction]
+    pub fn jaccard_similarity(region1: &GeoSotRegion, region2: &GeoSotRegion) -> f64 {
         if region1.level != region2.level {
             return 0.0;
         }
@@ -396,9 +896,8 @@ pub fn spatial_analysis(m: &Bound<'_, PyModule>) -> PyResult<()> {
     }
 
     /// 计算两个区域的重叠率
-    #[pyfn(m)]
-    #[pyo3(name = "overlap_ratio")]
-    fn overlap_ratio_py(region1: &GeoSotRegion, region2: &GeoSotRegion) -> f64 {
+    #[pyfunction]
+    pub fn overlap_ratio(region1: &GeoSotRegion, region2: &GeoSotRegion) -> f64 {
         if region1.level != region2.level || region1.is_empty() {
             return 0.0;
         }
@@ -408,24 +907,18 @@ pub fn spatial_analysis(m: &Bound<'_, PyModule>) -> PyResult<()> {
     }
 
     /// 计算区域的紧密度（连通性度量）
-    #[pyfn(m)]
-    #[pyo3(name = "compactness")]
-    fn compactness_py(region: &GeoSotRegion) -> f64 {
+    #[pyfunction]
+    pub fn compactness(region: &GeoSotRegion) -> f64 {
         if region.size() <= 1 {
             return 1.0;
         }
 
-        let codes: Vec<u64> = region.codes.iter().cloned().collect();
-        let mut adjacent_pairs = 0;
-        let total_pairs = codes.len() * (codes.len() - 1) / 2;
+        let total_pairs = region.size() * (region.size() - 1) / 2;
 
-        for i in 0..codes.len() {
-            for j in (i + 1)..codes.len() {
-                if are_adjacent_codes(codes[i], codes[j], region.level) {
-                    adjacent_pairs += 1;
-                }
-            }
-        }
+        #[cfg(feature = "parallel")]
+        let adjacent_pairs = crate::parallel::count_adjacent_pairs(region);
+        #[cfg(not(feature = "parallel"))]
+        let adjacent_pairs = region.cells().iter().map(|cell| region.neighbor_count(cell)).sum::<usize>() / 2;
 
         if total_pairs == 0 {
             1.0
@@ -434,17 +927,76 @@ pub fn spatial_analysis(m: &Bound<'_, PyModule>) -> PyResult<()> {
         }
     }
 
-    Ok(())
+    /// 计算两个区域的 Dice（Sørensen）系数 2|A∩B| / (|A| + |B|)
+    #[pyfunction]
+    pub fn dice_coefficient(region1: &GeoSotRegion, region2: &GeoSotRegion) -> f64 {
+        if region1.level != region2.level {
+            return 0.0;
+        }
+
+        let total = region1.size() + region2.size();
+        if total == 0 {
+            return 1.0; // 两个空集的系数为1
+        }
+
+        2.0 * region1.intersection(region2).size() as f64 / total as f64
+    }
+
+    /// 计算 Tversky 指数 |A∩B| / (|A∩B| + α|A-B| + β|B-A|)
+    ///
+    /// α = β = 0.5 时等于 Dice 系数，α = β = 1 时等于 Jaccard 相似度。
+    #[pyfunction]
+    #[pyo3(signature = (region1, region2, alpha = 0.5, beta = 0.5))]
+    pub fn tversky_index(region1: &GeoSotRegion, region2: &GeoSotRegion, alpha: f64, beta: f64) -> f64 {
+        if region1.level != region2.level {
+            return 0.0;
+        }
+        if region1.is_empty() && region2.is_empty() {
+            return 1.0;
+        }
+
+        let common = region1.intersection(region2).size() as f64;
+        let only1 = (region1.size() as f64 - common) * alpha;
+        let only2 = (region2.size() as f64 - common) * beta;
+        let denominator = common + only1 + only2;
+        if denominator == 0.0 {
+            return 0.0;
+        }
+
+        common / denominator
+    }
+
+    /// 计算两个区域之间的最短球面距离（米）
+    #[pyfunction]
+    pub fn min_distance(region1: &GeoSotRegion, region2: &GeoSotRegion) -> f64 {
+        region1.min_distance_m(region2)
+    }
+
+    /// 计算两个区域的 Hausdorff 距离（米）
+    #[pyfunction]
+    pub fn hausdorff_distance(region1: &GeoSotRegion, region2: &GeoSotRegion) -> f64 {
+        region1.hausdorff_distance_m(region2)
+    }
+
+    /// 计算两个区域质心之间的球面距离（米）
+    #[pyfunction]
+    pub fn centroid_distance(region1: &GeoSotRegion, region2: &GeoSotRegion) -> f64 {
+        region1.centroid_distance_m(region2)
+    }
 }
 
 
-/// 简化的相邻编码判断函数
-/// 实际实现中应该根据 GeoSOT 编码的空间结构来判断
-fn are_adjacent_codes(code1: u64, code2: u64, _level: usize) -> bool {
-    // 这里是一个简化的实现，实际应该根据莫顿码的性质来判断相邻性
-    let diff = if code1 > code2 { code1 - code2 } else { code2 - code1 };
-    // 相邻的莫顿码通常差值较小
-    diff <= 4 // 简化判断
+/// 生成矩形覆盖中某一行（固定纬度）的网格编码
+pub(crate) fn row_codes(lat: f64, min_lng: f64, max_lng: f64, step: f64, level: usize) -> impl Iterator<Item = u64> {
+    let mut lng = min_lng;
+    std::iter::from_fn(move || {
+        if lng > max_lng {
+            return None;
+        }
+        let code = get_code(lng, lat, level);
+        lng += step;
+        Some(code)
+    })
 }
 
 #[cfg(test)]
@@ -455,7 +1007,22 @@ mod tests {
     fn test_geosot_cell_creation() {
         let cell = GeoSotCell::from_coords(116.397, 39.916, 20);
         assert_eq!(cell.level, 20);
-        println!("Cell: {}", cell.to_string());
+        println!("Cell: {}", cell);
+    }
+
+    #[test]
+    fn test_grid_string_round_trip() {
+        for level in 1..=32 {
+            let cell = GeoSotCell::from_coords(-116.397, 39.916, level);
+            assert_eq!(GeoSotCell::from_grid_string(&cell.to_string()), Some(cell));
+        }
+        // 分隔符位置错误、多余或缺失
+        for grid in ["G-0-1.2", "G0013103220-1", "G001310322-0", "G001310322012", "G0-01310322"] {
+            assert_eq!(GeoSotCell::from_grid_string(grid), None, "{grid}");
+        }
+        // 经度超过 180 度、分超过 60
+        assert_eq!(GeoSotCell::from_grid_string("G3333"), None);
+        assert_eq!(GeoSotCell::from_grid_string("G001310322-3333"), None);
     }
 
     #[test]
@@ -468,6 +1035,187 @@ mod tests {
         assert_eq!(children.len(), 4);
         assert!(children.iter().any(|c| c.is_descendant_of(&parent)));
     }
+
+    #[test]
+    fn test_hierarchy_uses_aligned_codes() {
+        let cell = GeoSotCell::from_coords(116.397, 39.916, 20);
+        let parent = cell.parent().unwrap();
+        assert_eq!(parent, GeoSotCell::from_coords(116.397, 39.916, 19));
+        assert!(parent.children().contains(&cell));
+        assert!(parent.children().iter().all(|c| c.is_descendant_of(&parent)));
+        assert!(GeoSotCell::from_coords(116.397, 39.916, 12).is_ancestor_of(&cell));
+        assert!(!GeoSotCell::from_coords(-116.397, 39.916, 12).is_ancestor_of(&cell));
+        assert_eq!(GeoSotCell::from_coords(116.5, 39.5, 12).children().len(), 4);
+
+        // 56~60 分的 12 级截断网格只有 56 分一列子网格
+        let truncated = GeoSotCell::from_coords(116.0 + 58.0 / 60.0, 39.5, 12);
+        assert_eq!(truncated.children().len(), 2);
+    }
+
+    #[test]
+    fn test_compactness_counts_neighbours() {
+        let center = GeoSotCell::from_coords(116.397, 39.916, 15);
+        let block = GeoSotRegion::from_codes(center.k_ring(1).iter().map(|c| c.code).collect(), 15);
+        // 3x3 网格块：横向 6 对、纵向 6 对、对角 8 对，共 36 对
+        assert_eq!(spatial_analysis::compactness(&block), 20.0 / 36.0);
+
+        let mut scattered = GeoSotRegion::new(15);
+        scattered.add_point(116.397, 39.916);
+        scattered.add_point(121.47, 31.23);
+        assert_eq!(spatial_analysis::compactness(&scattered), 0.0);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let cell = GeoSotCell::from_coords(116.397, 39.916, 15);
+        let neighbors = cell.neighbors();
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.contains(&GeoSotCell::from_coords(116.397 + 1.0 / 60.0, 39.916, 15)));
+        assert!(neighbors.contains(&GeoSotCell::from_coords(116.397 - 1.0 / 60.0, 39.916 - 1.0 / 60.0, 15)));
+        assert!(neighbors.iter().all(|n| n.is_adjacent_to(&cell)));
+        assert!(!cell.is_adjacent_to(&GeoSotCell::from_coords(116.397 + 2.0 / 60.0, 39.916, 15)));
+        assert!(!cell.is_adjacent_to(&cell));
+
+        // 跨越本初子午线与赤道
+        let origin = GeoSotCell::from_coords(0.5, 0.5, 9);
+        let neighbors = origin.neighbors();
+        assert!(neighbors.contains(&GeoSotCell::from_coords(-0.5, -0.5, 9)));
+        assert!(neighbors.contains(&GeoSotCell::from_coords(1.5, 0.5, 9)));
+
+        // 截断网格 48~56 分的东侧是 56~60 分
+        let cell = GeoSotCell::from_coords(116.0 + 50.0 / 60.0, 39.5, 12);
+        assert_eq!(cell.neighbor(1, 0), Some(GeoSotCell::from_coords(116.0 + 58.0 / 60.0, 39.5, 12)));
+
+        // 经度绕回，极点处没有更北的网格
+        let east = GeoSotCell::from_coords(179.5, 10.5, 9);
+        assert_eq!(east.neighbor(1, 0), Some(GeoSotCell::from_coords(-179.5, 10.5, 9)));
+        assert_eq!(GeoSotCell::from_coords(-179.5, 10.5, 9).neighbor(-1, 0), Some(east));
+        let polar = GeoSotCell::from_coords(10.5, 89.5, 9);
+        assert_eq!(polar.neighbor(0, 1), None);
+        assert_eq!(polar.neighbors().len(), 5);
+    }
+
+    #[test]
+    fn test_k_ring() {
+        let cell = GeoSotCell::from_coords(116.397, 39.916, 15);
+        assert_eq!(cell.k_ring(0), vec![cell]);
+        let mut ring1 = cell.k_ring(1);
+        let mut expected = cell.neighbors();
+        expected.push(cell);
+        ring1.sort();
+        expected.sort();
+        assert_eq!(ring1, expected);
+        assert_eq!(cell.k_ring(3).len(), 49);
+
+        // 经度绕回后去重，极点处截断
+        assert_eq!(GeoSotCell::from_coords(0.5, 0.5, 1).k_ring(5).len(), 2 * 2);
+        assert_eq!(GeoSotCell::from_coords(10.5, 88.5, 9).k_ring(2).len(), 5 * 4);
+    }
+
+    #[test]
+    fn test_cell_distance() {
+        let a = GeoSotCell::from_coords(116.5, 39.5, 9);
+        let b = GeoSotCell::from_coords(118.5, 39.5, 9);
+        let d = a.distance_m(&b);
+        assert!((d - crate::utils::haversine_distance(116.5, 39.5, 118.5, 39.5)).abs() < 1e-6);
+        assert_eq!(a.distance_m(&a), 0.0);
+        // 椭球面距离与球面距离相差不超过 0.5%
+        let v = a.vincenty_distance_m(&b).unwrap();
+        assert!((v - d).abs() / d < 0.005);
+        assert_eq!(a.vincenty_distance_m(&a), Some(0.0));
+
+        let north = GeoSotCell::from_coords(116.5, 41.5, 9);
+        assert!(a.bearing_to(&north).abs() < 1e-9);
+        assert!((a.bearing_to(&b) - 90.0).abs() < 1.0);
+        assert!((north.bearing_to(&a) - 180.0).abs() < 1e-9);
+        let west = GeoSotCell::from_coords(-179.5, 0.5, 9);
+        let east = GeoSotCell::from_coords(179.5, 0.5, 9);
+        assert!((east.bearing_to(&west) - 90.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_vincenty_reference() {
+        // Flinders Peak 到 Buninyong（Vincenty 1975 原文算例）
+        let dms = |d: f64, m: f64, s: f64| d + m / 60.0 + s / 3600.0;
+        let d = crate::utils::vincenty_distance(
+            dms(144.0, 25.0, 29.5244),
+            -dms(37.0, 57.0, 3.7203),
+            dms(143.0, 55.0, 35.3839),
+            -dms(37.0, 39.0, 10.1561),
+        )
+        .unwrap();
+        assert!((d - 54_972.271).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_grid_distance() {
+        // 与 neighbor 逐步移动的步数一致，包含截断网格（第 12 级为 8 分网格，56~60 分为截断网格）
+        let start = GeoSotCell::from_coords(-0.3, -0.3, 12);
+        let mut cell = start;
+        for step in 1..=20u64 {
+            cell = cell.neighbor(1, 0).unwrap();
+            assert_eq!(start.grid_distance(&cell), Some(step));
+        }
+        for step in 1..=20u64 {
+            cell = cell.neighbor(0, 1).unwrap();
+            assert_eq!(start.grid_distance(&cell), Some(20 + step));
+            assert_eq!(cell.grid_distance(&start), Some(20 + step));
+        }
+
+        // 秒级与秒以下层级
+        for (level, steps) in [(18, 200u64), (24, 3000)] {
+            let start = GeoSotCell::from_coords(116.016, 39.999, level);
+            let mut cell = start;
+            for _ in 0..steps {
+                cell = cell.neighbor(1, 1).unwrap();
+            }
+            assert_eq!(start.grid_distance(&cell), Some(2 * steps));
+        }
+
+        // 跨越 180° 经线取较短方向
+        let west = GeoSotCell::from_coords(179.5, 0.5, 9);
+        let east = GeoSotCell::from_coords(-178.5, 0.5, 9);
+        assert_eq!(west.grid_distance(&east), Some(2));
+        assert_eq!(west.neighbor(1, 0).unwrap().grid_distance(&east), Some(1));
+        assert_eq!(GeoSotCell::from_coords(0.5, 0.5, 9).grid_distance(&GeoSotCell::from_coords(-179.5, 0.5, 9)), Some(180));
+
+        assert_eq!(start.grid_distance(&start), Some(0));
+        assert_eq!(start.grid_distance(&GeoSotCell::from_coords(-0.3, -0.3, 13)), None);
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        let a = GeoSotCell::from_coords(116.397, 39.916, 20);
+        let b = GeoSotCell::from_coords(116.398, 39.917, 20);
+        let ancestor = a.common_ancestor(&b).unwrap();
+        assert!(ancestor.is_ancestor_of(&a) && ancestor.is_ancestor_of(&b));
+        assert!(ancestor.children().iter().all(|c| !(c.is_ancestor_of(&a) && c.is_ancestor_of(&b))));
+        assert_eq!(b.common_ancestor(&a), Some(ancestor));
+        assert_eq!(a.hierarchy_distance(&b), Some(20 - ancestor.level));
+        assert_eq!(a.hierarchy_distance(&b), b.hierarchy_distance(&a));
+
+        // 自身与祖先
+        assert_eq!(a.common_ancestor(&a), Some(a));
+        assert_eq!(a.hierarchy_distance(&a), Some(0));
+        let parent = a.parent().unwrap().parent().unwrap();
+        assert_eq!(a.common_ancestor(&parent), Some(parent));
+        assert_eq!(parent.common_ancestor(&a), Some(parent));
+        assert_eq!(a.hierarchy_distance(&parent), Some(2));
+        assert_eq!(parent.hierarchy_distance(&a), Some(0));
+
+        // 不同层级
+        let coarse = GeoSotCell::from_coords(116.9, 39.1, 12);
+        let shared = a.common_ancestor(&coarse).unwrap();
+        assert!(shared.level <= 12 && shared.is_ancestor_of(&a));
+        assert!(shared == coarse || shared.is_ancestor_of(&coarse));
+
+        // 跨越本初子午线、赤道没有公共祖先
+        let east = GeoSotCell::from_coords(0.5, 0.5, 9);
+        let west = GeoSotCell::from_coords(-0.5, 0.5, 9);
+        assert_eq!(east.common_ancestor(&west), None);
+        assert_eq!(east.hierarchy_distance(&west), None);
+        assert_eq!(east.common_ancestor(&GeoSotCell::from_coords(0.5, -0.5, 9)), None);
+    }
     #[test]
     fn test_region_creation() {
         let mut region = GeoSotRegion::new(20);
@@ -528,4 +1276,23 @@ mod tests {
         let similarity = spatial_analysis::jaccard_similarity(&region1, &region2);
         assert!((similarity - 0.5).abs() < 1e-10); // 交集2个，并集4个，相似度0.5
     }
+
+    #[test]
+    fn test_dice_and_tversky() {
+        let region1 = GeoSotRegion::from_codes(vec![1, 2, 3], 20);
+        let region2 = GeoSotRegion::from_codes(vec![2, 3, 4], 20);
+        let empty = GeoSotRegion::new(20);
+
+        assert!((spatial_analysis::dice_coefficient(&region1, &region2) - 2.0 / 3.0).abs() < 1e-10);
+        assert_eq!(spatial_analysis::dice_coefficient(&empty, &empty), 1.0);
+        assert_eq!(spatial_analysis::dice_coefficient(&region1, &empty), 0.0);
+
+        let dice = spatial_analysis::tversky_index(&region1, &region2, 0.5, 0.5);
+        assert!((dice - 2.0 / 3.0).abs() < 1e-10);
+        let jaccard = spatial_analysis::tversky_index(&region1, &region2, 1.0, 1.0);
+        assert!((jaccard - spatial_analysis::jaccard_similarity(&region1, &region2)).abs() < 1e-10);
+        assert_eq!(spatial_analysis::tversky_index(&empty, &empty, 0.5, 0.5), 1.0);
+        assert_eq!(spatial_analysis::tversky_index(&region1, &empty, 0.0, 0.0), 0.0);
+        assert_eq!(spatial_analysis::tversky_index(&region1, &region2, 0.0, 0.0), 1.0);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,451 @@
+use pyo3::prelude::*;
+use roaring::RoaringTreemap;
+use std::collections::{btree_set, BTreeSet};
+use std::fmt;
+
+/// 区域编码集合的存储方式
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegionStorage {
+    /// 有序集合 `BTreeSet<u64>`，适合稀疏的点集
+    #[default]
+    BTree,
+    /// 64 位压缩位图（Roaring treemap），适合连片的稠密覆盖
+    Bitmap,
+}
+
+/// 压缩位图形式的编码集合
+///
+/// 同一精度等级的编码低位全为 0，直接放入位图会让每个容器只有一个值。
+/// 因此位图中保存的是右移 `shift` 位后的编码，使连片网格的莫顿码连续存放。
+/// 插入低位非 0 的编码时会自动减小 `shift`，任意 `u64` 都能无损保存。
+#[derive(Clone, Default)]
+pub struct CodeBitmap {
+    bits: RoaringTreemap,
+    shift: u32,
+}
+
+impl CodeBitmap {
+    /// 创建空位图，`level` 用于确定初始的移位量
+    pub fn new(level: usize) -> Self {
+        let shift = if (1..=32).contains(&level) { (32 - level as u32) * 2 } else { 0 };
+        Self { bits: RoaringTreemap::new(), shift }
+    }
+
+    /// 当前的移位量
+    pub fn shift(&self) -> u32 {
+        self.shift
+    }
+
+    /// 底层位图
+    pub fn bits(&self) -> &RoaringTreemap {
+        &self.bits
+    }
+
+    fn fits(&self, code: u64) -> bool {
+        self.shift == 0 || code.trailing_zeros() >= self.shift
+    }
+
+    /// 将移位量降低到 `shift`，重新编排已有的值
+    fn rescale(&mut self, shift: u32) {
+        if shift >= self.shift {
+            return;
+        }
+        let delta = self.shift - shift;
+        self.bits = RoaringTreemap::from_sorted_iter(self.bits.iter().map(|v| v << delta)).unwrap();
+        self.shift = shift;
+    }
+
+    /// 返回与 `other` 移位量一致的两个位图（必要时复制一份）
+    fn aligned<'a>(&'a self, other: &'a Self) -> (std::borrow::Cow<'a, Self>, std::borrow::Cow<'a, Self>) {
+        use std::borrow::Cow;
+        match self.shift.cmp(&other.shift) {
+            std::cmp::Ordering::Equal => (Cow::Borrowed(self), Cow::Borrowed(other)),
+            std::cmp::Ordering::Greater => {
+                let mut a = self.clone();
+                a.rescale(other.shift);
+                (Cow::Owned(a), Cow::Borrowed(other))
+            }
+            std::cmp::Ordering::Less => {
+                let mut b = other.clone();
+                b.rescale(self.shift);
+                (Cow::Borrowed(self), Cow::Owned(b))
+            }
+        }
+    }
+
+    pub fn insert(&mut self, code: u64) -> bool {
+        if !self.fits(code) {
+            self.rescale(code.trailing_zeros());
+        }
+        self.bits.insert(code >> self.shift)
+    }
+
+    pub fn remove(&mut self, code: u64) -> bool {
+        self.fits(code) && self.bits.remove(code >> self.shift)
+    }
+
+    pub fn contains(&self, code: u64) -> bool {
+        self.fits(code) && self.bits.contains(code >> self.shift)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    fn binary_op(&self, other: &Self, op: impl Fn(&RoaringTreemap, &RoaringTreemap) -> RoaringTreemap) -> Self {
+        let (a, b) = self.aligned(other);
+        Self { bits: op(&a.bits, &b.bits), shift: a.shift }
+    }
+
+    fn predicate(&self, other: &Self, op: impl Fn(&RoaringTreemap, &RoaringTreemap) -> bool) -> bool {
+        let (a, b) = self.aligned(other);
+        op(&a.bits, &b.bits)
+    }
+}
+
+/// 区域的编码集合，可选 `BTreeSet` 或压缩位图两种存储
+///
+/// 两种存储的迭代顺序（升序）与集合运算语义完全一致，
+/// 不同存储之间的运算结果沿用左操作数的存储方式。
+#[derive(Clone)]
+pub enum CodeSet {
+    BTree(BTreeSet<u64>),
+    Bitmap(CodeBitmap),
+}
+
+impl Default for CodeSet {
+    fn default() -> Self {
+        CodeSet::BTree(BTreeSet::new())
+    }
+}
+
+impl CodeSet {
+    /// 按指定存储方式创建空集合
+    pub fn new(storage: RegionStorage, level: usize) -> Self {
+        match storage {
+            RegionStorage::BTree => CodeSet::BTree(BTreeSet::new()),
+            RegionStorage::Bitmap => CodeSet::Bitmap(CodeBitmap::new(level)),
+        }
+    }
+
+    /// 当前的存储方式
+    pub fn storage(&self) -> RegionStorage {
+        match self {
+            CodeSet::BTree(_) => RegionStorage::BTree,
+            CodeSet::Bitmap(_) => RegionStorage::Bitmap,
+        }
+    }
+
+    /// 转换为指定存储方式，内容不变
+    pub fn to_storage(&self, storage: RegionStorage, level: usize) -> Self {
+        if self.storage() == storage {
+            return self.clone();
+        }
+        let mut out = Self::new(storage, level);
+        out.extend(self.iter());
+        out
+    }
+
+    pub fn insert(&mut self, code: u64) -> bool {
+        match self {
+            CodeSet::BTree(set) => set.insert(code),
+            CodeSet::Bitmap(bitmap) => bitmap.insert(code),
+        }
+    }
+
+    pub fn remove(&mut self, code: &u64) -> bool {
+        match self {
+            CodeSet::BTree(set) => set.remove(code),
+            CodeSet::Bitmap(bitmap) => bitmap.remove(*code),
+        }
+    }
+
+    pub fn contains(&self, code: &u64) -> bool {
+        match self {
+            CodeSet::BTree(set) => set.contains(code),
+            CodeSet::Bitmap(bitmap) => bitmap.contains(*code),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            CodeSet::BTree(set) => set.len(),
+            CodeSet::Bitmap(bitmap) => bitmap.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            CodeSet::BTree(set) => set.is_empty(),
+            CodeSet::Bitmap(bitmap) => bitmap.is_empty(),
+        }
+    }
+
+    /// 按升序遍历编码
+    pub fn iter(&self) -> Iter<'_> {
+        match self {
+            CodeSet::BTree(set) => Iter::BTree(set.iter()),
+            CodeSet::Bitmap(bitmap) => Iter::Bitmap(Box::new(bitmap.bits.iter()), bitmap.shift),
+        }
+    }
+
+    /// 最小编码
+    pub fn first(&self) -> Option<u64> {
+        match self {
+            CodeSet::BTree(set) => set.first().copied(),
+            CodeSet::Bitmap(bitmap) => bitmap.bits.min().map(|v| v << bitmap.shift),
+        }
+    }
+
+    /// 最大编码
+    pub fn last(&self) -> Option<u64> {
+        match self {
+            CodeSet::BTree(set) => set.last().copied(),
+            CodeSet::Bitmap(bitmap) => bitmap.bits.max().map(|v| v << bitmap.shift),
+        }
+    }
+
+    /// 转为 `BTreeSet<u64>`
+    pub fn to_btree_set(&self) -> BTreeSet<u64> {
+        match self {
+            CodeSet::BTree(set) => set.clone(),
+            CodeSet::Bitmap(_) => self.iter().collect(),
+        }
+    }
+
+    /// 以左操作数的存储方式取得右操作数
+    fn coerce<'a>(&self, other: &'a CodeSet) -> std::borrow::Cow<'a, CodeSet> {
+        if self.storage() == other.storage() {
+            std::borrow::Cow::Borrowed(other)
+        } else {
+            let level = match self {
+                CodeSet::Bitmap(bitmap) => 32 - (bitmap.shift / 2) as usize,
+                CodeSet::BTree(_) => 32,
+            };
+            std::borrow::Cow::Owned(other.to_storage(self.storage(), level))
+        }
+    }
+
+    pub fn intersection(&self, other: &CodeSet) -> CodeSet {
+        match (self, &*self.coerce(other)) {
+            (CodeSet::BTree(a), CodeSet::BTree(b)) => {
+                #[cfg(feature = "parallel")]
+                if crate::parallel::worth_parallel(a, b) {
+                    return CodeSet::BTree(crate::parallel::set_op(a, b, crate::parallel::SetOp::Intersection));
+                }
+                CodeSet::BTree(a.intersection(b).cloned().collect())
+            }
+            (CodeSet::Bitmap(a), CodeSet::Bitmap(b)) => CodeSet::Bitmap(a.binary_op(b, |x, y| x & y)),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn union(&self, other: &CodeSet) -> CodeSet {
+        match (self, &*self.coerce(other)) {
+            (CodeSet::BTree(a), CodeSet::BTree(b)) => {
+                #[cfg(feature = "parallel")]
+                if crate::parallel::worth_parallel(a, b) {
+                    return CodeSet::BTree(crate::parallel::set_op(a, b, crate::parallel::SetOp::Union));
+                }
+                CodeSet::BTree(a.union(b).cloned().collect())
+            }
+            (CodeSet::Bitmap(a), CodeSet::Bitmap(b)) => CodeSet::Bitmap(a.binary_op(b, |x, y| x | y)),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn difference(&self, other: &CodeSet) -> CodeSet {
+        match (self, &*self.coerce(other)) {
+            (CodeSet::BTree(a), CodeSet::BTree(b)) => {
+                #[cfg(feature = "parallel")]
+                if crate::parallel::worth_parallel(a, b) {
+                    return CodeSet::BTree(crate::parallel::set_op(a, b, crate::parallel::SetOp::Difference));
+                }
+                CodeSet::BTree(a.difference(b).cloned().collect())
+            }
+            (CodeSet::Bitmap(a), CodeSet::Bitmap(b)) => CodeSet::Bitmap(a.binary_op(b, |x, y| x - y)),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn symmetric_difference(&self, other: &CodeSet) -> CodeSet {
+        match (self, &*self.coerce(other)) {
+            (CodeSet::BTree(a), CodeSet::BTree(b)) => {
+                #[cfg(feature = "parallel")]
+                if crate::parallel::worth_parallel(a, b) {
+                    return CodeSet::BTree(crate::parallel::set_op(a, b, crate::parallel::SetOp::SymmetricDifference));
+                }
+                CodeSet::BTree(a.symmetric_difference(b).cloned().collect())
+            }
+            (CodeSet::Bitmap(a), CodeSet::Bitmap(b)) => CodeSet::Bitmap(a.binary_op(b, |x, y| x ^ y)),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn is_subset(&self, other: &CodeSet) -> bool {
+        match (self, &*self.coerce(other)) {
+            (CodeSet::BTree(a), CodeSet::BTree(b)) => a.is_subset(b),
+            (CodeSet::Bitmap(a), CodeSet::Bitmap(b)) => a.predicate(b, |x, y| x.is_subset(y)),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn is_superset(&self, other: &CodeSet) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &CodeSet) -> bool {
+        match (self, &*self.coerce(other)) {
+            (CodeSet::BTree(a), CodeSet::BTree(b)) => a.is_disjoint(b),
+            (CodeSet::Bitmap(a), CodeSet::Bitmap(b)) => a.predicate(b, |x, y| x.is_disjoint(y)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// 编码集合的升序迭代器
+pub enum Iter<'a> {
+    BTree(btree_set::Iter<'a, u64>),
+    Bitmap(Box<roaring::treemap::Iter<'a>>, u32),
+}
+
+impl Iterator for Iter<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        match self {
+            Iter::BTree(iter) => iter.next().copied(),
+            Iter::Bitmap(iter, shift) => iter.next().map(|v| v << *shift),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::BTree(iter) => iter.size_hint(),
+            Iter::Bitmap(iter, _) => iter.size_hint(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<u64> {
+        match self {
+            Iter::BTree(iter) => iter.next_back().copied(),
+            Iter::Bitmap(iter, shift) => iter.next_back().map(|v| v << *shift),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a CodeSet {
+    type Item = u64;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<u64> for CodeSet {
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Self {
+        CodeSet::BTree(iter.into_iter().collect())
+    }
+}
+
+impl Extend<u64> for CodeSet {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) {
+        match self {
+            CodeSet::BTree(set) => set.extend(iter),
+            CodeSet::Bitmap(bitmap) => {
+                for code in iter {
+                    bitmap.insert(code);
+                }
+            }
+        }
+    }
+}
+
+impl From<BTreeSet<u64>> for CodeSet {
+    fn from(set: BTreeSet<u64>) -> Self {
+        CodeSet::BTree(set)
+    }
+}
+
+impl PartialEq for CodeSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for CodeSet {}
+
+impl fmt::Debug for CodeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_code;
+
+    fn sample(storage: RegionStorage, codes: &[u64]) -> CodeSet {
+        let mut set = CodeSet::new(storage, 20);
+        set.extend(codes.iter().copied());
+        set
+    }
+
+    #[test]
+    fn test_bitmap_matches_btree() {
+        let a = [1u64, 2, 3, 4, 1 << 40, 5 << 24];
+        let b = [3u64, 4, 5, 6, 5 << 24];
+        for (sa, sb) in [
+            (RegionStorage::Bitmap, RegionStorage::Bitmap),
+            (RegionStorage::Bitmap, RegionStorage::BTree),
+            (RegionStorage::BTree, RegionStorage::Bitmap),
+        ] {
+            let (x, y) = (sample(sa, &a), sample(sb, &b));
+            let (bx, by) = (sample(RegionStorage::BTree, &a), sample(RegionStorage::BTree, &b));
+            assert_eq!(x.intersection(&y), bx.intersection(&by));
+            assert_eq!(x.union(&y), bx.union(&by));
+            assert_eq!(x.difference(&y), bx.difference(&by));
+            assert_eq!(x.symmetric_difference(&y), bx.symmetric_difference(&by));
+            assert_eq!(x.intersection(&y).storage(), sa);
+            assert!(!x.is_disjoint(&y));
+        }
+    }
+
+    #[test]
+    fn test_bitmap_rescale() {
+        let level = 20;
+        let mut set = CodeSet::new(RegionStorage::Bitmap, level);
+        let aligned = get_code(116.0, 39.0, level);
+        set.insert(aligned);
+        set.insert(7);
+        assert!(set.contains(&aligned));
+        assert!(set.contains(&7));
+        assert!(!set.contains(&6));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![7, aligned]);
+    }
+
+    #[test]
+    fn test_bitmap_dense_storage() {
+        let level = 20;
+        let mut set = CodeSet::new(RegionStorage::Bitmap, level);
+        let shift = (32 - level) * 2;
+        set.extend((0..10_000u64).map(|i| (1000 + i) << shift));
+        match &set {
+            CodeSet::Bitmap(bitmap) => {
+                assert_eq!(bitmap.shift(), shift as u32);
+                assert!(bitmap.bits().serialized_size() < 10_000 * 8 / 4);
+            }
+            CodeSet::BTree(_) => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! GeoSOT 网格与 Web Mercator XYZ 瓦片的互相转换
+//!
+//! 瓦片在经度方向等分，在纬度方向按墨卡托投影划分：纬度越高，
+//! 同一缩放级别的瓦片在纬度上越窄。GeoSOT 网格按经纬度等分，
+//! 因此两者的对应关系与纬度有关，见 [`zoom_for_level`]。
+
+use pyo3::prelude::*;
+use std::collections::BTreeSet;
+use std::f64::consts::PI;
+
+use crate::geometry::Geometry;
+use crate::spatial::{GeoSotCell, GeoSotRegion};
+use crate::utils::get_cell_size_in_degree;
+
+/// Web Mercator 可表示的最大纬度
+pub const MAX_MERCATOR_LAT: f64 = 85.051_128_779_806_59;
+
+/// 支持的最大缩放级别
+pub const MAX_ZOOM: u8 = 30;
+
+/// 纬度对应的瓦片行号（小数），0 为北端
+fn tile_y_at(lat: f64, zoom: u8) -> f64 {
+    let lat = lat.clamp(-MAX_MERCATOR_LAT, MAX_MERCATOR_LAT).to_radians();
+    (1.0 - lat.tan().asinh() / PI) / 2.0 * (1u64 << zoom) as f64
+}
+
+/// 瓦片行号对应的纬度
+fn lat_at_tile_y(y: f64, zoom: u8) -> f64 {
+    let n = (1u64 << zoom) as f64;
+    (PI * (1.0 - 2.0 * y / n)).sinh().atan().to_degrees()
+}
+
+/// 经度对应的瓦片列号（小数）
+fn tile_x_at(lng: f64, zoom: u8) -> f64 {
+    (lng.clamp(-180.0, 180.0) + 180.0) / 360.0 * (1u64 << zoom) as f64
+}
+
+/// 经纬度所在的瓦片 (x, y)
+/// # 参数
+/// - `lng`: 经度
+/// - `lat`: 纬度，超出 ±85.0511° 时取最近的瓦片
+/// - `zoom`: 缩放级别, 取值范围 [0~30]
+#[pyfunction]
+pub fn lnglat_to_tile(lng: f64, lat: f64, zoom: u8) -> (u32, u32) {
+    let zoom = zoom.min(MAX_ZOOM);
+    let max = (1u64 << zoom) - 1;
+    let x = (tile_x_at(lng, zoom).floor() as u64).min(max);
+    let y = (tile_y_at(lat, zoom).floor() as u64).min(max);
+    (x as u32, y as u32)
+}
+
+/// 瓦片的经纬度范围 (min_lng, min_lat, max_lng, max_lat)，编号越界时返回 None
+#[pyfunction]
+pub fn tile_bounds(zoom: u8, x: u32, y: u32) -> Option<(f64, f64, f64, f64)> {
+    if zoom > MAX_ZOOM || x as u64 >= 1u64 << zoom || y as u64 >= 1u64 << zoom {
+        return None;
+    }
+    let n = (1u64 << zoom) as f64;
+    let min_lng = x as f64 / n * 360.0 - 180.0;
+    let max_lng = (x + 1) as f64 / n * 360.0 - 180.0;
+    let max_lat = lat_at_tile_y(y as f64, zoom);
+    let min_lat = lat_at_tile_y(y as f64 + 1.0, zoom);
+    Some((min_lng, min_lat, max_lng, max_lat))
+}
+
+/// 在指定纬度处，瓦片覆盖的经纬度面积与 GeoSOT 网格最接近的缩放级别
+///
+/// 墨卡托瓦片在纬度 φ 处的纬度跨度约为 `360 / 2^z * cos φ`，经度跨度不变，
+/// 因此高纬度地区同一层级的网格对应更小的缩放级别。
+#[pyfunction]
+pub fn zoom_for_level(level: usize, lat: f64) -> u8 {
+    let size = get_cell_size_in_degree(level.clamp(1, 32) as i32).unwrap();
+    let scale = lat.clamp(-MAX_MERCATOR_LAT, MAX_MERCATOR_LAT).to_radians().cos();
+    let mismatch = |z: u8| {
+        let span = 360.0 / (1u64 << z) as f64;
+        (span * span * scale / (size * size)).ln().abs()
+    };
+    (0..=MAX_ZOOM).min_by(|&a, &b| mismatch(a).total_cmp(&mismatch(b))).unwrap()
+}
+
+/// 与开区间 (lo, hi) 相交的整数格 [floor(lo), ceil(hi))，边界恰好重合时不计入相邻格
+fn index_range(lo: f64, hi: f64, zoom: u8) -> std::ops::Range<u32> {
+    const EPS: f64 = 1e-9;
+    let n = 1u64 << zoom;
+    let start = ((lo + EPS).floor().max(0.0) as u64).min(n - 1);
+    let end = ((hi - EPS).ceil() as u64).clamp(start + 1, n);
+    start as u32..end as u32
+}
+
+impl GeoSotCell {
+    /// 与该网格相交的全部瓦片 (x, y)，网格完全位于墨卡托范围之外时返回空列表
+    pub fn to_tiles(&self, zoom: u8) -> Vec<(u32, u32)> {
+        let zoom = zoom.min(MAX_ZOOM);
+        let (min_lng, min_lat, max_lng, max_lat) = self.bounds();
+        if max_lat <= -MAX_MERCATOR_LAT || min_lat >= MAX_MERCATOR_LAT {
+            return Vec::new();
+        }
+        let xs = index_range(tile_x_at(min_lng, zoom), tile_x_at(max_lng, zoom), zoom);
+        let ys = index_range(tile_y_at(max_lat, zoom), tile_y_at(min_lat, zoom), zoom);
+        ys.flat_map(|y| xs.clone().map(move |x| (x, y))).collect()
+    }
+}
+
+impl GeoSotRegion {
+    /// 覆盖瓦片的指定层级区域，瓦片编号越界时返回 None
+    pub fn from_tile(zoom: u8, x: u32, y: u32, level: usize) -> Option<Self> {
+        let (min_lng, min_lat, max_lng, max_lat) = tile_bounds(zoom, x, y)?;
+        let ring = vec![(min_lng, min_lat), (max_lng, min_lat), (max_lng, max_lat), (min_lng, max_lat), (min_lng, min_lat)];
+        Some(Self::cover_geometry(&Geometry::Polygon(vec![ring]), level))
+    }
+
+    /// 与区域相交的全部瓦片，可用于按瓦片预聚合
+    pub fn to_tiles(&self, zoom: u8) -> BTreeSet<(u32, u32)> {
+        self.cells().iter().flat_map(|cell| cell.to_tiles(zoom)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_math() {
+        assert_eq!(lnglat_to_tile(0.0, 0.0, 0), (0, 0));
+        assert_eq!(lnglat_to_tile(116.3912, 39.9075, 10), (843, 388));
+        assert_eq!(lnglat_to_tile(180.0, -90.0, 2), (3, 3));
+
+        let (min_lng, min_lat, max_lng, max_lat) = tile_bounds(1, 1, 0).unwrap();
+        assert_eq!((min_lng, min_lat, max_lng), (0.0, 0.0, 180.0));
+        assert!((max_lat - MAX_MERCATOR_LAT).abs() < 1e-9);
+        assert_eq!(tile_bounds(2, 4, 0), None);
+    }
+
+    #[test]
+    fn test_zoom_depends_on_latitude() {
+        // 1 度网格在赤道处对应 z8（约 1.4 度），高纬度瓦片在纬度上更窄，对应更小的缩放级别
+        let equator = zoom_for_level(9, 0.0);
+        let north = zoom_for_level(9, 80.0);
+        assert_eq!(equator, 8);
+        assert!(north < equator);
+    }
+
+    #[test]
+    fn test_cell_to_tiles() {
+        let cell = GeoSotCell::from_coords(116.3912, 39.9075, 18);
+        assert_eq!(cell.to_tiles(10), vec![(843, 388)]);
+
+        // 粗网格跨越多个瓦片，且每个瓦片都与网格相交
+        let coarse = GeoSotCell::from_coords(116.3912, 39.9075, 9);
+        let tiles = coarse.to_tiles(10);
+        assert!(tiles.len() > 1);
+        let (min_lng, min_lat, max_lng, max_lat) = coarse.bounds();
+        for (x, y) in tiles {
+            let (a, b, c, d) = tile_bounds(10, x, y).unwrap();
+            assert!(a < max_lng && c > min_lng && b < max_lat && d > min_lat);
+        }
+
+        assert!(GeoSotCell::from_coords(0.0, 89.5, 9).to_tiles(3).is_empty());
+    }
+
+    #[test]
+    fn test_region_from_tile() {
+        let region = GeoSotRegion::from_tile(10, 843, 388, 16).unwrap();
+        assert!(region.contains_point(116.3912, 39.9075));
+        // 边缘网格会越出瓦片，但只会接触相邻的瓦片
+        let tiles = region.to_tiles(10);
+        assert!(tiles.contains(&(843, 388)));
+        assert!(tiles.iter().all(|&(x, y)| x.abs_diff(843) <= 1 && y.abs_diff(388) <= 1));
+        assert!(GeoSotRegion::from_tile(10, 1024, 0, 16).is_none());
+    }
+}
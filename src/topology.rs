@@ -0,0 +1,290 @@
+//! 区域的拓扑关系（DE-9IM）
+//!
+//! 把区域看作同层级闭合网格的并集：内部为网格内部及区域内网格之间的公共边，
+//! 边界为区域网格与区域外网格之间的公共边和角点，其余为外部。两个同层级区域的
+//! 交集矩阵只取决于相邻网格（共享边）和 2x2 网格块（共享角点）的归属，逐网格检查即可精确得到。
+//! 两极之外没有网格，极点处不视为边界；经度方向跨越 ±180° 时绕回。
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::morphology::Connectivity;
+use crate::spatial::{GeoSotCell, GeoSotRegion};
+
+/// 交集的维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dimension {
+    /// 空集（F）
+    Empty,
+    /// 点（0）
+    Point,
+    /// 线（1）
+    Line,
+    /// 面（2）
+    Area,
+}
+
+impl Dimension {
+    /// DE-9IM 字符表示
+    pub fn as_char(&self) -> char {
+        match self {
+            Dimension::Empty => 'F',
+            Dimension::Point => '0',
+            Dimension::Line => '1',
+            Dimension::Area => '2',
+        }
+    }
+
+    /// 是否匹配模式字符 `T`、`F`、`*`、`0`、`1`、`2`
+    fn matches(&self, pattern: char) -> bool {
+        match pattern {
+            '*' => true,
+            'T' | 't' => *self != Dimension::Empty,
+            _ => self.as_char() == pattern.to_ascii_uppercase(),
+        }
+    }
+}
+
+/// DE-9IM 交集矩阵，行为 A 的内部、边界、外部，列为 B 的内部、边界、外部
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IntersectionMatrix(pub [[Dimension; 3]; 3]);
+
+impl IntersectionMatrix {
+    /// 是否匹配 9 位 DE-9IM 模式（如 `"T*F**F***"`），模式长度不为 9 时返回 false
+    pub fn matches(&self, pattern: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        pattern.len() == 9 && self.0.iter().flatten().zip(pattern).all(|(d, p)| d.matches(p))
+    }
+
+    /// 交换 A、B 后的矩阵
+    pub fn transpose(&self) -> Self {
+        let m = self.0;
+        Self(std::array::from_fn(|i| std::array::from_fn(|j| m[j][i])))
+    }
+}
+
+impl fmt::Display for IntersectionMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in self.0.iter().flatten() {
+            write!(f, "{}", d.as_char())?;
+        }
+        Ok(())
+    }
+}
+
+const INTERIOR: usize = 0;
+const BOUNDARY: usize = 1;
+const EXTERIOR: usize = 2;
+
+impl GeoSotRegion {
+    /// 计算与另一个区域的 DE-9IM 交集矩阵，两个区域层级不同时 panic
+    pub fn relate(&self, other: &GeoSotRegion) -> IntersectionMatrix {
+        if self.level != other.level {
+            panic!("Cannot relate regions with different levels");
+        }
+        use Dimension::*;
+        let mut m = [[Empty; 3]; 3];
+        m[EXTERIOR][EXTERIOR] = Area;
+        if !self.codes.is_disjoint(&other.codes) {
+            m[INTERIOR][INTERIOR] = Area;
+        }
+        if !self.codes.is_subset(&other.codes) {
+            m[INTERIOR][EXTERIOR] = Area;
+        }
+        if !other.codes.is_subset(&self.codes) {
+            m[EXTERIOR][INTERIOR] = Area;
+        }
+
+        let membership = |cell: &GeoSotCell| (self.contains(cell.code), other.contains(cell.code));
+        for code in self.codes.union(&other.codes).iter() {
+            let cell = GeoSotCell::new(code, self.level);
+            let (a_p, b_p) = membership(&cell);
+
+            // 共享边：边两侧网格的归属决定该边属于哪一部分
+            for neighbor in Connectivity::Four.neighbors(&cell) {
+                let (a_q, b_q) = membership(&neighbor);
+                if a_p && a_q && b_p != b_q {
+                    m[INTERIOR][BOUNDARY] = Line;
+                }
+                if b_p && b_q && a_p != a_q {
+                    m[BOUNDARY][INTERIOR] = Line;
+                }
+                if a_p != a_q && b_p != b_q {
+                    m[BOUNDARY][BOUNDARY] = Line;
+                }
+                if a_p != a_q && !b_p && !b_q {
+                    m[BOUNDARY][EXTERIOR] = Line;
+                }
+                if b_p != b_q && !a_p && !a_q {
+                    m[EXTERIOR][BOUNDARY] = Line;
+                }
+            }
+
+            // 共享角点：角点周围 4 个网格部分属于区域时，角点在区域边界上
+            if m[BOUNDARY][BOUNDARY] == Empty {
+                for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                    let block = [cell.neighbor(dx, 0), cell.neighbor(0, dy), cell.neighbor(dx, dy)];
+                    if block.iter().any(Option::is_none) {
+                        continue;
+                    }
+                    let (mut a_count, mut b_count) = (a_p as usize, b_p as usize);
+                    for c in block.iter().flatten() {
+                        let (a, b) = membership(c);
+                        a_count += a as usize;
+                        b_count += b as usize;
+                    }
+                    if (1..4).contains(&a_count) && (1..4).contains(&b_count) {
+                        m[BOUNDARY][BOUNDARY] = Point;
+                    }
+                }
+            }
+        }
+        IntersectionMatrix(m)
+    }
+
+    /// 是否匹配 DE-9IM 模式
+    pub fn relate_pattern(&self, other: &GeoSotRegion, pattern: &str) -> bool {
+        self.relate(other).matches(pattern)
+    }
+
+    /// 相接：没有公共网格，但边界共享边或角点
+    pub fn touches(&self, other: &GeoSotRegion) -> bool {
+        let m = self.relate(other);
+        m.0[INTERIOR][INTERIOR] == Dimension::Empty && m.0[BOUNDARY][BOUNDARY] != Dimension::Empty
+    }
+
+    /// 重叠：有公共网格，且双方都有不属于对方的网格
+    pub fn overlaps(&self, other: &GeoSotRegion) -> bool {
+        self.relate(other).matches("T*T***T**")
+    }
+
+    /// 在另一个区域之内：非空且全部网格都属于对方
+    pub fn within(&self, other: &GeoSotRegion) -> bool {
+        self.relate(other).matches("T*F**F***")
+    }
+
+    /// 包含另一个区域：对方非空且全部网格都属于本区域
+    ///
+    /// `contains` 已用于判断单个编码，因此区域之间的包含关系使用此名称。
+    pub fn contains_region(&self, other: &GeoSotRegion) -> bool {
+        other.within(self)
+    }
+
+    /// 覆盖另一个区域：对方的每个点都在本区域内（含边界）
+    ///
+    /// 区域都由整网格组成，因此与 `contains_region` 结果相同。
+    pub fn covers(&self, other: &GeoSotRegion) -> bool {
+        let m = self.relate(other);
+        !other.is_empty() && m.0[EXTERIOR][INTERIOR] == Dimension::Empty && m.0[EXTERIOR][BOUNDARY] == Dimension::Empty
+    }
+
+    /// 被另一个区域覆盖
+    pub fn covered_by(&self, other: &GeoSotRegion) -> bool {
+        other.covers(self)
+    }
+
+    /// 穿过：有公共网格，且本区域在对方之外的部分被对方分割成更多块（8 邻接连通）
+    ///
+    /// OGC 对面与面没有定义 crosses，这里按“穿过”理解，例如道路网格带穿过行政区。
+    pub fn crosses(&self, other: &GeoSotRegion) -> bool {
+        if self.level != other.level {
+            panic!("Cannot relate regions with different levels");
+        }
+        self.intersects(other) && component_count(&self.difference(other)) > component_count(self)
+    }
+}
+
+/// 区域的 8 邻接连通分量数量
+fn component_count(region: &GeoSotRegion) -> usize {
+    let mut seen = HashSet::new();
+    let mut count = 0;
+    for code in region.codes.iter() {
+        if !seen.insert(code) {
+            continue;
+        }
+        count += 1;
+        let mut stack = vec![GeoSotCell::new(code, region.level)];
+        while let Some(cell) = stack.pop() {
+            for neighbor in Connectivity::Eight.neighbors(&cell) {
+                if region.contains(neighbor.code) && seen.insert(neighbor.code) {
+                    stack.push(neighbor);
+                }
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 以 (x, y) 为网格偏移（相对 116°E 39°N 所在网格）的网格块 [x0, x1) x [y0, y1)
+    fn block(x0: i32, y0: i32, x1: i32, y1: i32) -> GeoSotRegion {
+        let origin = GeoSotCell::from_coords(116.0, 39.0, 15);
+        let mut region = GeoSotRegion::new(15);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                region.add_code(offset(origin, x, y).code);
+            }
+        }
+        region
+    }
+
+    fn offset(mut cell: GeoSotCell, x: i32, y: i32) -> GeoSotCell {
+        for _ in 0..x.abs() {
+            cell = cell.neighbor(x.signum(), 0).unwrap();
+        }
+        for _ in 0..y.abs() {
+            cell = cell.neighbor(0, y.signum()).unwrap();
+        }
+        cell
+    }
+
+    #[test]
+    fn test_relate_matrix() {
+        let a = block(0, 0, 4, 4);
+        // 与 JTS 对两个矩形的计算结果一致
+        assert_eq!(a.relate(&block(2, 2, 6, 6)).to_string(), "212101212");
+        assert_eq!(a.relate(&block(4, 0, 8, 4)).to_string(), "FF2F11212");
+        assert_eq!(a.relate(&block(4, 4, 8, 8)).to_string(), "FF2F01212");
+        assert_eq!(a.relate(&block(6, 6, 8, 8)).to_string(), "FF2FF1212");
+        assert_eq!(a.relate(&block(1, 1, 3, 3)).to_string(), "212FF1FF2");
+        assert_eq!(a.relate(&block(0, 0, 2, 2)).to_string(), "212F11FF2");
+        assert_eq!(a.relate(&a).to_string(), "2FFF1FFF2");
+        assert_eq!(block(1, 1, 3, 3).relate(&a), a.relate(&block(1, 1, 3, 3)).transpose());
+        assert_eq!(GeoSotRegion::new(15).relate(&a).to_string(), "FFFFFF212");
+    }
+
+    #[test]
+    fn test_predicates() {
+        let a = block(0, 0, 4, 4);
+        let inner = block(1, 1, 3, 3);
+        let edge = block(4, 0, 8, 4);
+        let corner = block(4, 4, 8, 8);
+        let overlapping = block(2, 2, 6, 6);
+        let far = block(6, 6, 8, 8);
+
+        assert!(a.touches(&edge) && a.touches(&corner));
+        assert!(!a.touches(&far) && !a.touches(&overlapping) && !a.touches(&inner));
+        assert!(a.overlaps(&overlapping) && !a.overlaps(&inner) && !a.overlaps(&edge));
+        assert!(inner.within(&a) && !a.within(&inner) && a.within(&a));
+        assert!(a.contains_region(&inner) && a.covers(&inner) && inner.covered_by(&a));
+        assert!(!a.contains_region(&overlapping) && !a.covers(&GeoSotRegion::new(15)));
+        assert!(a.relate_pattern(&inner, "T*****FF*"));
+        assert!(!a.relate_pattern(&inner, "T*****FF"));
+
+        // 一条网格带穿过方块
+        let road = block(-2, 2, 6, 3);
+        assert!(road.crosses(&a) && a.crosses(&road));
+        assert!(!inner.crosses(&a) && !block(-2, 2, 2, 3).crosses(&a));
+    }
+
+    #[test]
+    fn test_antimeridian() {
+        let west = GeoSotRegion::from_codes(vec![crate::get_code(179.5, 0.5, 9)], 9);
+        let east = GeoSotRegion::from_codes(vec![crate::get_code(-179.5, 0.5, 9)], 9);
+        assert!(west.touches(&east));
+        assert_eq!(west.relate(&east).to_string(), "FF2F11212");
+    }
+}
@@ -8,9 +8,238 @@
 /// 返回指定层级的分块大小，单位为度
 pub fn get_cell_size_in_degree(i: i32) -> Result<f64, &'static str> {  
     match i {  
-        i if i >= 0 && i <= 9 => Ok(2.0f64.powf(9.0 - i as f64)),  
-        i if i >= 10 && i <= 15 => Ok(2.0f64.powf(15.0 - i as f64) / 60.0),  
-        i if i >= 16 && i <= 32 => Ok(2.0f64.powf(21.0 - i as f64) / 3600.0),  
+        i if (0..=9).contains(&i) => Ok(2.0f64.powf(9.0 - i as f64)),  
+        i if (10..=15).contains(&i) => Ok(2.0f64.powf(15.0 - i as f64) / 60.0),  
+        i if (16..=32).contains(&i) => Ok(2.0f64.powf(21.0 - i as f64) / 3600.0),  
         _ => Err("i must be between 0 and 32 inclusive"),  
     }  
-} 
\ No newline at end of file
+} 
+
+/// 每度包含的刻度数，刻度为 GeoSOT 的最小单位 1/2048 秒
+pub const TICKS_PER_DEGREE: i64 = 3600 * 2048;
+/// 每分包含的刻度数
+pub const TICKS_PER_MINUTE: i64 = 60 * 2048;
+/// 每秒包含的刻度数
+pub const TICKS_PER_SECOND: i64 = 2048;
+
+/// 获取 32 位经度或纬度编码在指定层级下覆盖的范围 [lo, hi)，单位为刻度（带符号）
+///
+/// 分、秒字段只使用 0~59，层级步长超过剩余部分时（如 8 分网格的 56~64 分），
+/// 范围会截断到上一级字段的边界，因此同一层级相邻网格的边界严格重合。
+///
+/// 第 0 级网格覆盖整个编码空间（±256°），与编码无关。
+///
+/// # 参数
+/// * `code` - `dec2code` 得到的 32 位编码
+/// * `level` - 层级 [0~32]
+pub fn axis_tick_range(code: u32, level: usize) -> (i64, i64) {
+    if level == 0 {
+        return (-256 * TICKS_PER_DEGREE, 256 * TICKS_PER_DEGREE);
+    }
+    let d = ((code >> 23) & 0xFF) as i64;
+    let m = ((code >> 17) & 0x3F) as i64;
+    let s = ((code >> 11) & 0x3F) as i64;
+    let s11 = (code & 0x7FF) as i64;
+    let lo = d * TICKS_PER_DEGREE + m * TICKS_PER_MINUTE + s * TICKS_PER_SECOND + s11;
+
+    let level = level.clamp(1, 32) as i64;
+    let hi = match level {
+        1..=9 => lo + (1 << (9 - level)) * TICKS_PER_DEGREE,
+        10..=15 => (lo + (1 << (15 - level)) * TICKS_PER_MINUTE).min((d + 1) * TICKS_PER_DEGREE),
+        16..=21 => (lo + (1 << (21 - level)) * TICKS_PER_SECOND)
+            .min(d * TICKS_PER_DEGREE + (m + 1) * TICKS_PER_MINUTE),
+        _ => lo + (1 << (32 - level)),
+    };
+
+    if code >> 31 == 1 {
+        (-hi, -lo)
+    } else {
+        (lo, hi)
+    }
+}
+
+/// 获取带符号刻度值所在网格的 32 位经度或纬度编码
+///
+/// 非负值落在正半球网格 [lo, hi)，负值落在负半球网格 (-hi, -lo]，与 `axis_tick_range` 对应。
+///
+/// # 参数
+/// * `ticks` - 带符号的刻度值
+/// * `level` - 层级 [1~32]
+pub fn axis_code_at(ticks: i64, level: usize) -> u32 {
+    let mag = ticks.unsigned_abs() as i64;
+    let d = mag / TICKS_PER_DEGREE;
+    let m = mag % TICKS_PER_DEGREE / TICKS_PER_MINUTE;
+    let s = mag % TICKS_PER_MINUTE / TICKS_PER_SECOND;
+    let s11 = mag % TICKS_PER_SECOND;
+    let g = if ticks < 0 { 1u32 } else { 0 };
+    let code = (g << 31) | ((d as u32) << 23) | ((m as u32) << 17) | ((s as u32) << 11) | s11 as u32;
+
+    let level = level.clamp(1, 32);
+    if level < 32 {
+        (code >> (32 - level)) << (32 - level)
+    } else {
+        code
+    }
+}
+
+/// 获取覆盖带符号刻度区间 [t0, t1) 的所有网格，按从小到大排列
+///
+/// 返回每个网格的 32 位编码及其刻度范围；`t0 == t1` 时返回包含该点的网格。
+pub fn axis_cells(t0: i64, t1: i64, level: usize) -> Vec<(u32, i64, i64)> {
+    let mut cells = Vec::new();
+    let mut code = axis_code_at(t0, level);
+    loop {
+        let (lo, hi) = axis_tick_range(code, level);
+        cells.push((code, lo, hi));
+        if hi >= t1 {
+            break;
+        }
+        // 负半球网格在上界处闭合，下一个网格从上界之后开始
+        code = axis_code_at(if hi < 0 { hi + 1 } else { hi }, level);
+    }
+    cells
+}
+
+/// 地球平均半径（米），用于球面距离计算
+pub const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// 两点间的球面大圆距离（haversine 公式），单位为米
+pub fn haversine_distance(lng1: f64, lat1: f64, lng2: f64, lat2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = phi2 - phi1;
+    let d_lambda = (lng2 - lng1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().min(1.0).asin()
+}
+
+/// 点到经纬度范围 (min_lng, min_lat, max_lng, max_lat) 的最短球面距离（米），点在范围内时为 0
+///
+/// 点与范围经度重叠时最近点在同一经线上；否则最近点在东、西两条边界经线上，
+/// 取该经线大圆上的最近纬度并截断到范围内。`min_lng > max_lng` 表示跨越 180° 经线。
+pub fn point_to_bounds_distance(lng: f64, lat: f64, bounds: (f64, f64, f64, f64)) -> f64 {
+    let (min_lng, min_lat, max_lng, max_lat) = bounds;
+    let width = (max_lng - min_lng).rem_euclid(360.0);
+    if (lng - min_lng).rem_euclid(360.0) <= width || (width == 0.0 && max_lng != min_lng) {
+        return haversine_distance(lng, lat, lng, lat.clamp(min_lat, max_lat));
+    }
+    [min_lng, max_lng]
+        .iter()
+        .map(|&edge| {
+            let d_lng = (lng - edge).to_radians();
+            let phi = lat.to_radians();
+            let nearest = phi.sin().atan2(phi.cos() * d_lng.cos()).to_degrees();
+            haversine_distance(lng, lat, edge, nearest.clamp(min_lat, max_lat))
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// WGS84 长半轴（米）
+pub const WGS84_A: f64 = 6_378_137.0;
+/// WGS84 扁率
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// 两点间的 WGS84 椭球面测地距离（Vincenty 反解公式），单位为米
+///
+/// 精度约 0.5 毫米；两点接近对跖时迭代可能不收敛，返回 None。
+pub fn vincenty_distance(lng1: f64, lat1: f64, lng2: f64, lat2: f64) -> Option<f64> {
+    let b = WGS84_A * (1.0 - WGS84_F);
+    let l = (lng2 - lng1).to_radians();
+    let u1 = ((1.0 - WGS84_F) * lat1.to_radians().tan()).atan();
+    let u2 = ((1.0 - WGS84_F) * lat2.to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = (cos_u2 * sin_lambda).hypot(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+        if sin_sigma == 0.0 {
+            return Some(0.0);
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos2_alpha = 1.0 - sin_alpha * sin_alpha;
+        // 两点都在赤道上时 cos²α = 0
+        let cos_2sigma_m = if cos2_alpha != 0.0 { cos_sigma - 2.0 * sin_u1 * sin_u2 / cos2_alpha } else { 0.0 };
+        let c = WGS84_F / 16.0 * cos2_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos2_alpha));
+        let previous = lambda;
+        lambda = l
+            + (1.0 - c)
+                * WGS84_F
+                * sin_alpha
+                * (sigma + c * sin_sigma * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)));
+
+        if (lambda - previous).abs() < 1e-12 {
+            let u_sq = cos2_alpha * (WGS84_A * WGS84_A - b * b) / (b * b);
+            let big_a = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = big_b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + big_b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)
+                            - big_b / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma * sin_sigma)
+                                * (-3.0 + 4.0 * cos_2sigma_m * cos_2sigma_m)));
+            return Some(b * big_a * (sigma - delta_sigma));
+        }
+    }
+    None
+}
+
+/// 从第一点出发指向第二点的大圆初始方位角（度），正北为 0，顺时针取值 [0, 360)
+pub fn initial_bearing(lng1: f64, lat1: f64, lng2: f64, lat2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lambda = (lng2 - lng1).to_radians();
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// 32 位经度或纬度编码在指定层级下的带符号网格序号
+///
+/// 正半球从 0 开始向外递增，负半球从 -1 开始向外递减；截断网格（如 56~60 分）各算一个，
+/// 因此同一轴上相邻网格的序号恰好相差 1。
+pub fn axis_index(code: u32, level: usize) -> i64 {
+    let d = ((code >> 23) & 0xFF) as i64;
+    let m = ((code >> 17) & 0x3F) as i64;
+    let s = ((code >> 11) & 0x3F) as i64;
+    let s11 = (code & 0x7FF) as i64;
+
+    let level = level.clamp(1, 32);
+    let index = match level {
+        1..=9 => d >> (9 - level),
+        10..=15 => d * axis_cells_per_degree(level) + (m >> (15 - level)),
+        16..=21 => {
+            let per_minute = 60_u32.div_ceil(1 << (21 - level)) as i64;
+            d * axis_cells_per_degree(level) + m * per_minute + (s >> (21 - level))
+        }
+        _ => {
+            let per_second = 1_i64 << (level - 21);
+            d * axis_cells_per_degree(level) + (m * 60 + s) * per_second + (s11 >> (32 - level))
+        }
+    };
+    if code >> 31 == 1 {
+        -index - 1
+    } else {
+        index
+    }
+}
+
+/// 指定层级下经度方向绕地球一圈的网格数量（东西半球各一半）
+pub fn lng_cells_around(level: usize) -> i64 {
+    let level = level.clamp(1, 32);
+    let per_hemisphere = if level <= 9 { 180_u32.div_ceil(1 << (9 - level)) as i64 } else { 180 * axis_cells_per_degree(level) };
+    2 * per_hemisphere
+}
+
+/// 第 10 级及以上每度包含的网格数量
+fn axis_cells_per_degree(level: usize) -> i64 {
+    match level {
+        10..=15 => 60_u32.div_ceil(1 << (15 - level)) as i64,
+        16..=21 => 60 * (60_u32.div_ceil(1 << (21 - level)) as i64),
+        _ => 3600 << (level - 21),
+    }
+}
@@ -0,0 +1,326 @@
+//! WKB（Well-Known Binary）读写
+//!
+//! 写出统一使用小端、二维坐标；读取时支持大小端、ISO 的 Z / M / ZM 类型码
+//! 以及 PostGIS EWKB 的维度与 SRID 标志，额外维度会被忽略。
+
+use crate::binary::DecodeError;
+use crate::geometry::{Coord, Geometry};
+use crate::spatial::GeoSotRegion;
+
+const POINT: u32 = 1;
+const LINE_STRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTI_POINT: u32 = 4;
+const MULTI_LINE_STRING: u32 = 5;
+const MULTI_POLYGON: u32 = 6;
+const GEOMETRY_COLLECTION: u32 = 7;
+
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+/// 集合嵌套的最大深度，防止恶意数据递归耗尽栈空间
+const MAX_DEPTH: usize = 64;
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn header(&mut self, kind: u32) {
+        self.0.push(1);
+        self.u32(kind);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn coord(&mut self, (x, y): Coord) {
+        self.0.extend_from_slice(&x.to_le_bytes());
+        self.0.extend_from_slice(&y.to_le_bytes());
+    }
+
+    fn coords(&mut self, coords: &[Coord]) {
+        self.u32(coords.len() as u32);
+        coords.iter().for_each(|&c| self.coord(c));
+    }
+
+    fn rings(&mut self, rings: &[Vec<Coord>]) {
+        self.u32(rings.len() as u32);
+        rings.iter().for_each(|r| self.coords(r));
+    }
+
+    fn geometry(&mut self, geometry: &Geometry) {
+        match geometry {
+            Geometry::Point(p) => {
+                self.header(POINT);
+                self.coord(*p);
+            }
+            Geometry::LineString(line) => {
+                self.header(LINE_STRING);
+                self.coords(line);
+            }
+            Geometry::Polygon(rings) => {
+                self.header(POLYGON);
+                self.rings(rings);
+            }
+            Geometry::MultiPoint(points) => {
+                self.header(MULTI_POINT);
+                self.u32(points.len() as u32);
+                points.iter().for_each(|&p| self.geometry(&Geometry::Point(p)));
+            }
+            Geometry::MultiLineString(lines) => {
+                self.header(MULTI_LINE_STRING);
+                self.u32(lines.len() as u32);
+                for line in lines {
+                    self.header(LINE_STRING);
+                    self.coords(line);
+                }
+            }
+            Geometry::MultiPolygon(polygons) => {
+                self.header(MULTI_POLYGON);
+                self.u32(polygons.len() as u32);
+                for rings in polygons {
+                    self.header(POLYGON);
+                    self.rings(rings);
+                }
+            }
+            Geometry::GeometryCollection(items) => {
+                self.header(GEOMETRY_COLLECTION);
+                self.u32(items.len() as u32);
+                items.iter().for_each(|g| self.geometry(g));
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let end = self.pos.checked_add(N).ok_or(DecodeError::Truncated)?;
+        let chunk = self.bytes.get(self.pos..end).ok_or(DecodeError::Truncated)?;
+        self.pos = end;
+        Ok(chunk.try_into().unwrap())
+    }
+
+    fn u32(&mut self, little: bool) -> Result<u32, DecodeError> {
+        let raw = self.take::<4>()?;
+        Ok(if little { u32::from_le_bytes(raw) } else { u32::from_be_bytes(raw) })
+    }
+
+    fn f64(&mut self, little: bool) -> Result<f64, DecodeError> {
+        let raw = self.take::<8>()?;
+        Ok(if little { f64::from_le_bytes(raw) } else { f64::from_be_bytes(raw) })
+    }
+
+    /// 元素个数，按剩余字节数做上限检查，避免恶意数据触发巨量分配
+    fn count(&mut self, little: bool, min_item_len: usize) -> Result<usize, DecodeError> {
+        let count = self.u32(little)? as usize;
+        if count.saturating_mul(min_item_len) > self.bytes.len() - self.pos {
+            return Err(DecodeError::Truncated);
+        }
+        Ok(count)
+    }
+
+    fn coord(&mut self, little: bool, dims: usize) -> Result<Coord, DecodeError> {
+        let x = self.f64(little)?;
+        let y = self.f64(little)?;
+        for _ in 2..dims {
+            self.f64(little)?;
+        }
+        Ok((x, y))
+    }
+
+    fn coords(&mut self, little: bool, dims: usize) -> Result<Vec<Coord>, DecodeError> {
+        let n = self.count(little, dims * 8)?;
+        (0..n).map(|_| self.coord(little, dims)).collect()
+    }
+
+    fn rings(&mut self, little: bool, dims: usize) -> Result<Vec<Vec<Coord>>, DecodeError> {
+        let n = self.count(little, 4)?;
+        (0..n).map(|_| self.coords(little, dims)).collect()
+    }
+
+    fn members(&mut self, little: bool, expected: u32) -> Result<Vec<Geometry>, DecodeError> {
+        let n = self.count(little, 5)?;
+        if self.depth >= MAX_DEPTH {
+            return Err(DecodeError::Corrupt("geometry nesting too deep"));
+        }
+        self.depth += 1;
+        let members = (0..n)
+            .map(|_| {
+                let (kind, geometry) = self.geometry()?;
+                if expected != GEOMETRY_COLLECTION && kind != expected {
+                    return Err(DecodeError::Corrupt("unexpected member type in multi geometry"));
+                }
+                Ok(geometry)
+            })
+            .collect();
+        self.depth -= 1;
+        members
+    }
+
+    /// 读取一个几何，同时返回其基本类型码
+    fn geometry(&mut self) -> Result<(u32, Geometry), DecodeError> {
+        let little = match self.take::<1>()?[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(DecodeError::Corrupt("invalid byte order")),
+        };
+        let raw = self.u32(little)?;
+        if raw & EWKB_SRID != 0 {
+            self.u32(little)?;
+        }
+        let (has_z, has_m) = (raw & EWKB_Z != 0, raw & EWKB_M != 0);
+        let iso = raw & 0x0FFF_FFFF;
+        let (kind, iso_dims) = (iso % 1000, iso / 1000);
+        let dims = 2 + match iso_dims {
+            0 => has_z as usize + has_m as usize,
+            1 | 2 => 1,
+            3 => 2,
+            _ => return Err(DecodeError::Corrupt("invalid geometry type")),
+        };
+
+        let geometry = match kind {
+            POINT => {
+                let (x, y) = self.coord(little, dims)?;
+                // 空点以 NaN 坐标表示
+                if x.is_nan() && y.is_nan() {
+                    Geometry::MultiPoint(Vec::new())
+                } else {
+                    Geometry::Point((x, y))
+                }
+            }
+            LINE_STRING => Geometry::LineString(self.coords(little, dims)?),
+            POLYGON => Geometry::Polygon(self.rings(little, dims)?),
+            MULTI_POINT => Geometry::MultiPoint(
+                self.members(little, POINT)?
+                    .into_iter()
+                    .filter_map(|g| match g {
+                        Geometry::Point(p) => Some(p),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            MULTI_LINE_STRING => Geometry::MultiLineString(
+                self.members(little, LINE_STRING)?
+                    .into_iter()
+                    .filter_map(|g| match g {
+                        Geometry::LineString(line) => Some(line),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            MULTI_POLYGON => Geometry::MultiPolygon(
+                self.members(little, POLYGON)?
+                    .into_iter()
+                    .filter_map(|g| match g {
+                        Geometry::Polygon(rings) => Some(rings),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            GEOMETRY_COLLECTION => Geometry::GeometryCollection(self.members(little, GEOMETRY_COLLECTION)?),
+            _ => return Err(DecodeError::Corrupt("invalid geometry type")),
+        };
+        Ok((kind, geometry))
+    }
+}
+
+impl Geometry {
+    /// 编码为小端 WKB
+    pub fn to_wkb(&self) -> Vec<u8> {
+        let mut writer = Writer(Vec::new());
+        writer.geometry(self);
+        writer.0
+    }
+
+    /// 从 WKB / EWKB 解码
+    pub fn from_wkb(bytes: &[u8]) -> Result<Geometry, DecodeError> {
+        let mut reader = Reader { bytes, pos: 0, depth: 0 };
+        let (_, geometry) = reader.geometry()?;
+        if reader.pos != bytes.len() {
+            return Err(DecodeError::Corrupt("trailing bytes after geometry"));
+        }
+        Ok(geometry)
+    }
+}
+
+impl GeoSotRegion {
+    /// 将区域的合并轮廓编码为 WKB MultiPolygon
+    pub fn to_wkb(&self) -> Vec<u8> {
+        Geometry::MultiPolygon(self.outline()).to_wkb()
+    }
+
+    /// 解码 WKB 并覆盖为指定层级的区域
+    pub fn from_wkb(bytes: &[u8], level: usize) -> Result<Self, DecodeError> {
+        Ok(Self::cover_geometry(&Geometry::from_wkb(bytes)?, level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_layout() {
+        let bytes = Geometry::Point((1.0, 2.0)).to_wkb();
+        assert_eq!(bytes.len(), 21);
+        assert_eq!(&bytes[..5], &[1, 1, 0, 0, 0]);
+        assert_eq!(Geometry::from_wkb(&bytes).unwrap(), Geometry::Point((1.0, 2.0)));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let wkt = "GEOMETRYCOLLECTION (MULTIPOINT (1 2, 3 4), MULTILINESTRING ((0 0, 1 1)), \
+                   MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), ((5 5, 6 5, 6 6, 5 5))))";
+        let geometry = Geometry::from_wkt(wkt).unwrap();
+        assert_eq!(Geometry::from_wkb(&geometry.to_wkb()).unwrap(), geometry);
+    }
+
+    #[test]
+    fn test_big_endian_ewkb_with_z() {
+        // SRID=4326;POINT Z (116 39 50)，大端
+        let mut bytes = vec![0];
+        bytes.extend_from_slice(&(POINT | EWKB_Z | EWKB_SRID).to_be_bytes());
+        bytes.extend_from_slice(&4326u32.to_be_bytes());
+        for v in [116.0f64, 39.0, 50.0] {
+            bytes.extend_from_slice(&v.to_be_bytes());
+        }
+        assert_eq!(Geometry::from_wkb(&bytes).unwrap(), Geometry::Point((116.0, 39.0)));
+
+        assert_eq!(Geometry::from_wkb(&bytes[..10]), Err(DecodeError::Truncated));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| {
+            let mut bytes = Vec::new();
+            for _ in 0..depth {
+                bytes.push(1);
+                bytes.extend_from_slice(&GEOMETRY_COLLECTION.to_le_bytes());
+                bytes.extend_from_slice(&1u32.to_le_bytes());
+            }
+            bytes.extend_from_slice(&Geometry::Point((1.0, 2.0)).to_wkb());
+            bytes
+        };
+        assert!(Geometry::from_wkb(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Geometry::from_wkb(&nested(MAX_DEPTH + 1)),
+            Err(DecodeError::Corrupt("geometry nesting too deep"))
+        );
+        // 足以溢出栈的深度也只会返回错误
+        assert!(Geometry::from_wkb(&nested(200_000)).is_err());
+    }
+
+    #[test]
+    fn test_region_outline() {
+        let region = GeoSotRegion::from_rectangle(116.0, 39.0, 116.05, 39.05, 15);
+        let bytes = region.to_wkb();
+        assert_eq!(GeoSotRegion::from_wkb(&bytes, 15).unwrap(), region);
+    }
+}
@@ -0,0 +1,347 @@
+//! WKT（Well-Known Text）解析
+//!
+//! 支持 POINT、LINESTRING、POLYGON、MULTIPOINT、MULTILINESTRING、
+//! MULTIPOLYGON、GEOMETRYCOLLECTION 及 EMPTY，关键字不区分大小写。
+//! 带 Z / M / ZM 的坐标只取前两维，EWKT 的 `SRID=...;` 前缀会被忽略。
+
+use crate::geometry::{Coord, Geometry, ParseError};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Symbol(char),
+    End,
+}
+
+/// 带位置的词法单元，行列号从 1 开始
+#[derive(Debug, Clone)]
+struct Spanned {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Spanned>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let (mut line, mut column) = (1, 1);
+
+    while let Some(&c) = chars.peek() {
+        let (start_line, start_column) = (line, column);
+        if c.is_whitespace() {
+            chars.next();
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+            continue;
+        }
+
+        let token = if c.is_ascii_alphabetic() {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_') {
+                word.push(c.to_ascii_uppercase());
+                chars.next();
+                column += 1;
+            }
+            Token::Word(word)
+        } else if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek() {
+                let exponent_sign = matches!(c, '-' | '+') && number.ends_with(['e', 'E']);
+                if c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E') || exponent_sign || number.is_empty() {
+                    number.push(c);
+                    chars.next();
+                    column += 1;
+                } else {
+                    break;
+                }
+            }
+            match number.parse::<f64>() {
+                Ok(value) if value.is_finite() => Token::Number(value),
+                _ => return Err(ParseError::new(start_line, start_column, format!("invalid number '{number}'"))),
+            }
+        } else if matches!(c, '(' | ')' | ',' | '=' | ';') {
+            chars.next();
+            column += 1;
+            Token::Symbol(c)
+        } else {
+            return Err(ParseError::new(line, column, format!("unexpected character '{c}'")));
+        };
+        tokens.push(Spanned { token, line: start_line, column: start_column });
+    }
+
+    tokens.push(Spanned { token: Token::End, line, column });
+    Ok(tokens)
+}
+
+/// GEOMETRYCOLLECTION 嵌套的最大深度，防止恶意输入递归耗尽栈空间
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].token
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].token.clone();
+        if token != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        let Spanned { line, column, .. } = self.tokens[self.pos];
+        ParseError::new(line, column, message)
+    }
+
+    fn describe(token: &Token) -> String {
+        match token {
+            Token::Word(word) => format!("'{word}'"),
+            Token::Number(value) => format!("number {value}"),
+            Token::Symbol(c) => format!("'{c}'"),
+            Token::End => "end of input".to_string(),
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ParseError> {
+        if *self.peek() == Token::Symbol(symbol) {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{symbol}', found {}", Self::describe(self.peek()))))
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Token::Word(w) if w == word) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 逗号分隔、括号包围的列表
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Result<T, ParseError>) -> Result<Vec<T>, ParseError> {
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        while *self.peek() == Token::Symbol(',') {
+            self.next();
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Ok(items)
+    }
+
+    /// 判断下一个列表是否为 EMPTY，是则消费该关键字
+    fn empty(&mut self) -> bool {
+        self.eat_word("EMPTY")
+    }
+
+    fn number(&mut self) -> Result<f64, ParseError> {
+        match self.peek() {
+            Token::Number(value) => {
+                let value = *value;
+                self.next();
+                Ok(value)
+            }
+            other => Err(self.error(format!("expected number, found {}", Self::describe(other)))),
+        }
+    }
+
+    /// 坐标：经度 纬度 [Z] [M]，只保留前两维
+    fn coord(&mut self) -> Result<Coord, ParseError> {
+        let lng = self.number()?;
+        let lat = self.number()?;
+        for _ in 0..2 {
+            if matches!(self.peek(), Token::Number(_)) {
+                self.next();
+            }
+        }
+        Ok((lng, lat))
+    }
+
+    fn coords(&mut self) -> Result<Vec<Coord>, ParseError> {
+        if self.empty() {
+            return Ok(Vec::new());
+        }
+        self.list(Self::coord)
+    }
+
+    fn rings(&mut self) -> Result<Vec<Vec<Coord>>, ParseError> {
+        if self.empty() {
+            return Ok(Vec::new());
+        }
+        self.list(Self::coords)
+    }
+
+    /// MULTIPOINT 中的点既可写作 `(1 2)` 也可省略括号写作 `1 2`
+    fn multi_point_item(&mut self) -> Result<Option<Coord>, ParseError> {
+        if self.empty() {
+            return Ok(None);
+        }
+        if *self.peek() == Token::Symbol('(') {
+            self.next();
+            let coord = self.coord()?;
+            self.expect(')')?;
+            Ok(Some(coord))
+        } else {
+            self.coord().map(Some)
+        }
+    }
+
+    fn geometry(&mut self) -> Result<Geometry, ParseError> {
+        let start = self.pos;
+        let kind = match self.peek() {
+            Token::Word(word) => word.clone(),
+            other => return Err(self.error(format!("expected geometry type, found {}", Self::describe(other)))),
+        };
+        self.next();
+        if let Token::Word(dims) = self.peek() {
+            if matches!(dims.as_str(), "Z" | "M" | "ZM") {
+                self.next();
+            }
+        }
+
+        let geometry = match kind.as_str() {
+            // POINT EMPTY 没有坐标，以空 MULTIPOINT 表示
+            "POINT" if self.empty() => Geometry::MultiPoint(Vec::new()),
+            "POINT" => {
+                self.expect('(')?;
+                let coord = self.coord()?;
+                self.expect(')')?;
+                Geometry::Point(coord)
+            }
+            "LINESTRING" => Geometry::LineString(self.coords()?),
+            "POLYGON" => Geometry::Polygon(self.rings()?),
+            "MULTIPOINT" if self.empty() => Geometry::MultiPoint(Vec::new()),
+            "MULTIPOINT" => Geometry::MultiPoint(self.list(Self::multi_point_item)?.into_iter().flatten().collect()),
+            "MULTILINESTRING" if self.empty() => Geometry::MultiLineString(Vec::new()),
+            "MULTILINESTRING" => Geometry::MultiLineString(self.list(Self::coords)?),
+            "MULTIPOLYGON" if self.empty() => Geometry::MultiPolygon(Vec::new()),
+            "MULTIPOLYGON" => Geometry::MultiPolygon(self.list(Self::rings)?),
+            "GEOMETRYCOLLECTION" if self.empty() => Geometry::GeometryCollection(Vec::new()),
+            "GEOMETRYCOLLECTION" => {
+                if self.depth >= MAX_DEPTH {
+                    self.pos = start;
+                    return Err(self.error("geometry nesting too deep"));
+                }
+                self.depth += 1;
+                let items = self.list(Self::geometry)?;
+                self.depth -= 1;
+                Geometry::GeometryCollection(items)
+            }
+            _ => {
+                self.pos = start;
+                return Err(self.error(format!("unknown geometry type '{kind}'")));
+            }
+        };
+        Ok(geometry)
+    }
+
+    /// EWKT 的 `SRID=4326;` 前缀
+    fn srid(&mut self) -> Result<(), ParseError> {
+        if self.eat_word("SRID") {
+            self.expect('=')?;
+            self.number()?;
+            self.expect(';')?;
+        }
+        Ok(())
+    }
+}
+
+impl Geometry {
+    /// 从 WKT / EWKT 文本解析几何
+    pub fn from_wkt(text: &str) -> Result<Geometry, ParseError> {
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0, depth: 0 };
+        parser.srid()?;
+        let geometry = parser.geometry()?;
+        match parser.peek() {
+            Token::End => Ok(geometry),
+            other => Err(parser.error(format!("unexpected {} after geometry", Parser::describe(other)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic_types() {
+        assert_eq!(Geometry::from_wkt("POINT (116 39)").unwrap(), Geometry::Point((116.0, 39.0)));
+        assert_eq!(
+            Geometry::from_wkt("linestring(1 2, 3.5 -4e1)").unwrap(),
+            Geometry::LineString(vec![(1.0, 2.0), (3.5, -40.0)])
+        );
+        assert_eq!(
+            Geometry::from_wkt("POLYGON ((0 0, 1 0, 1 1, 0 0), (0.2 0.2, 0.8 0.2, 0.8 0.4, 0.2 0.2))").unwrap(),
+            Geometry::Polygon(vec![
+                vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)],
+                vec![(0.2, 0.2), (0.8, 0.2), (0.8, 0.4), (0.2, 0.2)],
+            ])
+        );
+        assert_eq!(
+            Geometry::from_wkt("MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), EMPTY)").unwrap(),
+            Geometry::MultiPolygon(vec![vec![vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]], vec![]])
+        );
+    }
+
+    #[test]
+    fn test_parse_variants() {
+        assert_eq!(
+            Geometry::from_wkt("MULTIPOINT ((1 2), (3 4))").unwrap(),
+            Geometry::from_wkt("MULTIPOINT (1 2, 3 4)").unwrap()
+        );
+        assert_eq!(
+            Geometry::from_wkt("SRID=4326;POINT Z (116 39 50)").unwrap(),
+            Geometry::Point((116.0, 39.0))
+        );
+        assert_eq!(
+            Geometry::from_wkt("GEOMETRYCOLLECTION (POINT (1 2), LINESTRING EMPTY)").unwrap(),
+            Geometry::GeometryCollection(vec![Geometry::Point((1.0, 2.0)), Geometry::LineString(vec![])])
+        );
+        assert!(Geometry::from_wkt("POINT EMPTY").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_error_position() {
+        let err = Geometry::from_wkt("POLYGON ((0 0, 1 0,\n  1 1, x 0))").unwrap_err();
+        assert_eq!(err.position, Some((2, 8)));
+        assert_eq!(err.message, "expected number, found 'X'");
+
+        let err = Geometry::from_wkt("POINT (1 2) extra").unwrap_err();
+        assert_eq!(err.position, Some((1, 13)));
+
+        let err = Geometry::from_wkt("CIRCLE (1 2)").unwrap_err();
+        assert_eq!(err.position, Some((1, 1)));
+
+        let err = Geometry::from_wkt("POINT (1 2").unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 11: expected ')', found end of input");
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| {
+            format!("{}POINT (1 2){}", "GEOMETRYCOLLECTION (".repeat(depth), ")".repeat(depth))
+        };
+        assert!(Geometry::from_wkt(&nested(MAX_DEPTH)).is_ok());
+
+        let err = Geometry::from_wkt(&nested(MAX_DEPTH + 1)).unwrap_err();
+        assert_eq!(err.position, Some((1, 1 + 20 * MAX_DEPTH)));
+        assert_eq!(err.message, "geometry nesting too deep");
+
+        assert!(Geometry::from_wkt(&nested(200_000)).is_err());
+    }
+}
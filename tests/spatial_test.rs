@@ -1,7 +1,9 @@
 // tests/spatial_test.rs
+#![allow(unused_imports, clippy::items_after_test_module, clippy::manual_range_contains)]
 #[cfg(test)]
 mod tests {
     use geosot::spatial::{GeoSotRegion, spatial_analysis};
+    use geosot::storage::RegionStorage;
     use geosot::{get_code, to_string};
 
     #[test]
@@ -221,6 +223,32 @@ mod tests {
             assert!((orig_lat - conv_lat).abs() < 0.01, "纬度转换误差过大");
         }
     }
+
+    #[test]
+    fn test_bitmap_storage() {
+        println!("=== 位图存储测试 ===");
+
+        let level = 18;
+        let a = GeoSotRegion::from_rectangle(115.5, 38.5, 116.5, 39.5, level);
+        let b = GeoSotRegion::from_rectangle(116.0, 39.0, 117.0, 40.0, level);
+        let bitmap_a = a.to_storage(RegionStorage::Bitmap);
+        let bitmap_b = b.to_storage(RegionStorage::Bitmap);
+
+        assert_eq!(bitmap_a.storage(), RegionStorage::Bitmap);
+        assert_eq!(bitmap_a, a);
+
+        // 位图与 BTreeSet 的集合运算结果完全一致
+        assert_eq!(bitmap_a.intersection(&bitmap_b), a.intersection(&b));
+        assert_eq!(bitmap_a.union(&bitmap_b), a.union(&b));
+        assert_eq!(bitmap_a.difference(&bitmap_b), a.difference(&b));
+        assert_eq!(bitmap_a.symmetric_difference(&bitmap_b), a.symmetric_difference(&b));
+        assert_eq!(bitmap_a.to_strings(), a.to_strings());
+
+        // 混合存储时沿用左操作数的存储方式
+        let mixed = bitmap_a.union(&b);
+        assert_eq!(mixed.storage(), RegionStorage::Bitmap);
+        assert_eq!(mixed, a.union(&b));
+    }
 }
 
 fn main() {
@@ -1,5 +1,5 @@
 import geosot
-from geosot import GeoSotCell, GeoSotRegion, spatial_analysis
+from geosot import GeoSotCell, GeoSotRegion, RegionStorage, spatial_analysis
 
 def test_geosot_cell():
     cell = GeoSotCell.from_coords(116.397, 39.916, 20)
@@ -12,6 +12,11 @@ def test_geosot_cell():
     children = parent.children()
     assert len(children) == 4
     assert any(c.is_descendant_of(parent) for c in children)
+    assert cell.code in [c.code for c in children]
+
+    neighbors = cell.neighbors()
+    assert len(neighbors) == 8
+    assert all(n.is_adjacent_to(cell) for n in neighbors)
 
 def test_geosot_region():
     region = GeoSotRegion(20)
@@ -37,9 +42,355 @@ def test_spatial_analysis():
 
     similarity = spatial_analysis.jaccard_similarity(region1, region2)
     assert abs(similarity - 0.5) < 1e-9
+    assert abs(spatial_analysis.dice_coefficient(region1, region2) - 2 / 3) < 1e-9
+    assert abs(spatial_analysis.tversky_index(region1, region2, 1.0, 1.0) - 0.5) < 1e-9
+    assert abs(spatial_analysis.tversky_index(region1, region2) - 2 / 3) < 1e-9
+    empty = GeoSotRegion(20)
+    assert spatial_analysis.dice_coefficient(empty, empty) == 1.0
+    assert spatial_analysis.tversky_index(region1, empty, 0.0, 0.0) == 0.0
+
+    a = GeoSotRegion.from_rectangle(116.0, 39.0, 116.99, 39.99, 12)
+    b = GeoSotRegion.from_rectangle(118.0, 39.0, 118.99, 39.99, 12)
+    gap = spatial_analysis.min_distance(a, b)
+    assert 80_000 < gap < 90_000
+    assert gap < spatial_analysis.hausdorff_distance(a, b) < spatial_analysis.centroid_distance(a, b)
+    assert spatial_analysis.hausdorff_distance(a, a) == 0.0
+    assert spatial_analysis.min_distance(a, GeoSotRegion(12)) == float("inf")
+
+def test_bitmap_storage():
+    region1 = GeoSotRegion.from_codes([1, 2, 3, 4], 20).to_storage(RegionStorage.Bitmap)
+    region2 = GeoSotRegion.from_codes([3, 4, 5, 6], 20)
+    assert region1.storage == RegionStorage.Bitmap
+
+    intersection = region1.intersection(region2)
+    assert intersection.storage == RegionStorage.Bitmap
+    assert intersection.codes == {3, 4}
+
+def test_geojson_export():
+    import json
+    cell = GeoSotCell.from_coords(116.0, 39.0, 15)
+    feature = json.loads(cell.to_geojson())
+    assert feature["geometry"]["type"] == "Polygon"
+    assert feature["properties"]["grid"] == cell.to_string_py()
+
+    region = GeoSotRegion.from_rectangle(116.0, 39.0, 116.05, 39.05, 15)
+    collection = json.loads(region.to_geojson_feature_collection())
+    assert len(collection["features"]) == region.size()
+    outline = json.loads(region.to_geojson_feature_collection(dissolve=True))
+    assert len(outline["features"]) == 1
+
+def test_geometry_import():
+    region = GeoSotRegion.from_wkt("POLYGON ((116 39, 116.5 39, 116.5 39.5, 116 39.5, 116 39))", 15)
+    assert region.size() == 900
+    geojson = '{"type": "Feature", "geometry": {"type": "Point", "coordinates": [116.0, 39.0]}}'
+    assert GeoSotRegion.from_geojson(geojson, 20).size() == 1
+    try:
+        GeoSotRegion.from_wkt("POINT (1", 20)
+        assert False
+    except ValueError as e:
+        assert "line 1" in str(e)
+
+def test_binary_format():
+    region = GeoSotRegion.from_rectangle(116.0, 39.0, 116.2, 39.2, 18)
+    data = region.to_bytes()
+    assert isinstance(data, bytes) and data[:4] == b"GSOT"
+    assert GeoSotRegion.from_bytes(data).codes == region.codes
+    assert len(data) * 10 < len(str(sorted(region.codes)))
+    try:
+        GeoSotRegion.from_bytes(data[:-1] + bytes([data[-1] ^ 1]))
+        assert False
+    except ValueError as e:
+        assert "checksum" in str(e)
+
+    wkb = region.to_wkb()
+    assert GeoSotRegion.from_wkb(wkb, 18).codes == region.codes
+
+def test_geohash():
+    from geosot import geohash_encode, geohash_bounds, geohash_precision_for_level
+    assert geohash_encode(-5.6, 42.6, 5) == "ezs42"
+    assert geohash_bounds("ezs4a") is None
+    cell = GeoSotCell.from_coords(116.3912, 39.9075, 16)
+    precision = geohash_precision_for_level(16)
+    assert geohash_encode(116.3912, 39.9075, precision) in cell.to_geohashes(precision)
+    region = GeoSotRegion.from_geohash("wx4g0", 15)
+    assert region.contains_point(116.39, 39.9)
+
+def test_tiles():
+    from geosot import lnglat_to_tile, tile_bounds, zoom_for_level
+    assert lnglat_to_tile(116.3912, 39.9075, 10) == (843, 388)
+    assert tile_bounds(10, 1024, 0) is None
+    assert zoom_for_level(9, 80.0) < zoom_for_level(9, 0.0)
+    cell = GeoSotCell.from_coords(116.3912, 39.9075, 18)
+    assert cell.to_tiles(10) == [(843, 388)]
+    region = GeoSotRegion.from_tile(10, 843, 388, 16)
+    assert (843, 388) in region.to_tiles(10)
+
+def test_mgrs():
+    from geosot import lnglat_to_mgrs, mgrs_to_lnglat, utm_to_lnglat
+    assert lnglat_to_mgrs(2.2945, 48.8582, 5) == "31U DQ 48251 11932"
+    lng, lat = mgrs_to_lnglat("31UDQ4825111932")
+    assert abs(lng - 2.2945) < 1e-4 and abs(lat - 48.8582) < 1e-4
+    lng, lat = utm_to_lnglat("31N 448252 5411933")
+    assert abs(lng - 2.2945) < 1e-4
+    cell = GeoSotCell.from_mgrs("31U DQ 48 11")
+    assert cell.to_mgrs() == "31U DQ 48 11"
+    try:
+        GeoSotCell.from_mgrs("31U DI 48251 11932")
+        assert False
+    except ValueError as e:
+        assert "column 6" in str(e)
+
+def test_dms():
+    from geosot import parse_coordinates, format_dms, format_ddm
+    lng, lat = parse_coordinates("39°54'33.2\"N 116°23'27.6\"E")
+    assert abs(lat - 39.909222) < 1e-6 and abs(lng - 116.391) < 1e-6
+    assert parse_coordinates("S33.857 E151.215") == (151.215, -33.857)
+    assert format_dms(39.909222, True, 1) == "39°54'33.2\"N"
+    assert format_ddm(-116.391, False) == "116°23.460'W"
+    cell = GeoSotCell.from_dms("39°54'33.2\"N 116°23'27.6\"E", 21)
+    assert cell.to_dms() == "39°54'33.5\"N 116°23'27.5\"E"
+    try:
+        GeoSotCell.from_dms("39°61'N 116°E", 21)
+        assert False
+    except ValueError as e:
+        assert "column 4" in str(e)
+
+def test_csv_encoding():
+    import os, tempfile
+    from geosot import encode_csv, get_code, get_codes
+    assert get_codes([(116.3912, 39.9075), (-73.98, 40.75)], 18) == [get_code(116.3912, 39.9075, 18), get_code(-73.98, 40.75, 18)]
+    with tempfile.TemporaryDirectory() as tmp:
+        src, dst = os.path.join(tmp, "in.csv"), os.path.join(tmp, "out.csv")
+        with open(src, "w") as f:
+            f.write("id,lon,lat\n1,116.3912,39.9075\n2,,\n")
+        assert encode_csv(src, dst, [18], lng_column="lon", grid=True, skip_invalid=True) == (2, 1)
+        with open(dst) as f:
+            lines = f.read().splitlines()
+        assert lines[0] == "id,lon,lat,geosot_code,geosot_grid"
+        assert lines[1].split(",")[3] == str(get_code(116.3912, 39.9075, 18))
+        assert lines[2] == "2,,,,"
+
+def test_index():
+    from geosot import GeoSotIndex
+    index = GeoSotIndex(18)
+    beijing = index.insert(116.3912, 39.9075, {"name": "Beijing"})
+    shanghai = index.insert(121.4737, 31.2304, {"name": "Shanghai"})
+    assert len(index) == 2 and beijing in index
+    assert [obj["name"] for _, obj in index.query_bbox(115, 39, 117, 41)] == ["Beijing"]
+    assert [i for i, _ in index.query_radius(121.47, 31.23, 10_000)] == [shanghai]
+    region = GeoSotRegion.from_rectangle(121.0, 31.0, 122.0, 32.0, 12)
+    assert [i for i, _ in index.query_region(region)] == [shanghai]
+    nearest = index.nearest(116.0, 39.0, 2)
+    assert [i for i, _, _ in nearest] == [beijing, shanghai]
+    assert nearest[0][1] < nearest[1][1] and nearest[0][2]["name"] == "Beijing"
+    index.update(beijing, 121.48, 31.23)
+    assert len(index.query_radius(121.47, 31.23, 10_000)) == 2
+    assert index.remove(shanghai)["name"] == "Shanghai"
+    try:
+        index.remove(shanghai)
+        assert False
+    except KeyError:
+        pass
+
+def test_aggregation():
+    import json
+    from geosot import GeoSotAggregator
+    agg = GeoSotAggregator(18)
+    agg.add_points([(116.3912, 39.9075, 2.0), (116.3912, 39.9075, 4.0), (121.4737, 31.2304, 10.0)])
+    agg.add(116.3912, 39.9075, 6.0)
+    assert len(agg) == 2 and agg.level == 18
+    stats = agg.to_dict()[GeoSotCell.from_coords(116.3912, 39.9075, 18).code]
+    assert stats["count"] == 3 and stats["sum"] == 12.0 and stats["mean"] == 4.0
+    assert stats["min"] == 2.0 and stats["max"] == 6.0 and abs(stats["variance"] - 8 / 3) < 1e-9
+    coarse = agg.roll_up(1)
+    assert len(coarse) == 1 and list(coarse.to_dict().values())[0]["count"] == 4
+    assert agg.region().size() == 2
+    features = json.loads(agg.to_geojson())["features"]
+    assert sorted(f["properties"]["count"] for f in features) == [1, 3]
+    for level in (0, 33):
+        try:
+            GeoSotAggregator(level)
+            assert False
+        except ValueError:
+            pass
+
+def test_grid():
+    import math
+    from geosot import GeoSotGrid, Resampling
+    region = GeoSotRegion.from_rectangle(116.0, 39.0, 116.5, 39.5, 14)
+    grid = GeoSotGrid.from_region(region, 1.0)
+    assert len(grid) == region.size() and grid.level == 14
+    cell = GeoSotCell.from_coords(116.2, 39.2, 14)
+    assert cell in grid and grid.get(cell) == 1.0
+    grid.set(cell, 5.0)
+    assert grid.value_at(116.2, 39.2) == 5.0
+    try:
+        grid.set(cell.parent(), 1.0)
+        assert False
+    except ValueError:
+        pass
+    doubled = grid.map(lambda v: v * 2)
+    assert doubled.get(cell) == 10.0
+    assert grid.zip(doubled, lambda a, b: b - a).get(cell) == 5.0
+    masked = grid.mask(GeoSotRegion.from_codes([cell.code], 14))
+    assert [c.code for c, _ in masked.items()] == [cell.code]
+    total = sum(v for _, v in grid.items())
+    coarse = grid.resample(12, Resampling.Sum)
+    assert abs(sum(v for _, v in coarse.items()) - total) < 1e-9
+    assert grid.resample(16).value_at(116.2, 39.2) == 5.0
+    try:
+        grid.resample(32)
+        assert False
+    except ValueError:
+        pass
+    point = GeoSotGrid(9)
+    point.set_point(116.5, 39.5, 1.0)
+    data, transform = point.to_dense(116.0, 38.0, 118.0, 40.0)
+    assert transform == [116.0, 1.0, 0.0, 40.0, 0.0, -1.0]
+    assert data[0][0] == 1.0 and math.isnan(data[1][1])
+    try:
+        GeoSotGrid(12).to_dense(116.0, 39.0, 117.0, 40.0)
+        assert False
+    except ValueError:
+        pass
+
+def test_focal():
+    from geosot import GeoSotGrid, Focal
+    center = GeoSotCell.from_coords(116.397, 39.916, 15)
+    ring = center.k_ring(1)
+    assert len(ring) == 9 and len(center.k_ring(2)) == 25
+    grid = GeoSotGrid(15)
+    for i, cell in enumerate(ring):
+        grid.set(cell, float(i))
+    assert grid.focal(1, Focal.Sum).get(center) == 36.0
+    assert grid.focal(1).get(center) == 4.0
+    assert grid.focal(1, Focal.Max).get(center) == 8.0
+    gi = grid.getis_ord_gi_star(1)
+    assert len(gi) == 9 and all(isinstance(v, float) for _, v in gi.items())
+
+def test_morphology():
+    from geosot import Connectivity
+    center = GeoSotCell.from_coords(116.397, 39.916, 15)
+    square = GeoSotRegion.from_codes([c.code for c in center.k_ring(2)], 15)
+    assert square.dilate(1).size() == 49
+    assert square.dilate(1, Connectivity.Four).size() == 45
+    assert square.erode(1).codes == {c.code for c in center.k_ring(1)}
+    noisy = GeoSotRegion.from_codes(list(square.codes) + [GeoSotCell.from_coords(116.6, 39.6, 15).code], 15)
+    assert noisy.open(1).codes == square.codes
+    holed = GeoSotRegion.from_codes([c for c in square.codes if c != center.code], 15)
+    assert holed.close(1).codes == square.codes
+
+def test_distance_transform():
+    target = GeoSotRegion(14)
+    target.add_point(116.2, 39.2)
+    extent = GeoSotRegion.from_rectangle(116.0, 39.0, 116.5, 39.5, 14)
+    distances = target.distance_transform(extent)
+    assert len(distances) == extent.size()
+    assert distances.value_at(116.2, 39.2) == 0.0
+    assert 17_000 < distances.value_at(116.45, 39.2) < 20_000
+    try:
+        target.distance_transform(GeoSotRegion.from_rectangle(116.0, 39.0, 116.5, 39.5, 13))
+        assert False
+    except ValueError:
+        pass
+    near = target.buffer_m(5_000)
+    far = target.buffer_m(10_000)
+    assert far.is_superset(near) and near.is_superset(target) and far.size() > near.size()
+
+def test_topology():
+    center = GeoSotCell.from_coords(116.397, 39.916, 15)
+    square = GeoSotRegion.from_codes([c.code for c in center.k_ring(1)], 15)
+    inner = GeoSotRegion.from_codes([center.code], 15)
+    ring = GeoSotRegion.from_codes([c.code for c in center.k_ring(2)], 15).difference(square)
+    assert square.relate(inner) == "212FF1FF2"
+    assert square.contains_region(inner) and inner.within(square)
+    assert square.covers(inner) and inner.covered_by(square)
+    assert square.touches(ring) and not square.touches(inner)
+    assert square.relate(ring) == "FF2F1F212"
+    assert not square.overlaps(inner) and square.relate_pattern(inner, "T*****FF*")
+    strip = GeoSotRegion.from_codes([c.code for c in center.k_ring(2) if c.center()[1] == center.center()[1]], 15)
+    assert strip.size() == 5 and strip.crosses(square) and not inner.crosses(square)
+    coarse = GeoSotRegion.from_codes([center.parent().code], 14)
+    for relation in (square.relate, square.touches, square.overlaps, square.within, square.covers, square.crosses):
+        try:
+            relation(coarse)
+            assert False
+        except ValueError:
+            pass
+
+def test_extent():
+    empty = GeoSotRegion(9)
+    assert empty.bbox() is None and empty.centroid() is None and empty.covering_cell() is None
+    region = GeoSotRegion(9)
+    region.add_point(116.5, 40.5)
+    region.add_point(118.5, 39.5)
+    assert region.bbox() == (116.0, 39.0, 119.0, 41.0)
+    lng, lat = region.centroid()
+    assert 117.0 < lng < 118.0 and 39.5 < lat < 40.5
+    assert region.min_cell().code == GeoSotCell.from_coords(116.5, 39.5, 9).code
+    assert region.max_cell().code == GeoSotCell.from_coords(118.5, 40.5, 9).code
+    ancestor = region.covering_cell()
+    assert all(ancestor.is_ancestor_of(c) for c in region.cells())
+    wrapped = GeoSotRegion(9)
+    wrapped.add_point(179.5, 10.5)
+    wrapped.add_point(-179.5, 10.5)
+    assert wrapped.bbox() == (179.0, 10.0, -179.0, 11.0)
+    assert abs(abs(wrapped.centroid()[0]) - 180.0) < 1e-6
+    globe = wrapped.covering_cell()
+    assert globe.level == 0 and globe.code == 0
+    assert all(globe.is_ancestor_of(c) for c in wrapped.cells())
+
+def test_cell_distance():
+    a = GeoSotCell.from_coords(116.5, 39.5, 9)
+    b = GeoSotCell.from_coords(118.5, 39.5, 9)
+    north = GeoSotCell.from_coords(116.5, 41.5, 9)
+    d = a.distance_m(b)
+    assert 170_000 < d < 175_000
+    assert abs(a.vincenty_distance_m(b) - d) / d < 0.005
+    assert a.distance_m(a) == 0.0
+    assert abs(a.bearing_to(north)) < 1e-9
+    assert abs(north.bearing_to(a) - 180.0) < 1e-9
+    assert a.grid_distance(b) == 2
+    assert a.grid_distance(north) == 2
+    assert a.grid_distance(GeoSotCell.from_coords(116.5, 39.5, 10)) is None
+    west = GeoSotCell.from_coords(179.5, 0.5, 9)
+    east = GeoSotCell.from_coords(-178.5, 0.5, 9)
+    assert west.grid_distance(east) == 2
+
+def test_common_ancestor():
+    a = GeoSotCell.from_coords(116.397, 39.916, 20)
+    b = GeoSotCell.from_coords(116.405, 39.920, 20)
+    ancestor = a.common_ancestor(b)
+    assert ancestor.is_ancestor_of(a) and ancestor.is_ancestor_of(b)
+    assert a.hierarchy_distance(b) == 20 - ancestor.level
+    assert a.common_ancestor(a).code == a.code and a.hierarchy_distance(a) == 0
+    parent = a.parent()
+    assert a.common_ancestor(parent).code == parent.code and a.hierarchy_distance(parent) == 1
+    east = GeoSotCell.from_coords(0.5, 0.5, 9)
+    west = GeoSotCell.from_coords(-0.5, 0.5, 9)
+    assert east.common_ancestor(west) is None and east.hierarchy_distance(west) is None
 
 if __name__ == "__main__":
     test_geosot_cell()
     test_geosot_region()
     test_spatial_analysis()
+    test_bitmap_storage()
+    test_geojson_export()
+    test_geometry_import()
+    test_binary_format()
+    test_geohash()
+    test_tiles()
+    test_mgrs()
+    test_dms()
+    test_csv_encoding()
+    test_index()
+    test_aggregation()
+    test_grid()
+    test_focal()
+    test_morphology()
+    test_distance_transform()
+    test_topology()
+    test_extent()
+    test_cell_distance()
+    test_common_ancestor()
     print("All spatial tests passed!")